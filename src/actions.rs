@@ -1,16 +1,668 @@
+use crate::actions::archive_action::{CompressDialog, CompressDialogResult};
+use crate::actions::file_operation::FileOperation;
+use crate::actions::git_action::{DiscardConfirmation, DiscardConfirmationResult, FileDiff};
+use crate::actions::properties_action::{PropertiesDialog, PropertiesDialogResult};
+use crate::actions::rename_action::RenameAction;
 use crate::actions::select_action::SelectAction;
+use crate::actions::transfer_action::{
+    FileClipboard, TransferConfirmation, TransferConfirmationResult, TrashConfirmation,
+    TrashConfirmationResult,
+};
+use crate::actions::user_action::UserActionRun;
+use crate::archive_manager;
+use crate::config_manager::Config;
+use crate::error_manager::{AppError, ErrorManager, Retry};
+use crate::git_manager;
+use crate::job_manager::JobManager;
+use crate::lang_string::{LangKeys, LangString};
+use crate::mime_manager::MimeManager;
+use crate::transfer_manager;
+use crate::transfer_manager::TransferMode;
+use crate::user_actions;
+use crate::user_actions::UserAction;
+use crate::vfs::{Vfs, VfsRouter};
+use egui::Context;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
+pub mod archive_action;
+pub mod background_action;
 mod copy_action;
+pub mod file_operation;
+pub mod git_action;
+pub mod properties_action;
+pub mod rename_action;
 pub mod select_action;
+pub mod transfer_action;
+#[cfg(unix)]
+pub mod unix_properties;
+pub mod user_action;
 
 pub struct Actions {
     pub select_action: SelectAction,
+
+    /// Operations running in the background and the history of the finished ones.
+    pub job_manager: JobManager,
+
+    /// User actions run in the background whose modal has not been closed yet.
+    pub user_action_runs: Vec<UserActionRun>,
+
+    /// Command of a user action to type in the terminal panel, taken by the app.
+    pub terminal_command: Option<String>,
+
+    /// Options window shown before a compression starts.
+    pub compress_dialog: Option<CompressDialog>,
+
+    /// Dropped files waiting for the user to confirm the move.
+    pub transfer_confirmation: Option<TransferConfirmation>,
+
+    /// Files waiting for the user to confirm they go to the trash.
+    pub trash_confirmation: Option<TrashConfirmation>,
+
+    /// Operations run on the next render, where the VFS is available.
+    pending_operations: Vec<FileOperation>,
+
+    /// File whose name is being edited inside the central panel.
+    pub rename_action: Option<RenameAction>,
+
+    pub properties_dialog: Option<PropertiesDialog>,
+
+    /// Files whose unstaged changes are discarded once the user confirms it.
+    pub discard_confirmation: Option<DiscardConfirmation>,
+
+    /// Changes of a file, shown in the preview panel while the file is selected.
+    pub file_diff: Option<FileDiff>,
+
+    /// Files copied or cut with Copy/Cut, until they are pasted.
+    pub clipboard: Option<FileClipboard>,
+
+    /// Used to give every action modal its own egui Id.
+    next_action_id: usize,
 }
 
 impl Actions {
     pub fn new() -> Self {
         Self {
             select_action: SelectAction::new(),
+            job_manager: JobManager::new(),
+            user_action_runs: Vec::new(),
+            terminal_command: None,
+            compress_dialog: None,
+            transfer_confirmation: None,
+            trash_confirmation: None,
+            pending_operations: Vec::new(),
+            rename_action: None,
+            properties_dialog: None,
+            discard_confirmation: None,
+            file_diff: None,
+            clipboard: None,
+            next_action_id: 0,
+        }
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.next_action_id += 1;
+        self.next_action_id
+    }
+
+    /// Extracts `selection` (paths inside `archive`, empty for everything) into `destination`.
+    pub fn extract(
+        &mut self,
+        lang_string: &LangString,
+        archive: &Path,
+        selection: Vec<PathBuf>,
+        destination: &Path,
+    ) {
+        let id = self.next_id();
+        self.job_manager.push(archive_action::extract_action(
+            id,
+            lang_string,
+            archive,
+            selection,
+            destination,
+        ));
+    }
+
+    /// Extracts a single archive entry into a private directory and opens it.
+    pub fn extract_and_open(
+        &mut self,
+        lang_string: &LangString,
+        archive: &Path,
+        entry: &Path,
+    ) -> io::Result<()> {
+        let Some(entry_name) = entry.file_name() else {
+            return Ok(());
+        };
+        let destination = archive_manager::open_directory(archive)?;
+        let file = destination.join(entry_name);
+
+        // Extraction never overwrites, the copy of a previous opening would be opened instead
+        match fs::remove_file(&file) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        let id = self.next_id();
+        self.job_manager.push(
+            archive_action::extract_action(
+                id,
+                lang_string,
+                archive,
+                vec![entry.to_path_buf()],
+                &destination,
+            )
+            .open_when_done(file),
+        );
+        Ok(())
+    }
+
+    /// Downloads a file of a server in the background, then opens the local copy.
+    pub fn download_and_open(
+        &mut self,
+        lang_string: &LangString,
+        vfs: &VfsRouter,
+        file: &Path,
+    ) -> io::Result<()> {
+        let Some(file_name) = file.file_name() else {
+            return Ok(());
+        };
+        let destination = archive_manager::open_directory(file)?;
+        let copy = destination.join(file_name);
+
+        // The transfer would pick another name next to the copy of a previous opening
+        match fs::remove_file(&copy) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        let id = self.next_id();
+        self.job_manager.push(
+            transfer_action::transfer_action(
+                id,
+                lang_string,
+                vfs,
+                vec![file.to_path_buf()],
+                &destination,
+                TransferMode::Copy,
+            )
+            .open_when_done(copy),
+        );
+        Ok(())
+    }
+
+    pub fn open_compress_dialog(&mut self, files: Vec<PathBuf>, directory: &Path) {
+        self.compress_dialog = Some(CompressDialog::new(files, directory));
+    }
+
+    /// Copies, moves or links `files` inside the `destination` folder, in the background.
+    /// Transfers involving another backend (e.g. a remote server) go through the VFS.
+    pub fn transfer(
+        &mut self,
+        lang_string: &LangString,
+        vfs: &VfsRouter,
+        files: Vec<PathBuf>,
+        destination: &Path,
+        mode: TransferMode,
+    ) {
+        let id = self.next_id();
+        self.job_manager.push(transfer_action::transfer_action(
+            id,
+            lang_string,
+            vfs,
+            files,
+            destination,
+            mode,
+        ));
+    }
+
+    /// Puts the selected files inside the clipboard, they are copied or moved on paste.
+    pub fn set_clipboard(&mut self, mode: TransferMode) {
+        if self.select_action.files.is_empty() {
+            return;
+        }
+
+        self.clipboard = Some(FileClipboard {
+            files: self.select_action.files.iter().cloned().collect(),
+            mode,
+        });
+    }
+
+    /// Transfers the clipboard content inside `destination`.
+    /// Cut files can only be pasted once.
+    pub fn paste(&mut self, destination: &Path) {
+        let Some(clipboard) = &self.clipboard else {
+            return;
+        };
+
+        let files = clipboard.files.clone();
+        let mode = clipboard.mode;
+        if mode == TransferMode::Move {
+            self.clipboard = None;
+        }
+
+        self.queue(FileOperation::Transfer {
+            files,
+            destination: destination.to_path_buf(),
+            mode,
+        });
+    }
+
+    /// Asks the user before running the transfer.
+    pub fn confirm_transfer(
+        &mut self,
+        files: Vec<PathBuf>,
+        destination: &Path,
+        mode: TransferMode,
+    ) {
+        self.transfer_confirmation = Some(TransferConfirmation {
+            files,
+            destination: destination.to_path_buf(),
+            mode,
+        });
+    }
+
+    /// Transfers files dropped on `destination`, moves are confirmed first if `confirm_moves`.
+    pub fn drop_files(
+        &mut self,
+        files: Vec<PathBuf>,
+        destination: &Path,
+        mode: TransferMode,
+        confirm_moves: bool,
+    ) {
+        if confirm_moves && mode == TransferMode::Move {
+            self.confirm_transfer(files, destination, mode);
+            return;
+        }
+
+        self.pending_operations.push(FileOperation::Transfer {
+            files,
+            destination: destination.to_path_buf(),
+            mode,
+        });
+    }
+
+    pub fn queue(&mut self, operation: FileOperation) {
+        self.pending_operations.push(operation);
+    }
+
+    /// Moves the selected files to the trash, the user confirms it first if `confirm`.
+    /// Only files of the local disk have a trash, others (e.g. on a server) are deleted
+    /// for good, which is always confirmed. Archives are read-only.
+    pub fn trash_selection(&mut self, vfs: &VfsRouter, confirm: bool) {
+        let mut files: Vec<PathBuf> = self.select_action.files.iter().cloned().collect();
+        let trashable = files.iter().all(|file| vfs.is_local(file));
+
+        if files.is_empty() || files.iter().any(|file| vfs.archive(file).is_some()) {
+            return;
+        }
+
+        if confirm || !trashable {
+            files.sort();
+            self.trash_confirmation = Some(TrashConfirmation {
+                files,
+                permanent: !trashable,
+            });
+        } else {
+            self.queue(FileOperation::Trash(files));
+        }
+    }
+
+    /// Renames the selected file, archives are read-only.
+    pub fn rename_selection(&mut self, vfs: &VfsRouter) {
+        if self.select_action.files.len() != 1 {
+            return;
+        }
+
+        if let Some(file) = self.select_action.files.iter().next().cloned()
+            && vfs.archive(&file).is_none()
+        {
+            self.start_rename(&file);
+        }
+    }
+
+    pub fn is_renaming(&self, file: &Path) -> bool {
+        self.rename_action
+            .as_ref()
+            .is_some_and(|rename_action| rename_action.file == file)
+    }
+
+    pub fn start_rename(&mut self, file: &Path) {
+        self.rename_action = Some(RenameAction::new(file));
+    }
+
+    /// Renames the file to the typed name, if it changed.
+    pub fn finish_rename(&mut self) {
+        let Some(rename_action) = self.rename_action.take() else {
+            return;
+        };
+
+        if let Some(target) = rename_action.target() {
+            self.queue(FileOperation::Rename {
+                from: rename_action.file,
+                to: target,
+            });
         }
     }
+
+    /// Creates "New Folder" (or "New Folder (2)"...) inside `directory`, then renames it.
+    pub fn new_folder(&mut self, lang_string: &LangString, vfs: &VfsRouter, directory: &Path) {
+        let path = vfs.unused_path(directory, &lang_string.get(LangKeys::NewFolder));
+        self.queue(FileOperation::CreateFolder(path));
+    }
+
+    /// Creates an empty "New File" inside `directory`, then renames it.
+    pub fn new_file(&mut self, lang_string: &LangString, vfs: &VfsRouter, directory: &Path) {
+        let path = vfs.unused_path(directory, &lang_string.get(LangKeys::NewFile));
+        self.queue(FileOperation::CreateFile(path));
+    }
+
+    /// Copies `template` inside `directory`, then renames the copy.
+    pub fn new_from_template(&mut self, vfs: &VfsRouter, template: &Path, directory: &Path) {
+        let Some(file_name) = template.file_name() else {
+            return;
+        };
+
+        let destination = vfs.unused_path(directory, &file_name.to_string_lossy());
+        self.queue(FileOperation::CreateFromTemplate {
+            template: template.to_path_buf(),
+            destination,
+        });
+    }
+
+    /// Links to the selected local files, hard links only apply to files.
+    pub fn create_links(&mut self, vfs: &VfsRouter, hard: bool) {
+        let files: Vec<PathBuf> = self.select_action.files.iter().cloned().collect();
+        let linkable = files
+            .iter()
+            .all(|file| vfs.is_local(file) && !(hard && vfs.is_dir(file)));
+
+        if !files.is_empty() && linkable {
+            self.queue(FileOperation::CreateLinks { files, hard });
+        }
+    }
+
+    /// Folder to open to show the target of `link`.
+    /// A file target gets the keyboard cursor once its folder is listed.
+    pub fn go_to_link_target(&mut self, vfs: &VfsRouter, link: &Path) -> Option<PathBuf> {
+        let target = vfs.link_target(link)?;
+        if vfs.is_dir(&target) {
+            return Some(target);
+        }
+
+        let parent = target.parent()?.to_path_buf();
+        self.select_action.cursor = Some(target);
+        self.select_action.key_select.scroll_to_widget = true;
+
+        Some(parent)
+    }
+
+    pub fn open_properties(
+        &mut self,
+        files: Vec<PathBuf>,
+        vfs: &VfsRouter,
+        mime_manager: &MimeManager,
+    ) {
+        if !files.is_empty() {
+            self.properties_dialog = Some(PropertiesDialog::new(files, vfs, mime_manager));
+        }
+    }
+
+    /// Runs `user_action` on `files`, in the terminal panel or in the background.
+    pub fn run_user_action(
+        &mut self,
+        user_action: &UserAction,
+        files: &[PathBuf],
+        directory: &Path,
+    ) {
+        let command = user_actions::expand(&user_action.command, files, directory);
+        if user_action.in_terminal {
+            self.terminal_command = Some(command);
+            return;
+        }
+
+        let id = self.next_id();
+        self.user_action_runs.push(UserActionRun::spawn(
+            id,
+            user_action.name.clone(),
+            command,
+            directory,
+        ));
+    }
+
+    /// Asks the user before discarding the changes of `files`.
+    pub fn confirm_discard(&mut self, files: Vec<PathBuf>) {
+        if !files.is_empty() {
+            self.discard_confirmation = Some(DiscardConfirmation { files });
+        }
+    }
+
+    pub fn show_diff(&mut self, file: &Path) -> Result<(), git2::Error> {
+        self.file_diff = Some(FileDiff::new(file.to_path_buf())?);
+        Ok(())
+    }
+
+    fn run_operation(
+        &mut self,
+        lang_string: &LangString,
+        vfs: &VfsRouter,
+        operation: FileOperation,
+    ) -> io::Result<()> {
+        match operation {
+            FileOperation::Transfer {
+                files,
+                destination,
+                mode,
+            } => {
+                self.transfer(lang_string, vfs, files, &destination, mode);
+                self.select_action.clear_selection();
+            }
+            FileOperation::Rename { from, to } => {
+                if vfs.stat(&to).is_ok() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{:?} already exists", to),
+                    ));
+                }
+
+                vfs.rename(&from, &to)?;
+                self.select_action.clear_selection();
+                self.select_action.select_file(&to, None);
+            }
+            FileOperation::Trash(files) => {
+                let id = self.next_id();
+                self.job_manager
+                    .push(transfer_action::trash_action(id, lang_string, files));
+            }
+            FileOperation::Delete(files) => {
+                let id = self.next_id();
+                self.job_manager
+                    .push(transfer_action::delete_action(id, lang_string, vfs, files));
+            }
+            FileOperation::CreateFolder(path) => {
+                vfs.create_dir(&path)?;
+                self.created(&path);
+            }
+            FileOperation::CreateFile(path) => {
+                vfs.create(&path)?;
+                self.created(&path);
+            }
+            FileOperation::CreateFromTemplate {
+                template,
+                destination,
+            } => {
+                vfs.copy(&template, &destination)?;
+                self.created(&destination);
+            }
+            FileOperation::CreateLinks { files, hard } => {
+                let mut links = Vec::new();
+                for file in &files {
+                    let (Some(parent), Some(file_name)) = (file.parent(), file.file_name()) else {
+                        continue;
+                    };
+                    let name = lang_string.format(
+                        LangKeys::LinkTo,
+                        &[("name", file_name.to_string_lossy().into_owned().into())],
+                    );
+                    let link = vfs.unused_path(parent, &name);
+
+                    if hard {
+                        fs::hard_link(file, &link)?;
+                    } else {
+                        // Relative to the folder, the link survives moving it
+                        transfer_manager::symlink(Path::new(file_name), &link)?;
+                    }
+                    links.push(link);
+                }
+
+                if let [link] = links.as_slice() {
+                    self.created(link);
+                } else {
+                    self.select_action.clear_selection();
+                    self.select_action.files.extend(links);
+                }
+            }
+            FileOperation::GitStage(files) => {
+                git_manager::stage(&files).map_err(io::Error::other)?;
+            }
+            FileOperation::GitUnstage(files) => {
+                git_manager::unstage(&files).map_err(io::Error::other)?;
+            }
+            FileOperation::GitDiscard(files) => {
+                git_manager::discard(&files).map_err(io::Error::other)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Selects a newly created file and lets the user name it.
+    fn created(&mut self, path: &Path) {
+        self.select_action.clear_selection();
+        self.select_action.select_file(&path.to_path_buf(), None);
+        self.select_action.key_select.scroll_to_widget = true;
+        self.start_rename(path);
+    }
+
+    /// Runs the queued operations and renders every action modal, failures go to `error_manager`.
+    /// Returns true if an action finished or failed this frame (the directory content may have changed).
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        lang_string: &LangString,
+        config: &Config,
+        vfs: &VfsRouter,
+        error_manager: &mut ErrorManager,
+    ) -> bool {
+        if let Some(compress_dialog) = &mut self.compress_dialog {
+            match compress_dialog.render(ctx, lang_string) {
+                CompressDialogResult::Pending => {}
+                CompressDialogResult::Cancelled => self.compress_dialog = None,
+                CompressDialogResult::Confirmed => {
+                    if let Some(dialog) = self.compress_dialog.take()
+                        && let Some(destination) = dialog.destination(vfs)
+                    {
+                        let id = self.next_id();
+                        self.job_manager.push(archive_action::compress_action(
+                            id,
+                            lang_string,
+                            dialog.files.clone(),
+                            &destination,
+                            dialog.kind,
+                            dialog.level,
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut finished = self.job_manager.poll(error_manager);
+        if finished {
+            self.select_action.remove_deleted_files(vfs);
+        }
+        self.job_manager.render(ctx, lang_string, config);
+
+        // Commands may have created or changed files
+        for user_action_run in &mut self.user_action_runs {
+            finished |= user_action_run.poll();
+            user_action_run.render(ctx, lang_string);
+        }
+        self.user_action_runs.retain(|run| run.visible);
+
+        // A failing operation does not stop the ones queued after it
+        for operation in std::mem::take(&mut self.pending_operations) {
+            let retry = operation.clone();
+            if let Err(err) = self.run_operation(lang_string, vfs, operation) {
+                let mut error = AppError::io(retry.error_title(), &err);
+                if let Some(path) = retry.error_path() {
+                    error = error.path(path);
+                }
+                error_manager.report(error.retry(Retry::Operation(retry)));
+            }
+            finished = true;
+        }
+
+        if let Some(properties_dialog) = &mut self.properties_dialog {
+            match properties_dialog.render(ctx, lang_string, config) {
+                PropertiesDialogResult::Open => {}
+                PropertiesDialogResult::Closed => self.properties_dialog = None,
+                #[cfg(unix)]
+                PropertiesDialogResult::Apply(change) => {
+                    let id = self.next_id();
+                    self.job_manager.push(properties_action::permissions_action(
+                        id,
+                        lang_string,
+                        change,
+                    ));
+                    self.properties_dialog = None;
+                }
+            }
+        }
+
+        if let Some(transfer_confirmation) = &self.transfer_confirmation {
+            match transfer_confirmation.render(ctx, lang_string) {
+                TransferConfirmationResult::Pending => {}
+                TransferConfirmationResult::Cancelled => self.transfer_confirmation = None,
+                TransferConfirmationResult::Confirmed => {
+                    if let Some(confirmation) = self.transfer_confirmation.take() {
+                        self.queue(FileOperation::Transfer {
+                            files: confirmation.files,
+                            destination: confirmation.destination,
+                            mode: confirmation.mode,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(trash_confirmation) = &self.trash_confirmation {
+            match trash_confirmation.render(ctx, lang_string) {
+                TrashConfirmationResult::Pending => {}
+                TrashConfirmationResult::Cancelled => self.trash_confirmation = None,
+                TrashConfirmationResult::Confirmed => {
+                    if let Some(confirmation) = self.trash_confirmation.take() {
+                        self.queue(if confirmation.permanent {
+                            FileOperation::Delete(confirmation.files)
+                        } else {
+                            FileOperation::Trash(confirmation.files)
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(discard_confirmation) = &self.discard_confirmation {
+            match discard_confirmation.render(ctx, lang_string) {
+                DiscardConfirmationResult::Pending => {}
+                DiscardConfirmationResult::Cancelled => self.discard_confirmation = None,
+                DiscardConfirmationResult::Confirmed => {
+                    if let Some(confirmation) = self.discard_confirmation.take() {
+                        self.queue(FileOperation::GitDiscard(confirmation.files));
+                    }
+                }
+            }
+        }
+
+        finished
+    }
 }
@@ -0,0 +1,189 @@
+use crate::actions::background_action::BackgroundAction;
+use crate::actions::rename_action;
+use crate::archive_manager;
+use crate::archive_manager::{ArchiveKind, CompressionLevel};
+use crate::lang_string::{LangKeys, LangString};
+use crate::vfs::{Vfs, VfsRouter};
+use egui::{ComboBox, Context, Id, TextEdit, Window};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Drag and drop payload: entries of `archive` dragged out of the archive listing.
+pub struct ArchiveEntriesPayload {
+    pub archive: PathBuf,
+    pub entries: Vec<PathBuf>,
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Extracts `selection` (paths inside `archive`, empty for everything) into `destination`.
+pub fn extract_action(
+    id: usize,
+    lang_string: &LangString,
+    archive: &Path,
+    selection: Vec<PathBuf>,
+    destination: &Path,
+) -> BackgroundAction {
+    let title = lang_string.format(
+        LangKeys::Extracting,
+        &[("name", file_name_of(archive).into())],
+    );
+    let archive = archive.to_path_buf();
+    let destination = destination.to_path_buf();
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        archive_manager::extract(&archive, &selection, &destination, sender)
+    })
+}
+
+pub fn compress_action(
+    id: usize,
+    lang_string: &LangString,
+    files: Vec<PathBuf>,
+    destination: &Path,
+    kind: ArchiveKind,
+    level: CompressionLevel,
+) -> BackgroundAction {
+    let title = lang_string.format(
+        LangKeys::Compressing,
+        &[("name", file_name_of(destination).into())],
+    );
+    let destination = destination.to_path_buf();
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        let result = archive_manager::create(&files, &destination, kind, level, sender);
+        // A failed or cancelled compression leaves no broken archive behind
+        if result.is_err() {
+            let _ = fs::remove_file(&destination);
+        }
+
+        result
+    })
+}
+
+/// Options chosen by the user before a compression starts.
+pub struct CompressDialog {
+    pub files: Vec<PathBuf>,
+
+    /// Directory where the archive is created.
+    pub directory: PathBuf,
+    pub name: String,
+    pub kind: ArchiveKind,
+    pub level: CompressionLevel,
+}
+
+pub enum CompressDialogResult {
+    Pending,
+    Cancelled,
+    Confirmed,
+}
+
+impl CompressDialog {
+    pub fn new(files: Vec<PathBuf>, directory: &Path) -> Self {
+        // Single selections are named after the file, otherwise after the directory
+        let name = match files.as_slice() {
+            [file] => file.file_stem(),
+            _ => directory.file_name(),
+        }
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("archive"));
+
+        Self {
+            files,
+            directory: directory.to_path_buf(),
+            name,
+            kind: ArchiveKind::Zip,
+            level: CompressionLevel::Normal,
+        }
+    }
+
+    /// Can the typed name be used as a file name inside `directory`?
+    fn is_name_valid(&self) -> bool {
+        rename_action::is_file_name_valid(self.name.trim())
+    }
+
+    /// Path of the archive to create, None if the name is invalid.
+    /// An existing file is never overwritten, the archive gets a free name next to it.
+    pub fn destination(&self, vfs: &VfsRouter) -> Option<PathBuf> {
+        if !self.is_name_valid() {
+            return None;
+        }
+
+        let file_name = format!("{}.{}", self.name.trim(), self.kind.extension());
+        Some(vfs.unused_path(&self.directory, &file_name))
+    }
+
+    pub fn render(&mut self, ctx: &Context, lang_string: &LangString) -> CompressDialogResult {
+        let mut result = CompressDialogResult::Pending;
+
+        Window::new(lang_string.get(LangKeys::Compress))
+            .id(Id::new("compress_dialog"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("compress_dialog_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label(lang_string.get(LangKeys::ArchiveName));
+                        ui.add(TextEdit::singleline(&mut self.name));
+                        ui.end_row();
+
+                        ui.label(lang_string.get(LangKeys::ArchiveFormat));
+                        ComboBox::from_id_salt("compress_dialog_kind")
+                            .selected_text(self.kind.extension())
+                            .show_ui(ui, |ui| {
+                                for kind in ArchiveKind::ALL {
+                                    ui.selectable_value(&mut self.kind, kind, kind.extension());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(lang_string.get(LangKeys::CompressionLevel));
+                        ComboBox::from_id_salt("compress_dialog_level")
+                            .selected_text(compression_level_name(lang_string, self.level))
+                            .show_ui(ui, |ui| {
+                                for level in CompressionLevel::ALL {
+                                    ui.selectable_value(
+                                        &mut self.level,
+                                        level,
+                                        compression_level_name(lang_string, level),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            self.is_name_valid(),
+                            egui::Button::new(lang_string.get(LangKeys::Create)),
+                        )
+                        .clicked()
+                    {
+                        result = CompressDialogResult::Confirmed;
+                    }
+
+                    if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                        result = CompressDialogResult::Cancelled;
+                    }
+                });
+            });
+
+        result
+    }
+}
+
+fn compression_level_name(lang_string: &LangString, level: CompressionLevel) -> String {
+    lang_string.get(match level {
+        CompressionLevel::Store => LangKeys::LevelStore,
+        CompressionLevel::Fast => LangKeys::LevelFast,
+        CompressionLevel::Normal => LangKeys::LevelNormal,
+        CompressionLevel::Best => LangKeys::LevelBest,
+    })
+}
@@ -0,0 +1,294 @@
+use crate::config_manager::Config;
+use crate::job_manager::{JobOutcome, JobRecord, Speedometer, format_duration};
+use crate::lang_string::{LangKeys, LangString};
+use crate::progress::{JobControl, Progress, ProgressSender};
+use crate::ui::additional_info_panel::convert_bytes_size_to_human;
+use egui::{Context, Id, ProgressBar, Ui, Window};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// An operation running on its own thread (extraction, copy, move...),
+/// rendered as its own modal so several of them can run at the same time.
+pub struct BackgroundAction {
+    id: usize,
+
+    /// Displayed as the title of the modal.
+    pub title: String,
+    receiver: Receiver<Progress>,
+    control: Arc<JobControl>,
+
+    total_bytes: u64,
+    done_bytes: u64,
+    current_file: Option<PathBuf>,
+
+    /// Progress is counted in files instead of bytes (e.g. permission changes).
+    counts_items: bool,
+    speedometer: Speedometer,
+    started: Instant,
+
+    /// None while the background thread is still working.
+    outcome: Option<JobOutcome>,
+
+    /// When the action finished and how long it took.
+    finished: Option<(SystemTime, Duration)>,
+
+    /// File to open with the default application once the action succeeds.
+    open_when_done: Option<PathBuf>,
+
+    /// The modal can be hidden while the action runs, it stays inside the jobs panel.
+    /// A finished action whose modal is hidden moves to the history.
+    pub visible: bool,
+}
+
+impl BackgroundAction {
+    pub fn spawn<F>(id: usize, title: String, work: F) -> Self
+    where
+        F: FnOnce(&ProgressSender) -> std::io::Result<()> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let control = Arc::new(JobControl::default());
+        let progress = ProgressSender::new(sender, control.clone());
+
+        thread::spawn(move || {
+            progress.finished(work(&progress));
+        });
+
+        Self {
+            id,
+            title,
+            receiver,
+            control,
+            total_bytes: 0,
+            done_bytes: 0,
+            current_file: None,
+            counts_items: false,
+            speedometer: Speedometer::new(),
+            started: Instant::now(),
+            outcome: None,
+            finished: None,
+            open_when_done: None,
+            visible: true,
+        }
+    }
+
+    pub fn open_when_done(mut self, file: PathBuf) -> Self {
+        self.open_when_done = Some(file);
+        self
+    }
+
+    pub fn counting_items(mut self) -> Self {
+        self.counts_items = true;
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Error of the action once it has failed.
+    pub fn error(&self) -> Option<&str> {
+        match &self.outcome {
+            Some(JobOutcome::Failed(err)) => Some(err),
+            _ => None,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        !self.is_finished() && self.control.is_paused()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.control.set_paused(paused);
+        // The speed before the pause says nothing about the speed after it
+        self.speedometer.reset();
+    }
+
+    /// The thread stops at its next progress report.
+    pub fn cancel(&self) {
+        self.control.cancel();
+    }
+
+    /// Drains the progress channel. Returns true on the frame the action finishes.
+    pub fn poll(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Progress::Total(total) => self.total_bytes = total,
+                Progress::Advance { bytes, file } => {
+                    self.done_bytes += bytes;
+                    self.current_file = Some(file);
+                }
+                Progress::Finished(result) => {
+                    // A job cancelled after its last checkpoint still finished its work
+                    self.outcome = Some(match result {
+                        Err(err)
+                            if err.kind() == io::ErrorKind::Interrupted
+                                && self.control.is_cancelled() =>
+                        {
+                            JobOutcome::Cancelled
+                        }
+                        Err(err) => JobOutcome::Failed(err.to_string()),
+                        Ok(()) => match self.open_when_done.take().map(opener::open) {
+                            Some(Err(err)) => JobOutcome::Failed(err.to_string()),
+                            _ => JobOutcome::Done,
+                        },
+                    });
+                    self.finished = Some((SystemTime::now(), self.started.elapsed()));
+                    return true;
+                }
+            }
+        }
+
+        if !self.control.is_paused() {
+            self.speedometer.push(Instant::now(), self.done_bytes);
+        }
+
+        false
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            if self.is_finished() { 1.0 } else { 0.0 }
+        } else {
+            self.done_bytes as f32 / self.total_bytes as f32
+        }
+    }
+
+    /// Done and total amounts, e.g. "1.2 MiB / 4.0 MiB" or "12 / 40".
+    fn amount_text(&self, config: &Config) -> String {
+        if self.counts_items {
+            return format!("{} / {}", self.done_bytes, self.total_bytes);
+        }
+
+        format!(
+            "{} / {}",
+            convert_bytes_size_to_human(self.done_bytes, config.size_units),
+            convert_bytes_size_to_human(self.total_bytes, config.size_units)
+        )
+    }
+
+    /// Speed and remaining time, None until enough progress was seen.
+    fn speed_text(&self, lang_string: &LangString, config: &Config) -> Option<String> {
+        let per_second = self.speedometer.per_second()?;
+        let speed = if self.counts_items {
+            format!("{:.0}/s", per_second)
+        } else {
+            format!(
+                "{}/s",
+                convert_bytes_size_to_human(per_second as u64, config.size_units)
+            )
+        };
+
+        match Speedometer::eta(self.total_bytes.saturating_sub(self.done_bytes), per_second) {
+            Some(eta) => Some(lang_string.format(
+                LangKeys::JobSpeed,
+                &[
+                    ("speed", speed.into()),
+                    ("eta", format_duration(eta).into()),
+                ],
+            )),
+            None => Some(speed),
+        }
+    }
+
+    /// Progress bar, state and details, shared by the modal and the jobs panel.
+    pub fn progress_ui(&self, ui: &mut Ui, lang_string: &LangString, config: &Config) {
+        ui.add(ProgressBar::new(self.fraction()).show_percentage());
+
+        match &self.outcome {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.weak(self.amount_text(config));
+                    if self.is_paused() {
+                        ui.weak(lang_string.get(LangKeys::JobPaused));
+                    } else if let Some(speed) = self.speed_text(lang_string, config) {
+                        ui.weak(speed);
+                    }
+                });
+                if let Some(file) = &self.current_file {
+                    ui.small(file.to_string_lossy());
+                }
+            }
+            Some(JobOutcome::Done) => {
+                ui.label(lang_string.get(LangKeys::ActionDone));
+            }
+            Some(JobOutcome::Cancelled) => {
+                ui.label(lang_string.get(LangKeys::JobCancelled));
+            }
+            Some(JobOutcome::Failed(err)) => {
+                ui.colored_label(ui.visuals().error_fg_color, err);
+            }
+        }
+    }
+
+    /// Pause/Resume and Cancel while running.
+    pub fn controls_ui(&mut self, ui: &mut Ui, lang_string: &LangString) {
+        if self.is_finished() {
+            return;
+        }
+
+        let paused = self.is_paused();
+        let pause_key = if paused {
+            LangKeys::JobResume
+        } else {
+            LangKeys::JobPause
+        };
+        if ui.button(lang_string.get(pause_key)).clicked() {
+            self.set_paused(!paused);
+        }
+        if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+            self.cancel();
+        }
+    }
+
+    pub fn render(&mut self, ctx: &Context, lang_string: &LangString, config: &Config) {
+        Window::new(&self.title)
+            .id(Id::new(("background_action", self.id)))
+            .collapsible(true)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.vertical_centered_justified(|ui| {
+                    self.progress_ui(ui, lang_string, config);
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    self.controls_ui(ui, lang_string);
+
+                    if self.is_finished() {
+                        if ui.button(lang_string.get(LangKeys::Close)).clicked() {
+                            self.visible = false;
+                        }
+                    } else if ui
+                        .button(lang_string.get(LangKeys::RunInBackground))
+                        .on_hover_text(lang_string.get(LangKeys::RunInBackgroundHint))
+                        .clicked()
+                    {
+                        self.visible = false;
+                    }
+                });
+            });
+    }
+
+    /// Entry of the history, once the action has finished.
+    pub fn record(&self, config: &Config) -> Option<JobRecord> {
+        let outcome = self.outcome.clone()?;
+        let (finished_at, elapsed) = self.finished?;
+
+        Some(JobRecord {
+            title: self.title.clone(),
+            outcome,
+            finished_at,
+            elapsed,
+            amount: self.amount_text(config),
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use crate::lang_string::LangKeys;
+use crate::transfer_manager::TransferMode;
+use std::path::{Path, PathBuf};
+
+/// Operation requested from a panel, run by `Actions::render` where the VFS
+/// is available and the directory content is refreshed afterwards.
+#[derive(Clone)]
+pub enum FileOperation {
+    Transfer {
+        files: Vec<PathBuf>,
+        destination: PathBuf,
+        mode: TransferMode,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+
+    /// Moves local files to the trash of the desktop.
+    Trash(Vec<PathBuf>),
+
+    /// Deletes files for good, on backends without a trash (e.g. a server).
+    Delete(Vec<PathBuf>),
+
+    /// Creates an empty folder and starts renaming it.
+    CreateFolder(PathBuf),
+
+    /// Creates an empty file and starts renaming it.
+    CreateFile(PathBuf),
+
+    /// Copies a template and starts renaming the copy.
+    CreateFromTemplate {
+        template: PathBuf,
+        destination: PathBuf,
+    },
+
+    /// Creates a link to each file next to it, hard links instead of symbolic ones if `hard`.
+    CreateLinks {
+        files: Vec<PathBuf>,
+        hard: bool,
+    },
+
+    /// Adds the changes of files of a git work tree to the index.
+    GitStage(Vec<PathBuf>),
+
+    /// Removes the staged changes of files from the index, the files are not touched.
+    GitUnstage(Vec<PathBuf>),
+
+    /// Restores the staged content of modified files.
+    GitDiscard(Vec<PathBuf>),
+}
+
+impl FileOperation {
+    /// File named by the error when the operation fails, None for several files.
+    pub fn error_path(&self) -> Option<&Path> {
+        match self {
+            FileOperation::Transfer { destination, .. } => Some(destination),
+            FileOperation::Rename { from, .. } => Some(from),
+            FileOperation::CreateFolder(path) | FileOperation::CreateFile(path) => Some(path),
+            FileOperation::CreateFromTemplate { destination, .. } => Some(destination),
+            FileOperation::Trash(files)
+            | FileOperation::Delete(files)
+            | FileOperation::CreateLinks { files, .. }
+            | FileOperation::GitStage(files)
+            | FileOperation::GitUnstage(files)
+            | FileOperation::GitDiscard(files) => match files.as_slice() {
+                [file] => Some(file),
+                _ => None,
+            },
+        }
+    }
+
+    /// What the error reports as having failed when the operation fails.
+    pub fn error_title(&self) -> LangKeys {
+        match self {
+            FileOperation::Transfer { mode, .. } => match mode {
+                TransferMode::Copy => LangKeys::CopyError,
+                TransferMode::Move => LangKeys::MoveError,
+                TransferMode::Link => LangKeys::LinkError,
+            },
+            FileOperation::Rename { .. } => LangKeys::RenameError,
+            FileOperation::Trash(_) => LangKeys::TrashError,
+            FileOperation::Delete(_) => LangKeys::DeleteError,
+            FileOperation::CreateLinks { .. } => LangKeys::LinkError,
+            FileOperation::GitStage(_)
+            | FileOperation::GitUnstage(_)
+            | FileOperation::GitDiscard(_) => LangKeys::GitError,
+            FileOperation::CreateFolder(_)
+            | FileOperation::CreateFile(_)
+            | FileOperation::CreateFromTemplate { .. } => LangKeys::CreateError,
+        }
+    }
+}
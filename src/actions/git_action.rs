@@ -0,0 +1,103 @@
+use crate::git_manager;
+use crate::git_manager::DiffLine;
+use crate::lang_string::{LangKeys, LangString};
+use egui::{Color32, Context, Id, RichText, ScrollArea, Ui, Window};
+use std::path::PathBuf;
+
+/// Discarding changes can not be undone, the user confirms it first.
+pub struct DiscardConfirmation {
+    pub files: Vec<PathBuf>,
+}
+
+pub enum DiscardConfirmationResult {
+    Pending,
+    Cancelled,
+    Confirmed,
+}
+
+impl DiscardConfirmation {
+    pub fn render(&self, ctx: &Context, lang_string: &LangString) -> DiscardConfirmationResult {
+        let mut result = DiscardConfirmationResult::Pending;
+
+        Window::new(lang_string.get(LangKeys::GitDiscard))
+            .id(Id::new("discard_confirmation"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(lang_string.get(LangKeys::ConfirmGitDiscard));
+                for file in &self.files {
+                    ui.small(file.to_string_lossy());
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(lang_string.get(LangKeys::GitDiscard)).clicked() {
+                        result = DiscardConfirmationResult::Confirmed;
+                    }
+
+                    if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                        result = DiscardConfirmationResult::Cancelled;
+                    }
+                });
+            });
+
+        result
+    }
+}
+
+/// Changes of a file since the last commit, read once and shown in the preview panel.
+pub struct FileDiff {
+    pub file: PathBuf,
+    lines: Vec<DiffLine>,
+}
+
+impl FileDiff {
+    pub fn new(file: PathBuf) -> Result<Self, git2::Error> {
+        let lines = git_manager::diff(&file)?;
+        Ok(Self { file, lines })
+    }
+
+    /// Returns false once the user closes it.
+    pub fn render(&self, ui: &mut Ui, lang_string: &LangString) -> bool {
+        let mut open = true;
+        let name = self
+            .file
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.strong(lang_string.format(LangKeys::GitDiffOf, &[("name", name.into())]));
+            if ui.button(lang_string.get(LangKeys::Close)).clicked() {
+                open = false;
+            }
+        });
+        ui.separator();
+
+        if self.lines.is_empty() {
+            ui.label(lang_string.get(LangKeys::GitNoChanges));
+            return open;
+        }
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            for line in &self.lines {
+                let color = match line.origin {
+                    '+' => Color32::from_rgb(80, 180, 80),
+                    '-' => ui.visuals().error_fg_color,
+                    'H' => ui.visuals().hyperlink_color,
+                    'F' => ui.visuals().strong_text_color(),
+                    _ => ui.visuals().text_color(),
+                };
+
+                // Headers hold their own text, content lines are prefixed with their origin
+                let text = match line.origin {
+                    '+' | '-' | ' ' => format!("{}{}", line.origin, line.text),
+                    _ => line.text.clone(),
+                };
+                ui.label(RichText::new(text).monospace().color(color));
+            }
+        });
+
+        open
+    }
+}
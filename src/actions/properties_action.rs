@@ -0,0 +1,262 @@
+#[cfg(unix)]
+use crate::actions::background_action::BackgroundAction;
+#[cfg(unix)]
+use crate::actions::unix_properties::UnixProperties;
+use crate::config_manager::Config;
+use crate::lang_string::{LangKeys, LangString};
+use crate::mime_manager::MimeManager;
+#[cfg(unix)]
+use crate::permissions_manager;
+#[cfg(unix)]
+use crate::permissions_manager::PermissionChange;
+use crate::ui::additional_info_panel::{convert_bytes_size_to_human, format_date_time};
+use crate::vfs::{Vfs, VfsMetadata, VfsRouter};
+#[cfg(unix)]
+use egui::CollapsingHeader;
+use egui::{Context, Id, Ui, Window};
+use std::path::{Path, PathBuf};
+
+/// Changes the owner and mode chosen inside the Properties dialog.
+#[cfg(unix)]
+pub fn permissions_action(
+    id: usize,
+    lang_string: &LangString,
+    change: PermissionChange,
+) -> BackgroundAction {
+    let name = change
+        .file
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = lang_string.format(LangKeys::ChangingPermissions, &[("name", name.into())]);
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        permissions_manager::apply(&change, sender)
+    })
+    .counting_items()
+}
+
+pub enum PropertiesDialogResult {
+    Open,
+    Closed,
+    #[cfg(unix)]
+    Apply(PermissionChange),
+}
+
+/// Details of the selected files, read once when the dialog is opened
+/// so remote files are not queried every frame.
+pub struct PropertiesDialog {
+    pub files: Vec<PathBuf>,
+    metadata: Vec<Option<VfsMetadata>>,
+    mime_types: Vec<String>,
+
+    /// Number of entries inside each folder, None for files.
+    item_counts: Vec<Option<usize>>,
+
+    /// Ownership, permissions, links and extended attributes of a single local file.
+    #[cfg(unix)]
+    unix: Option<UnixProperties>,
+}
+
+impl PropertiesDialog {
+    pub fn new(files: Vec<PathBuf>, vfs: &VfsRouter, mime_manager: &MimeManager) -> Self {
+        let metadata: Vec<Option<VfsMetadata>> =
+            files.iter().map(|file| vfs.stat(file).ok()).collect();
+        let mime_types = files
+            .iter()
+            .map(|file| mime_manager.mime_type(file, vfs))
+            .collect();
+        let item_counts = files
+            .iter()
+            .zip(&metadata)
+            .map(|(file, metadata)| match metadata {
+                Some(metadata) if metadata.is_dir => vfs.list(file).ok().map(|list| list.len()),
+                _ => None,
+            })
+            .collect();
+
+        #[cfg(unix)]
+        let unix = match files.as_slice() {
+            [file] if vfs.is_local(file) => {
+                UnixProperties::read(file)
+            }
+            _ => None,
+        };
+
+        Self {
+            files,
+            metadata,
+            mime_types,
+            item_counts,
+            #[cfg(unix)]
+            unix,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        lang_string: &LangString,
+        config: &Config,
+    ) -> PropertiesDialogResult {
+        let mut open = true;
+        let mut result = PropertiesDialogResult::Open;
+
+        Window::new(lang_string.get(LangKeys::Properties))
+            .id(Id::new("properties_dialog"))
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("properties_dialog_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        if let ([file], [metadata], [mime_type], [item_count]) = (
+                            self.files.as_slice(),
+                            self.metadata.as_slice(),
+                            self.mime_types.as_slice(),
+                            self.item_counts.as_slice(),
+                        ) {
+                            self.file_ui(
+                                ui,
+                                lang_string,
+                                config,
+                                file,
+                                metadata,
+                                mime_type,
+                                *item_count,
+                            );
+                        } else {
+                            self.selection_ui(ui, lang_string, config);
+                        }
+
+                        #[cfg(unix)]
+                        if let Some(unix) = &self.unix {
+                            unix.details_ui(ui, lang_string);
+                        }
+                    });
+
+                #[cfg(unix)]
+                if let Some(unix) = &mut self.unix {
+                    CollapsingHeader::new(lang_string.get(LangKeys::Permissions))
+                        .default_open(true)
+                        .show(ui, |ui| unix.permissions_ui(ui, lang_string));
+                    CollapsingHeader::new(lang_string.get(LangKeys::ExtendedAttributes))
+                        .show(ui, |ui| unix.xattrs_ui(ui, lang_string));
+
+                    ui.separator();
+                    if ui.button(lang_string.get(LangKeys::Apply)).clicked() {
+                        match unix.change() {
+                            Ok(Some(change)) => result = PropertiesDialogResult::Apply(change),
+                            Ok(None) => result = PropertiesDialogResult::Closed,
+                            Err(key) => unix.set_error(lang_string.get(key)),
+                        }
+                    }
+                }
+            });
+
+        if !open {
+            result = PropertiesDialogResult::Closed;
+        }
+
+        result
+    }
+
+    fn file_ui(
+        &self,
+        ui: &mut Ui,
+        lang_string: &LangString,
+        config: &Config,
+        file: &Path,
+        metadata: &Option<VfsMetadata>,
+        mime_type: &str,
+        item_count: Option<usize>,
+    ) {
+        let file_name = file
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.to_string_lossy().into_owned());
+        row(ui, lang_string.get(LangKeys::Name), file_name);
+
+        if let Some(parent) = file.parent() {
+            row(
+                ui,
+                lang_string.get(LangKeys::Location),
+                parent.to_string_lossy().into_owned(),
+            );
+        }
+
+        let Some(metadata) = metadata else {
+            return;
+        };
+
+        if metadata.is_dir {
+            row(
+                ui,
+                lang_string.get(LangKeys::Kind),
+                lang_string.get(LangKeys::Folder),
+            );
+            if let Some(item_count) = item_count {
+                row(
+                    ui,
+                    lang_string.get(LangKeys::Contents),
+                    lang_string.format(LangKeys::ItemCount, &[("count", item_count.into())]),
+                );
+            }
+        } else {
+            row(ui, lang_string.get(LangKeys::Kind), mime_type.to_string());
+            row(
+                ui,
+                lang_string.get(LangKeys::Size),
+                convert_bytes_size_to_human(metadata.len, config.size_units),
+            );
+        }
+
+        let date_rows = [
+            (LangKeys::Created, metadata.created),
+            (LangKeys::Accessed, metadata.accessed),
+            (LangKeys::Modified, metadata.modified),
+        ];
+        for (key, time) in date_rows {
+            if let Some(time) = time {
+                row(
+                    ui,
+                    lang_string.get(key),
+                    format_date_time(time, &config.date_format),
+                );
+            }
+        }
+
+        if metadata.readonly {
+            row(ui, String::new(), lang_string.get(LangKeys::ReadOnly));
+        }
+    }
+
+    fn selection_ui(&self, ui: &mut Ui, lang_string: &LangString, config: &Config) {
+        row(
+            ui,
+            lang_string.get(LangKeys::Contents),
+            lang_string.format(LangKeys::ItemCount, &[("count", self.files.len().into())]),
+        );
+
+        // Folders are not measured recursively
+        let total_size: u64 = self
+            .metadata
+            .iter()
+            .flatten()
+            .filter(|metadata| !metadata.is_dir)
+            .map(|metadata| metadata.len)
+            .sum();
+        row(
+            ui,
+            lang_string.get(LangKeys::Size),
+            convert_bytes_size_to_human(total_size, config.size_units),
+        );
+    }
+}
+
+fn row(ui: &mut Ui, label: String, value: String) {
+    ui.label(label);
+    ui.label(value);
+    ui.end_row();
+}
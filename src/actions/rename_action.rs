@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+/// Can `name` be given to a file inside a folder? It must not point elsewhere,
+/// separators of other systems are refused too since the folder may be on a server.
+pub fn is_file_name_valid(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// File being renamed inline, its name is replaced by a text field.
+pub struct RenameAction {
+    pub file: PathBuf,
+
+    /// Name typed so far, starts as the current name.
+    pub name: String,
+
+    /// The text field takes the focus and selects the name (without extension) on the first frame.
+    pub request_focus: bool,
+}
+
+impl RenameAction {
+    pub fn new(file: &Path) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            name: file
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            request_focus: true,
+        }
+    }
+
+    /// Number of characters selected when the text field gets the focus: the name without extension.
+    pub fn stem_len(&self) -> usize {
+        match self.name.rfind('.') {
+            // Hidden files such as ".bashrc" have no extension
+            Some(0) | None => self.name.chars().count(),
+            Some(dot_idx) => self.name[..dot_idx].chars().count(),
+        }
+    }
+
+    /// New path of the file, None if the name is unchanged or invalid.
+    pub fn target(&self) -> Option<PathBuf> {
+        let name = self.name.trim();
+        if !is_file_name_valid(name) {
+            return None;
+        }
+
+        let target = self.file.with_file_name(name);
+        (target != self.file).then_some(target)
+    }
+}
@@ -1,5 +1,5 @@
-use crate::lang_string::{LangKeys, LangString};
-use egui::Key;
+use crate::vfs::{Vfs, VfsRouter};
+use egui::{Modifiers, Pos2, Rect, Vec2};
 use std::collections::HashSet;
 use std::path::PathBuf;
 
@@ -10,33 +10,48 @@ pub enum SelectionMode {
     Ranged,
 }
 
-pub enum SelectionResult {
-    Single(PathBuf),
-    Multiple(HashSet<PathBuf>),
-    Err(String),
-}
-
-impl SelectionResult {
-    pub fn clone(&self) -> SelectionResult {
-        match self {
-            SelectionResult::Single(path) => SelectionResult::Single(path.clone()),
-            SelectionResult::Multiple(paths) => SelectionResult::Multiple(paths.clone()),
-            SelectionResult::Err(err) => SelectionResult::Err(err.clone()),
+impl SelectionMode {
+    /// Shift selects a range, Ctrl (Cmd on Mac) adds to the selection.
+    pub fn from_modifiers(modifiers: Modifiers) -> Self {
+        match (modifiers.shift, modifiers.command) {
+            (true, _) => SelectionMode::Ranged,
+            (false, true) => SelectionMode::Multiple,
+            (false, false) => SelectionMode::Single,
         }
     }
 }
 
-pub struct KeySelect {
-    /// Current key being pressed
-    /// ! Garbage value when was_pressed == false
-    pub key: Key,
-
-    /// Was the key pressed in previous frames?
-    /// This is set to false when the key is released
-    pub was_pressed: bool,
+/// Characters typed within this many seconds of each other are searched together.
+const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
 
+pub struct KeySelect {
     /// Do we need to scroll to the widget?
     pub scroll_to_widget: bool,
+
+    /// Characters typed in a row: "src" jumps to `src` instead of cycling through the 's' files.
+    pub typed: String,
+
+    /// Time (egui input time) of the last typed character.
+    pub last_typed_at: f64,
+}
+
+/// Rectangle dragged over the empty space of the central panel.
+pub struct RubberBand {
+    /// Where the drag started, in screen coordinates.
+    pub origin: Pos2,
+
+    /// Where the drag started, relative to the top-left corner of the file list content,
+    /// so it stays on the same files while the list scrolls. Set once the list is laid out.
+    content_origin: Option<Vec2>,
+
+    /// Current position of the pointer, in screen coordinates.
+    pointer: Pos2,
+
+    /// Current rectangle, in screen coordinates.
+    pub rect: Rect,
+
+    /// Selection from before the drag, kept when Ctrl is held.
+    base_selection: HashSet<PathBuf>,
 }
 
 pub struct SelectAction {
@@ -47,9 +62,20 @@ pub struct SelectAction {
     pub mode: SelectionMode,
 
     /// For 'key' selection
-    /// Did we press a key to select a file?
-    /// What key?
     pub key_select: KeySelect,
+
+    /// File the keyboard acts on, it is moved with the arrow keys.
+    /// Unlike the selection, there is only one and it can be moved alone (Ctrl+arrows).
+    pub cursor: Option<PathBuf>,
+
+    /// File a range selection (Shift) starts from.
+    pub anchor: Option<PathBuf>,
+
+    /// Active while a rectangle is dragged to select files.
+    pub rubber_band: Option<RubberBand>,
+
+    /// Text of the "Select by pattern" window, None while it is closed.
+    pub select_pattern: Option<String>,
 }
 
 impl SelectAction {
@@ -58,10 +84,14 @@ impl SelectAction {
             files: HashSet::new(),
             mode: SelectionMode::Single,
             key_select: KeySelect {
-                key: Key::A,
-                was_pressed: false,
                 scroll_to_widget: false,
+                typed: String::new(),
+                last_typed_at: 0.0,
             },
+            cursor: None,
+            anchor: None,
+            rubber_band: None,
+            select_pattern: None,
         }
     }
 
@@ -70,6 +100,11 @@ impl SelectAction {
     }
 
     pub fn select_file(&mut self, file: &PathBuf, directory_content: Option<&[PathBuf]>) {
+        self.cursor = Some(file.clone());
+        if self.mode != SelectionMode::Ranged {
+            self.anchor = Some(file.clone());
+        }
+
         match self.mode {
             // Only one file can be selected at a time
             // If the file is already selected, deselect it
@@ -108,12 +143,11 @@ impl SelectAction {
                     return;
                 };
 
-                let start_idx = content
-                    .iter()
-                    .enumerate()
-                    .rev()
-                    .find(|(_, f)| self.files.contains(*f))
-                    .map(|(idx, _)| idx)
+                let start_idx = self
+                    .anchor
+                    .as_ref()
+                    .and_then(|anchor| content.iter().position(|f| f == anchor))
+                    .or_else(|| content.iter().rposition(|f| self.files.contains(f)))
                     .unwrap_or(0);
 
                 let min = start_idx.min(target_idx);
@@ -126,15 +160,203 @@ impl SelectAction {
         }
     }
 
-    pub fn select_file_by_key(&mut self, file: &PathBuf, key: Key) {
-        self.clear_selection();
+    /// Moves the cursor onto `file` and scrolls to it.
+    ///
+    /// Shift selects everything between the anchor and `file`,
+    /// Ctrl only moves the cursor, otherwise `file` becomes the only selected file.
+    pub fn move_cursor(
+        &mut self,
+        file: &PathBuf,
+        directory_content: &[PathBuf],
+        modifiers: Modifiers,
+    ) {
+        if modifiers.shift {
+            let cursor_idx = directory_content.iter().position(|f| f == file);
+            let anchor_idx = self
+                .anchor
+                .as_ref()
+                .or(self.cursor.as_ref())
+                .and_then(|anchor| directory_content.iter().position(|f| f == anchor));
+
+            if !modifiers.command {
+                self.files.clear();
+            }
+
+            if let (Some(cursor_idx), Some(anchor_idx)) = (cursor_idx, anchor_idx) {
+                let range = cursor_idx.min(anchor_idx)..=cursor_idx.max(anchor_idx);
+                self.files.extend(directory_content[range].iter().cloned());
+                self.anchor = Some(directory_content[anchor_idx].clone());
+            } else {
+                self.files.insert(file.clone());
+                self.anchor = Some(file.clone());
+            }
+        } else if !modifiers.command {
+            self.files.clear();
+            self.files.insert(file.clone());
+            self.anchor = Some(file.clone());
+        }
 
-        self.files.insert(file.clone());
-        self.key_select.key = key;
-        self.key_select.was_pressed = true;
+        self.cursor = Some(file.clone());
         self.key_select.scroll_to_widget = true;
     }
 
+    /// Space: adds or removes the file under the cursor from the selection.
+    pub fn toggle_cursor(&mut self) {
+        let Some(cursor) = self.cursor.clone() else {
+            return;
+        };
+
+        if !self.files.remove(&cursor) {
+            self.files.insert(cursor.clone());
+        }
+        self.anchor = Some(cursor);
+    }
+
+    /// Adds `text` to the characters typed so far and returns the file they designate, if any.
+    ///
+    /// The same character typed repeatedly cycles through the files starting with it,
+    /// otherwise the search starts from the cursor, so typing "src" stays on `src`.
+    pub fn type_ahead<'a>(
+        &mut self,
+        text: &str,
+        time: f64,
+        directory_content: &'a [PathBuf],
+    ) -> Option<&'a PathBuf> {
+        if time - self.key_select.last_typed_at > TYPE_AHEAD_TIMEOUT {
+            self.key_select.typed.clear();
+        }
+        self.key_select.last_typed_at = time;
+        self.key_select.typed.push_str(&text.to_lowercase());
+
+        let typed = &self.key_select.typed;
+        let first_char = typed.chars().next()?;
+        let repeated = typed.chars().all(|c| c == first_char);
+
+        let cursor_idx = self
+            .cursor
+            .as_ref()
+            .and_then(|cursor| directory_content.iter().position(|f| f == cursor));
+        let (needle, start_idx) = match (repeated, cursor_idx) {
+            (true, Some(cursor_idx)) => (first_char.to_string(), cursor_idx + 1),
+            (true, None) => (first_char.to_string(), 0),
+            (false, cursor_idx) => (typed.clone(), cursor_idx.unwrap_or(0)),
+        };
+        let start_idx = start_idx.min(directory_content.len());
+
+        // Wraps around to the first matching file
+        directory_content[start_idx..]
+            .iter()
+            .chain(&directory_content[..start_idx])
+            .find(|file| {
+                file.file_name().is_some_and(|file_name| {
+                    file_name
+                        .to_string_lossy()
+                        .to_lowercase()
+                        .starts_with(&needle)
+                })
+            })
+    }
+
+    /// Whether characters are being typed for the type-ahead search (Space then belongs to it).
+    pub fn is_typing_ahead(&self, time: f64) -> bool {
+        !self.key_select.typed.is_empty()
+            && time - self.key_select.last_typed_at <= TYPE_AHEAD_TIMEOUT
+    }
+
+    pub fn select_all(&mut self, directory_content: &[PathBuf]) {
+        self.files.extend(directory_content.iter().cloned());
+    }
+
+    pub fn invert_selection(&mut self, directory_content: &[PathBuf]) {
+        self.files = directory_content
+            .iter()
+            .filter(|file| !self.files.contains(*file))
+            .cloned()
+            .collect();
+    }
+
+    /// Selects the files whose name matches `pattern`, e.g. `*.log` or `*.jpg; *.png`.
+    /// Returns how many files matched.
+    pub fn select_by_pattern(&mut self, pattern: &str, directory_content: &[PathBuf]) -> usize {
+        let patterns: Vec<String> = pattern
+            .split(';')
+            .map(|pattern| pattern.trim().to_lowercase())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+
+        let matching: Vec<&PathBuf> = directory_content
+            .iter()
+            .filter(|file| {
+                file.file_name().is_some_and(|file_name| {
+                    let file_name = file_name.to_string_lossy().to_lowercase();
+                    patterns
+                        .iter()
+                        .any(|pattern| wildcard_match(pattern, &file_name))
+                })
+            })
+            .collect();
+
+        self.files.clear();
+        self.files
+            .extend(matching.iter().map(|file| (*file).clone()));
+        matching.len()
+    }
+
+    /// Starts a rubber band selection, `additive` (Ctrl) keeps the current selection.
+    pub fn start_rubber_band(&mut self, origin: Pos2, additive: bool) {
+        let base_selection = if additive {
+            self.files.clone()
+        } else {
+            HashSet::new()
+        };
+
+        self.rubber_band = Some(RubberBand {
+            origin,
+            content_origin: None,
+            pointer: origin,
+            rect: Rect::from_two_pos(origin, origin),
+            base_selection,
+        });
+    }
+
+    /// Must be called every frame of the drag, before select_rubber_band.
+    pub fn update_rubber_band(&mut self, pointer: Pos2) {
+        if let Some(rubber_band) = &mut self.rubber_band {
+            rubber_band.pointer = pointer;
+        }
+    }
+
+    /// Selects every file of `directory_content` touched by the rubber band, laid out
+    /// in `columns` cells of `cell_size` from `content_min` (the top-left corner of the content).
+    /// Files are found from their index, so the ones scrolled out of view are selected too.
+    pub fn select_rubber_band(
+        &mut self,
+        directory_content: &[PathBuf],
+        content_min: Pos2,
+        cell_size: Vec2,
+        columns: usize,
+    ) {
+        let Some(rubber_band) = &mut self.rubber_band else {
+            return;
+        };
+
+        let content_origin = *rubber_band
+            .content_origin
+            .get_or_insert(rubber_band.origin - content_min);
+        rubber_band.rect = Rect::from_two_pos(content_min + content_origin, rubber_band.pointer);
+
+        let band = rubber_band.rect.translate(-content_min.to_vec2());
+        self.files = rubber_band.base_selection.clone();
+        self.files.extend(
+            cells_in_band(band, cell_size, columns, directory_content.len())
+                .map(|idx| directory_content[idx].clone()),
+        );
+    }
+
+    pub fn stop_rubber_band(&mut self) {
+        self.rubber_band = None;
+    }
+
     pub fn deselect_file(&mut self, file: &PathBuf) {
         self.files.retain(|f| *f != *file);
     }
@@ -144,21 +366,112 @@ impl SelectAction {
         self.mode = SelectionMode::Single;
     }
 
-    pub fn get_selected_files(&self, lang_string: &LangString) -> SelectionResult {
-        // Attempt to get the first file immediately
-        let Some(first_file) = self.files.iter().next() else {
-            return SelectionResult::Err(lang_string.get(LangKeys::NothingSelected));
-        };
+    /// Checked through the VFS, selected files may be on a server or inside an archive.
+    /// Broken links can not be stat'ed but still exist.
+    pub fn remove_deleted_files(&mut self, vfs: &VfsRouter) {
+        self.files
+            .retain(|f| vfs.stat(f).is_ok() || vfs.read_link(f).is_some());
+    }
+}
 
-        match self.mode {
-            SelectionMode::Single => SelectionResult::Single(first_file.clone()),
-            SelectionMode::Multiple | SelectionMode::Ranged => {
-                SelectionResult::Multiple(self.files.clone())
+/// Matches `text` against a pattern where `*` is any sequence of characters and `?` any character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut pattern_idx, mut text_idx) = (0, 0);
+
+    // Position of the last '*' and of the text it was matched against, to backtrack
+    let mut star: Option<(usize, usize)> = None;
+
+    while text_idx < text.len() {
+        match pattern.get(pattern_idx) {
+            Some('*') => {
+                star = Some((pattern_idx, text_idx));
+                pattern_idx += 1;
+            }
+            Some(c) if *c == '?' || *c == text[text_idx] => {
+                pattern_idx += 1;
+                text_idx += 1;
+            }
+            _ => {
+                let Some((star_pattern_idx, star_text_idx)) = star else {
+                    return false;
+                };
+
+                // Let the '*' swallow one more character
+                pattern_idx = star_pattern_idx + 1;
+                text_idx = star_text_idx + 1;
+                star = Some((star_pattern_idx, star_text_idx + 1));
             }
         }
     }
 
-    pub fn remove_deleted_files(&mut self) {
-        self.files.retain(|f| f.exists());
+    pattern[pattern_idx..].iter().all(|c| *c == '*')
+}
+
+/// Indices of the cells touched by `band`, in a grid of `columns` cells of `cell_size`
+/// holding `count` cells. `band` is relative to the top-left corner of the grid.
+fn cells_in_band(
+    band: Rect,
+    cell_size: Vec2,
+    columns: usize,
+    count: usize,
+) -> impl Iterator<Item = usize> {
+    let cell = |position: f32, size: f32| (position.max(0.0) / size) as usize;
+    let rows = cell(band.min.y, cell_size.y)..=cell(band.max.y, cell_size.y);
+    let first_column = cell(band.min.x, cell_size.x);
+    let last_column = cell(band.max.x, cell_size.x).min(columns - 1);
+
+    // Clamped to the grid, a band above or left of it would still touch the first cells
+    let outside = band.max.x < 0.0 || band.max.y < 0.0;
+
+    rows.flat_map(move |row| (first_column..=last_column).map(move |column| row * columns + column))
+        .take_while(move |idx| !outside && *idx < count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(wildcard_match("*.log", "server.log"));
+        assert!(wildcard_match("*.log", ".log"));
+        assert!(!wildcard_match("*.log", "server.log.1"));
+        assert!(wildcard_match("*.log*", "server.log.1"));
+        assert!(wildcard_match("img_??.png", "img_01.png"));
+        assert!(!wildcard_match("img_??.png", "img_1.png"));
+        assert!(wildcard_match("*a*b*", "xxaxxbxx"));
+        assert!(!wildcard_match("*a*b", "xxbxxa"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn rubber_band_cells() {
+        let cells = |band: Rect, cell_size: Vec2, columns: usize| {
+            cells_in_band(band, cell_size, columns, 10).collect::<Vec<_>>()
+        };
+        let rows = Vec2::new(f32::INFINITY, 30.0);
+        let grid = Vec2::new(100.0, 100.0);
+
+        // Rows far below the first ones, e.g. scrolled out of view
+        let band = Rect::from_two_pos(Pos2::new(50.0, 95.0), Pos2::new(10.0, 215.0));
+        assert_eq!(cells(band, rows, 1), vec![3, 4, 5, 6, 7]);
+
+        // The band ends past the last file
+        let band = Rect::from_two_pos(Pos2::new(0.0, 250.0), Pos2::new(10.0, 900.0));
+        assert_eq!(cells(band, rows, 1), vec![8, 9]);
+
+        let band = Rect::from_two_pos(Pos2::new(150.0, 50.0), Pos2::new(250.0, 150.0));
+        assert_eq!(cells(band, grid, 4), vec![1, 2, 5, 6]);
+
+        // Right of the last column, left of and above the grid
+        let band = Rect::from_two_pos(Pos2::new(350.0, 50.0), Pos2::new(900.0, 150.0));
+        assert_eq!(cells(band, grid, 3), Vec::<usize>::new());
+        let band = Rect::from_two_pos(Pos2::new(-50.0, 50.0), Pos2::new(-10.0, 150.0));
+        assert_eq!(cells(band, grid, 3), Vec::<usize>::new());
+        let band = Rect::from_two_pos(Pos2::new(-50.0, -50.0), Pos2::new(50.0, 50.0));
+        assert_eq!(cells(band, grid, 3), vec![0]);
     }
 }
@@ -0,0 +1,191 @@
+use crate::actions::background_action::BackgroundAction;
+use crate::lang_string::{LangKeys, LangString};
+use crate::transfer_manager;
+use crate::transfer_manager::TransferMode;
+use crate::vfs::{Vfs, VfsRouter};
+use egui::{Context, Id, Window};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Drag and drop payload: files dragged out of the directory listing.
+pub struct FilesPayload {
+    pub files: Vec<PathBuf>,
+}
+
+/// Files copied or cut, waiting to be pasted.
+pub struct FileClipboard {
+    pub files: Vec<PathBuf>,
+    pub mode: TransferMode,
+}
+
+/// Copies, moves or links `files` inside the `destination` folder.
+/// Unless everything is on the local disk, the thread connects to the servers involved on its own.
+pub fn transfer_action(
+    id: usize,
+    lang_string: &LangString,
+    vfs: &VfsRouter,
+    files: Vec<PathBuf>,
+    destination: &Path,
+    mode: TransferMode,
+) -> BackgroundAction {
+    let key = match mode {
+        TransferMode::Copy => LangKeys::Copying,
+        TransferMode::Move => LangKeys::Moving,
+        TransferMode::Link => LangKeys::Linking,
+    };
+    let title = lang_string.format(
+        key,
+        &[("destination", destination.to_string_lossy().to_string().into())],
+    );
+    let local = files.iter().all(|file| vfs.is_local(file)) && vfs.is_local(destination);
+    let trusted_keys = vfs.trusted_keys();
+    let destination = destination.to_path_buf();
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        if local {
+            return transfer_manager::transfer(&files, &destination, mode, sender);
+        }
+
+        let paths: Vec<PathBuf> = files.iter().chain([&destination]).cloned().collect();
+        let vfs = VfsRouter::mounted_for(&paths, trusted_keys)?;
+        transfer_manager::transfer_through_vfs(&vfs, &files, &destination, mode, sender)
+    })
+}
+
+/// Moves `files` to the trash one by one, so the job can be paused or cancelled between two.
+pub fn trash_action(id: usize, lang_string: &LangString, files: Vec<PathBuf>) -> BackgroundAction {
+    let title = lang_string.format(LangKeys::MovingToTrash, &[("count", files.len().into())]);
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        sender.total(files.len() as u64);
+        for file in &files {
+            trash::delete(file).map_err(|err| io::Error::other(err.to_string()))?;
+            sender.advance(1, file)?;
+        }
+
+        Ok(())
+    })
+    .counting_items()
+}
+
+/// Deletes `files` one by one through the VFS, with its own connections.
+pub fn delete_action(
+    id: usize,
+    lang_string: &LangString,
+    vfs: &VfsRouter,
+    files: Vec<PathBuf>,
+) -> BackgroundAction {
+    let title = lang_string.format(LangKeys::DeletingFiles, &[("count", files.len().into())]);
+    let trusted_keys = vfs.trusted_keys();
+
+    BackgroundAction::spawn(id, title, move |sender| {
+        let vfs = VfsRouter::mounted_for(&files, trusted_keys)?;
+        sender.total(files.len() as u64);
+        for file in &files {
+            vfs.remove(file)?;
+            sender.advance(1, file)?;
+        }
+
+        Ok(())
+    })
+    .counting_items()
+}
+
+/// Transfer waiting for the user to confirm it.
+pub struct TransferConfirmation {
+    pub files: Vec<PathBuf>,
+    pub destination: PathBuf,
+    pub mode: TransferMode,
+}
+
+pub enum TransferConfirmationResult {
+    Pending,
+    Cancelled,
+    Confirmed,
+}
+
+impl TransferConfirmation {
+    pub fn render(&self, ctx: &Context, lang_string: &LangString) -> TransferConfirmationResult {
+        let mut result = TransferConfirmationResult::Pending;
+
+        Window::new(lang_string.get(LangKeys::MoveFiles))
+            .id(Id::new("transfer_confirmation"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(lang_string.format(
+                    LangKeys::ConfirmMoveFiles,
+                    &[
+                        ("count", self.files.len().into()),
+                        ("destination", self.destination.to_string_lossy().to_string().into()),
+                    ],
+                ));
+                for file in &self.files {
+                    ui.small(file.to_string_lossy());
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(lang_string.get(LangKeys::Move)).clicked() {
+                        result = TransferConfirmationResult::Confirmed;
+                    }
+
+                    if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                        result = TransferConfirmationResult::Cancelled;
+                    }
+                });
+            });
+
+        result
+    }
+}
+
+/// Files moved to the trash once the user confirms it.
+pub struct TrashConfirmation {
+    pub files: Vec<PathBuf>,
+
+    /// The files are deleted for good, their backend has no trash.
+    pub permanent: bool,
+}
+
+pub enum TrashConfirmationResult {
+    Pending,
+    Cancelled,
+    Confirmed,
+}
+
+impl TrashConfirmation {
+    pub fn render(&self, ctx: &Context, lang_string: &LangString) -> TrashConfirmationResult {
+        let mut result = TrashConfirmationResult::Pending;
+
+        let (title, question) = if self.permanent {
+            (LangKeys::DeletePermanently, LangKeys::ConfirmDeleteFiles)
+        } else {
+            (LangKeys::MoveToTrash, LangKeys::ConfirmTrashFiles)
+        };
+
+        Window::new(lang_string.get(title))
+            .id(Id::new("trash_confirmation"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(lang_string.format(question, &[("count", self.files.len().into())]));
+                for file in &self.files {
+                    ui.small(file.to_string_lossy());
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(lang_string.get(title)).clicked() {
+                        result = TrashConfirmationResult::Confirmed;
+                    }
+
+                    if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                        result = TrashConfirmationResult::Cancelled;
+                    }
+                });
+            });
+
+        result
+    }
+}
@@ -0,0 +1,274 @@
+use crate::lang_string::{LangKeys, LangString};
+use crate::permissions_manager;
+use crate::permissions_manager::PermissionChange;
+use egui::{Button, TextEdit, Ui};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Permission bits shown as checkboxes: (owner, group, others) x (read, write, execute).
+const MODE_BITS: [(LangKeys, [u32; 3]); 3] = [
+    (LangKeys::Owner, [0o400, 0o200, 0o100]),
+    (LangKeys::Group, [0o040, 0o020, 0o010]),
+    (LangKeys::Others, [0o004, 0o002, 0o001]),
+];
+
+const SPECIAL_BITS: [(LangKeys, u32); 3] = [
+    (LangKeys::SetUserId, 0o4000),
+    (LangKeys::SetGroupId, 0o2000),
+    (LangKeys::Sticky, 0o1000),
+];
+
+/// Unix details of a single local file, editable by the Properties dialog.
+/// Symbolic links are described themselves, not their target.
+pub struct UnixProperties {
+    file: PathBuf,
+    is_dir: bool,
+    is_symlink: bool,
+
+    uid: u32,
+    gid: u32,
+
+    /// Owner typed by the user, a name or a numeric id.
+    owner: String,
+    group: String,
+
+    /// Permission bits (special bits included) read from the disk.
+    original_mode: u32,
+    mode: u32,
+
+    /// Also apply the changes to everything inside the folder.
+    recursive: bool,
+
+    link_target: Option<PathBuf>,
+    inode: u64,
+    device: u64,
+    hard_links: u64,
+
+    /// Extended attributes, values are displayed lossily.
+    xattrs: Vec<(String, String)>,
+    new_xattr_name: String,
+    new_xattr_value: String,
+
+    /// Last error, displayed at the bottom of the dialog.
+    error: Option<String>,
+}
+
+impl UnixProperties {
+    pub fn read(file: &Path) -> Option<Self> {
+        let metadata = file.symlink_metadata().ok()?;
+        let uid = metadata.uid();
+        let gid = metadata.gid();
+        let mode = metadata.mode() & 0o7777;
+
+        let mut properties = Self {
+            file: file.to_path_buf(),
+            is_dir: metadata.is_dir(),
+            is_symlink: metadata.file_type().is_symlink(),
+            uid,
+            gid,
+            owner: permissions_manager::user_name(uid).unwrap_or_else(|| uid.to_string()),
+            group: permissions_manager::group_name(gid).unwrap_or_else(|| gid.to_string()),
+            original_mode: mode,
+            mode,
+            recursive: false,
+            link_target: file.read_link().ok(),
+            inode: metadata.ino(),
+            device: metadata.dev(),
+            hard_links: metadata.nlink(),
+            xattrs: Vec::new(),
+            new_xattr_name: String::new(),
+            new_xattr_value: String::new(),
+            error: None,
+        };
+        properties.read_xattrs();
+
+        Some(properties)
+    }
+
+    fn read_xattrs(&mut self) {
+        self.xattrs = match xattr::list(&self.file) {
+            Ok(names) => names
+                .map(|name| {
+                    let value = xattr::get(&self.file, &name)
+                        .ok()
+                        .flatten()
+                        .map(|value| String::from_utf8_lossy(&value).into_owned())
+                        .unwrap_or_default();
+                    (name.to_string_lossy().into_owned(), value)
+                })
+                .collect(),
+
+            // Some file systems do not support extended attributes at all
+            Err(_) => Vec::new(),
+        };
+        self.xattrs.sort();
+    }
+
+    /// Changes to apply, None if nothing changed.
+    /// A recursive change applies the current values to everything inside the folder.
+    pub fn change(&self) -> Result<Option<PermissionChange>, LangKeys> {
+        let uid = permissions_manager::parse_user(&self.owner).ok_or(LangKeys::UnknownUser)?;
+        let gid = permissions_manager::parse_group(&self.group).ok_or(LangKeys::UnknownGroup)?;
+        let recursive = self.recursive && self.is_dir;
+
+        let change = PermissionChange {
+            file: self.file.clone(),
+            mode: (self.mode != self.original_mode || recursive).then_some(self.mode),
+            uid: (uid != self.uid || recursive).then_some(uid),
+            gid: (gid != self.gid || recursive).then_some(gid),
+            recursive,
+        };
+
+        if change.mode.is_none() && change.uid.is_none() && change.gid.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(change))
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Rows of the Properties grid.
+    pub fn details_ui(&self, ui: &mut Ui, lang_string: &LangString) {
+        if let Some(link_target) = &self.link_target {
+            ui.label(lang_string.get(LangKeys::LinkTarget));
+            ui.label(link_target.to_string_lossy());
+            ui.end_row();
+        }
+
+        ui.label(lang_string.get(LangKeys::Inode));
+        ui.label(self.inode.to_string());
+        ui.end_row();
+
+        ui.label(lang_string.get(LangKeys::Device));
+        ui.label(self.device.to_string());
+        ui.end_row();
+
+        ui.label(lang_string.get(LangKeys::HardLinks));
+        ui.label(self.hard_links.to_string());
+        ui.end_row();
+    }
+
+    pub fn permissions_ui(&mut self, ui: &mut Ui, lang_string: &LangString) {
+        egui::Grid::new("properties_ownership_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label(lang_string.get(LangKeys::Owner));
+                ui.add(TextEdit::singleline(&mut self.owner).desired_width(150.0));
+                ui.end_row();
+
+                ui.label(lang_string.get(LangKeys::Group));
+                ui.add(TextEdit::singleline(&mut self.group).desired_width(150.0));
+                ui.end_row();
+            });
+
+        // Symbolic links have no mode of their own
+        if self.is_symlink {
+            return;
+        }
+
+        ui.add_space(4.0);
+        egui::Grid::new("properties_mode_grid")
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.label(format!("{:04o}", self.mode));
+                ui.label(lang_string.get(LangKeys::Read));
+                ui.label(lang_string.get(LangKeys::Write));
+                ui.label(lang_string.get(LangKeys::Execute));
+                ui.end_row();
+
+                for (key, bits) in MODE_BITS {
+                    ui.label(lang_string.get(key));
+                    for bit in bits {
+                        mode_checkbox(ui, &mut self.mode, bit, String::new());
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            for (key, bit) in SPECIAL_BITS {
+                mode_checkbox(ui, &mut self.mode, bit, lang_string.get(key));
+            }
+        });
+
+        if self.is_dir {
+            ui.checkbox(
+                &mut self.recursive,
+                lang_string.get(LangKeys::ApplyRecursively),
+            );
+        }
+    }
+
+    /// Extended attributes are added and removed right away.
+    pub fn xattrs_ui(&mut self, ui: &mut Ui, lang_string: &LangString) {
+        let mut removed = None;
+
+        egui::Grid::new("properties_xattrs_grid")
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, value) in &self.xattrs {
+                    ui.label(name);
+                    ui.label(value);
+                    if ui
+                        .small_button("x")
+                        .on_hover_text(lang_string.get(LangKeys::Remove))
+                        .clicked()
+                    {
+                        removed = Some(name.clone());
+                    }
+                    ui.end_row();
+                }
+
+                ui.add(
+                    TextEdit::singleline(&mut self.new_xattr_name)
+                        .hint_text("user.comment")
+                        .desired_width(120.0),
+                );
+                ui.add(
+                    TextEdit::singleline(&mut self.new_xattr_value)
+                        .hint_text(lang_string.get(LangKeys::Value))
+                        .desired_width(160.0),
+                );
+                let add_response = ui.add_enabled(
+                    !self.new_xattr_name.trim().is_empty(),
+                    Button::new(lang_string.get(LangKeys::Add)),
+                );
+                if add_response.clicked() {
+                    let name = self.new_xattr_name.trim().to_string();
+                    match xattr::set(&self.file, &name, self.new_xattr_value.as_bytes()) {
+                        Ok(()) => {
+                            self.new_xattr_name.clear();
+                            self.new_xattr_value.clear();
+                            self.error = None;
+                        }
+                        Err(err) => self.error = Some(err.to_string()),
+                    }
+                    self.read_xattrs();
+                }
+                ui.end_row();
+            });
+
+        if let Some(name) = removed {
+            match xattr::remove(&self.file, &name) {
+                Ok(()) => self.error = None,
+                Err(err) => self.error = Some(err.to_string()),
+            }
+            self.read_xattrs();
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+    }
+}
+
+fn mode_checkbox(ui: &mut Ui, mode: &mut u32, bit: u32, label: String) {
+    let mut checked = *mode & bit != 0;
+    if ui.checkbox(&mut checked, label).changed() {
+        *mode ^= bit;
+    }
+}
@@ -0,0 +1,141 @@
+use crate::lang_string::{LangKeys, LangString};
+use crate::user_actions;
+use egui::{Context, Id, RichText, ScrollArea, Window};
+use std::io;
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+/// User action running in the background, rendered as its own modal
+/// showing the output and the exit status once the command is done.
+pub struct UserActionRun {
+    id: usize,
+
+    /// Name of the action, displayed as the title of the modal.
+    title: String,
+    command: String,
+    receiver: Receiver<io::Result<Output>>,
+
+    /// None while the command is still running.
+    result: Option<Result<Output, String>>,
+
+    /// The modal is closed by the user once the command has finished.
+    pub visible: bool,
+}
+
+impl UserActionRun {
+    pub fn spawn(id: usize, title: String, command: String, directory: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        let mut shell = user_actions::shell_command(&command);
+        shell.current_dir(directory).stdin(Stdio::null());
+        thread::spawn(move || {
+            let _ = sender.send(shell.output());
+        });
+
+        Self {
+            id,
+            title,
+            command,
+            receiver,
+            result: None,
+            visible: true,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Returns true on the frame the command finishes.
+    pub fn poll(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        match self.receiver.try_recv() {
+            Ok(output) => {
+                self.result = Some(output.map_err(|err| err.to_string()));
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn render(&mut self, ctx: &Context, lang_string: &LangString) {
+        Window::new(&self.title)
+            .id(Id::new(("user_action", self.id)))
+            .collapsible(true)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.command).monospace().weak());
+                ui.separator();
+
+                match &self.result {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(lang_string.get(LangKeys::CommandRunning));
+                        });
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(ui.visuals().error_fg_color, err);
+                    }
+                    Some(Ok(output)) => {
+                        let status = match output.status.code() {
+                            Some(code) => lang_string
+                                .format(LangKeys::CommandExitStatus, &[("code", code.into())]),
+                            None => lang_string.get(LangKeys::CommandKilled),
+                        };
+                        if output.status.success() {
+                            ui.label(status);
+                        } else {
+                            ui.colored_label(ui.visuals().error_fg_color, status);
+                        }
+
+                        output_ui(ui, lang_string, output);
+                    }
+                }
+
+                ui.separator();
+                if ui
+                    .add_enabled(
+                        self.is_finished(),
+                        egui::Button::new(lang_string.get(LangKeys::Close)),
+                    )
+                    .clicked()
+                {
+                    self.visible = false;
+                }
+            });
+
+        // The command thread cannot wake up the UI, keep polling while it runs
+        if !self.is_finished() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+}
+
+/// Standard output, then standard error in the error color.
+fn output_ui(ui: &mut egui::Ui, lang_string: &LangString, output: &Output) {
+    if output.stdout.is_empty() && output.stderr.is_empty() {
+        ui.weak(lang_string.get(LangKeys::NoOutput));
+        return;
+    }
+
+    ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+        if !output.stdout.is_empty() {
+            ui.label(RichText::new(String::from_utf8_lossy(&output.stdout)).monospace());
+        }
+        if !output.stderr.is_empty() {
+            ui.label(
+                RichText::new(String::from_utf8_lossy(&output.stderr))
+                    .monospace()
+                    .color(ui.visuals().error_fg_color),
+            );
+        }
+    });
+}
@@ -0,0 +1,245 @@
+use crate::lang_string::{LangKeys, LangString};
+use crate::vfs::sftp_vfs;
+use crate::vfs::{Vfs, VfsRouter};
+use std::env;
+use std::env::home_dir;
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+
+/// State of the path panel while the user types a path instead of clicking the breadcrumb.
+pub struct AddressBar {
+    /// Is the breadcrumb replaced by the text field?
+    pub editing: bool,
+
+    /// The focus must be requested on the frame the text field appears.
+    pub request_focus: bool,
+
+    pub text: String,
+
+    /// Displayed next to the text field when the typed path cannot be opened.
+    pub error: Option<String>,
+
+    /// Candidates of the last Tab press, cycled through by pressing Tab again.
+    completions: Vec<String>,
+    completion_idx: usize,
+}
+
+impl AddressBar {
+    pub fn new() -> Self {
+        Self {
+            editing: false,
+            request_focus: false,
+            text: String::new(),
+            error: None,
+            completions: Vec::new(),
+            completion_idx: 0,
+        }
+    }
+
+    pub fn start_editing(&mut self, current_path: &Path) {
+        self.editing = true;
+        self.request_focus = true;
+        self.text = current_path.to_string_lossy().into_owned();
+        self.error = None;
+        self.completions.clear();
+    }
+
+    pub fn stop_editing(&mut self) {
+        self.editing = false;
+        self.error = None;
+        self.completions.clear();
+    }
+
+    /// Any edit made by the user invalidates the Tab candidates.
+    pub fn text_changed(&mut self) {
+        self.completions.clear();
+        self.error = None;
+    }
+
+    /// Expands a leading `~` and every `$VAR`/`${VAR}` of the typed text.
+    pub fn expand(text: &str) -> PathBuf {
+        let text = text.trim();
+        let mut expanded = String::with_capacity(text.len());
+
+        let rest = match text.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+                if let Some(home) = home_dir() {
+                    expanded.push_str(&home.to_string_lossy());
+                    rest
+                } else {
+                    text
+                }
+            }
+            _ => text,
+        };
+
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                expanded.push(c);
+                continue;
+            }
+
+            let mut name = String::new();
+            let braced = chars.peek() == Some(&'{');
+            let mut closed = false;
+            if braced {
+                chars.next();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+            }
+
+            // Unknown variables are kept as typed, so the error shows what was wrong
+            match env::var(&name) {
+                Ok(value) if !name.is_empty() => expanded.push_str(&value),
+                _ => {
+                    expanded.push('$');
+                    if braced {
+                        expanded.push('{');
+                    }
+                    expanded.push_str(&name);
+                    if closed {
+                        expanded.push('}');
+                    }
+                }
+            }
+        }
+
+        PathBuf::from(expanded)
+    }
+
+    /// Completes the last segment of the text with the name of a matching directory.
+    /// Pressing Tab again cycles through the other matches.
+    pub fn complete(&mut self, vfs: &mut VfsRouter) {
+        if !self.completions.is_empty() {
+            self.completion_idx = (self.completion_idx + 1) % self.completions.len();
+            self.text = self.completions[self.completion_idx].clone();
+            return;
+        }
+
+        let split_idx = self.text.rfind(['/', '\\']).map(|idx| idx + 1).unwrap_or(0);
+        let (typed_dir, prefix) = self.text.split_at(split_idx);
+        if typed_dir.is_empty() {
+            return;
+        }
+
+        let dir = Self::expand(typed_dir);
+        if sftp_vfs::is_sftp_path(&dir) && vfs.mount_for(&dir).is_err() {
+            return;
+        }
+        let Ok(entries) = vfs.list(&dir) else {
+            return;
+        };
+
+        let prefix_lowercase = prefix.to_lowercase();
+        let mut names: Vec<String> = entries
+            .iter()
+            .filter(|entry| vfs.is_dir(entry))
+            .filter_map(|entry| entry.file_name())
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .filter(|name| name.to_lowercase().starts_with(&prefix_lowercase))
+            .collect();
+        names.sort();
+
+        self.completions = names
+            .iter()
+            .map(|name| format!("{}{}{}", typed_dir, name, MAIN_SEPARATOR))
+            .collect();
+        self.completion_idx = 0;
+
+        if let Some(first) = self.completions.first() {
+            self.text = first.clone();
+        }
+
+        // A single candidate is final, the next Tab completes inside it
+        if self.completions.len() == 1 {
+            self.completions.clear();
+        }
+    }
+
+    /// Validates the typed text. Returns the folder to open, or sets `error`.
+    pub fn submit(&mut self, lang_string: &LangString, vfs: &mut VfsRouter) -> Option<PathBuf> {
+        let path = Self::expand(&self.text);
+
+        // Connecting can take a while, this is left to the central panel
+        if sftp_vfs::is_sftp_path(&path) {
+            self.stop_editing();
+            return Some(path);
+        }
+
+        if !path.is_absolute() {
+            self.error = Some(lang_string.get(LangKeys::PathNotAbsolute));
+            return None;
+        }
+
+        let metadata = vfs.mount_for(&path).and_then(|_| vfs.stat(&path));
+        match metadata {
+            Ok(metadata) if metadata.is_dir => {
+                self.stop_editing();
+                Some(path)
+            }
+            // Archives are browsed like folders
+            Ok(_) if vfs.list(&path).is_ok() => {
+                self.stop_editing();
+                Some(path)
+            }
+            Ok(_) => {
+                self.error = Some(lang_string.get(LangKeys::PathNotAFolder));
+                None
+            }
+            Err(_) => {
+                self.error = Some(lang_string.get(LangKeys::PathDoesNotExist));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(text: &str) -> String {
+        AddressBar::expand(text).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn expands_the_home_folder() {
+        let home = home_dir().unwrap().to_string_lossy().to_string();
+
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand(" ~/x "), format!("{}/x", home));
+        assert_eq!(expand("~x"), "~x");
+        assert_eq!(expand("/a/~"), "/a/~");
+    }
+
+    #[test]
+    fn expands_variables() {
+        // SAFETY: no other test reads or writes this variable
+        unsafe { env::set_var("ROCKET_EXPAND_TEST", "/var/data") };
+
+        assert_eq!(expand("$ROCKET_EXPAND_TEST/x"), "/var/data/x");
+        assert_eq!(expand("${ROCKET_EXPAND_TEST}x"), "/var/datax");
+        assert_eq!(expand("/a/$ROCKET_EXPAND_TEST.d"), "/a//var/data.d");
+    }
+
+    #[test]
+    fn keeps_unknown_variables_as_typed() {
+        assert_eq!(expand("/a/$ROCKET_UNSET_TEST/b"), "/a/$ROCKET_UNSET_TEST/b");
+        assert_eq!(expand("${ROCKET_UNSET_TEST}/b"), "${ROCKET_UNSET_TEST}/b");
+        assert_eq!(expand("/a/$"), "/a/$");
+        assert_eq!(expand("/a/$/b"), "/a/$/b");
+    }
+}
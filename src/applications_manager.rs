@@ -0,0 +1,292 @@
+use crate::error_manager::AppError;
+use crate::lang_string::LangKeys;
+use crate::xdg;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Application installed on the desktop, read from its `.desktop` file.
+pub struct Application {
+    pub name: String,
+
+    /// Command line with the freedesktop field codes (%f, %U...).
+    exec: String,
+    mime_types: Vec<String>,
+}
+
+impl Application {
+    /// Parses the `[Desktop Entry]` group, None for hidden entries and non applications.
+    fn parse(content: &str) -> Option<Self> {
+        let mut in_main_group = false;
+        let mut name = None;
+        let mut exec = None;
+        let mut mime_types = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_main_group = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_main_group || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            // Localized keys (Name[ro]=...) are ignored
+            match (key.trim(), value.trim()) {
+                ("Type", kind) if kind != "Application" => return None,
+                ("NoDisplay" | "Hidden", "true") => return None,
+                ("Name", value) => name = Some(value.to_string()),
+                ("Exec", value) => exec = Some(value.to_string()),
+                ("MimeType", value) => {
+                    mime_types = value
+                        .split(';')
+                        .filter(|mime_type| !mime_type.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            name: name?,
+            exec: exec?,
+            mime_types,
+        })
+    }
+
+    fn handles(&self, mime_type: &str) -> bool {
+        let group = mime_type.split('/').next().unwrap_or_default();
+
+        self.mime_types
+            .iter()
+            .any(|handled| handled == mime_type || handled.strip_suffix("/*") == Some(group))
+    }
+
+    /// Starts the application with `files`, without waiting for it.
+    pub fn launch(&self, files: &[PathBuf]) -> io::Result<()> {
+        let mut args = exec_args(&self.exec, files).into_iter();
+        let Some(program) = args.next() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("empty command for {}", self.name),
+            ));
+        };
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // Reaped once it exits, it would stay a zombie until Rocket quits otherwise
+        thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+/// Splits an Exec line into arguments and replaces the field codes with `files`.
+fn exec_args(exec: &str, files: &[PathBuf]) -> Vec<String> {
+    let files: Vec<String> = files
+        .iter()
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect();
+
+    let mut args = Vec::new();
+    for word in split_words(exec) {
+        match word.as_str() {
+            "%f" | "%u" => args.extend(files.first().cloned()),
+            "%F" | "%U" => args.extend(files.iter().cloned()),
+
+            // Icon, translated name and location of the desktop file
+            "%i" | "%c" | "%k" => {}
+            _ => args.push(word.replace("%%", "%")),
+        }
+    }
+
+    // Applications without a field code still get the file
+    if !exec.contains("%f") && !exec.contains("%F") && !exec.contains("%u") && !exec.contains("%U")
+    {
+        args.extend(files);
+    }
+
+    args
+}
+
+/// Splits on whitespace, double quoted parts are kept together.
+fn split_words(exec: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => word.extend(chars.next()),
+            c if c.is_whitespace() && !in_quotes => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Every application found inside the XDG `applications` directories, used for "Open with".
+pub struct ApplicationsManager {
+    applications: Vec<Application>,
+
+    /// Unreadable `.desktop` files, until the app takes them to report them.
+    errors: Vec<AppError>,
+}
+
+impl ApplicationsManager {
+    pub fn new() -> Self {
+        let mut applications = Vec::new();
+        let mut errors = Vec::new();
+
+        // A desktop file id found in several directories is taken from the first one
+        let mut seen_ids = HashSet::new();
+        for dir in xdg::data_dirs() {
+            let dir = dir.join("applications");
+            load_dir(&dir, &dir, &mut seen_ids, &mut applications, &mut errors);
+        }
+        applications.sort_by_key(|application: &Application| application.name.to_lowercase());
+
+        Self {
+            applications,
+            errors,
+        }
+    }
+
+    /// Errors met while loading the applications since the last call.
+    pub fn take_errors(&mut self) -> Vec<AppError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Applications which declare they can open one of `mime_types`, most specific type first.
+    pub fn for_mime_types(&self, mime_types: &[String]) -> Vec<&Application> {
+        let mut applications: Vec<&Application> = Vec::new();
+        for mime_type in mime_types {
+            for application in &self.applications {
+                let listed = applications
+                    .iter()
+                    .any(|listed| std::ptr::eq(*listed, application));
+                if application.handles(mime_type) && !listed {
+                    applications.push(application);
+                }
+            }
+        }
+
+        applications
+    }
+}
+
+fn load_dir(
+    root: &Path,
+    dir: &Path,
+    seen_ids: &mut HashSet<String>,
+    applications: &mut Vec<Application>,
+    errors: &mut Vec<AppError>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Linked folders are not followed, a link to a parent would recurse forever
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            load_dir(root, &path, seen_ids, applications, errors);
+            continue;
+        }
+
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "desktop")
+        {
+            continue;
+        }
+
+        // The id of applications/kde/foo.desktop is kde-foo.desktop
+        let id = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('/', "-");
+        if !seen_ids.insert(id) {
+            continue;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(content) => applications.extend(Application::parse(&content)),
+            Err(err) => errors.push(AppError::io(LangKeys::ApplicationLoadError, &err).path(&path)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_desktop_entry() {
+        let application = Application::parse(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Text Editor\n\
+             Name[ro]=Editor de text\n\
+             Exec=editor --new-window %U\n\
+             MimeType=text/plain;image/*;\n\
+             \n\
+             [Desktop Action new]\n\
+             Name=New Window\n",
+        )
+        .unwrap();
+
+        assert_eq!(application.name, "Text Editor");
+        assert!(application.handles("text/plain"));
+        assert!(application.handles("image/png"));
+        assert!(!application.handles("text/html"));
+
+        assert!(Application::parse("[Desktop Entry]\nType=Link\nName=Site\nExec=x\n").is_none());
+        assert!(
+            Application::parse("[Desktop Entry]\nName=Hidden\nExec=x\nNoDisplay=true\n").is_none()
+        );
+    }
+
+    #[test]
+    fn expands_field_codes() {
+        let files = vec![PathBuf::from("/tmp/a b.txt"), PathBuf::from("/tmp/c.txt")];
+
+        assert_eq!(
+            exec_args("editor %F", &files),
+            vec!["editor", "/tmp/a b.txt", "/tmp/c.txt"]
+        );
+        assert_eq!(
+            exec_args("\"/opt/my app/run\" --icon %i %f", &files),
+            vec!["/opt/my app/run", "--icon", "/tmp/a b.txt"]
+        );
+        assert_eq!(
+            exec_args("viewer", &files[..1]),
+            vec!["viewer", "/tmp/a b.txt"]
+        );
+        assert_eq!(exec_args("echo 100%%", &[]), vec!["echo", "100%"]);
+    }
+}
@@ -0,0 +1,779 @@
+use crate::config_manager::CONFIG_DIRECTORY_NAME;
+use crate::progress::ProgressSender;
+use crate::vfs::Vfs;
+use crate::vfs::local_vfs::LocalVfs;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl ArchiveKind {
+    pub const ALL: [ArchiveKind; 4] = [
+        ArchiveKind::Zip,
+        ArchiveKind::Tar,
+        ArchiveKind::TarGz,
+        ArchiveKind::TarZst,
+    ];
+
+    /// Archives are recognized by their (possibly double) extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?.to_lowercase();
+
+        if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if file_name.ends_with(".tar.zst") || file_name.ends_with(".tzst") {
+            Some(ArchiveKind::TarZst)
+        } else if file_name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if file_name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else {
+            None
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::Tar => "tar",
+            ArchiveKind::TarGz => "tar.gz",
+            ArchiveKind::TarZst => "tar.zst",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CompressionLevel {
+    Store,
+    Fast,
+    Normal,
+    Best,
+}
+
+impl CompressionLevel {
+    pub const ALL: [CompressionLevel; 4] = [
+        CompressionLevel::Store,
+        CompressionLevel::Fast,
+        CompressionLevel::Normal,
+        CompressionLevel::Best,
+    ];
+
+    /// Level understood by deflate (zip and gzip), in the 0..=9 range.
+    fn deflate_level(&self) -> u32 {
+        match self {
+            CompressionLevel::Store => 0,
+            CompressionLevel::Fast => 1,
+            CompressionLevel::Normal => 6,
+            CompressionLevel::Best => 9,
+        }
+    }
+
+    /// Level understood by zstd, in the 1..=22 range.
+    /// zstd cannot store without compressing, so Store maps to the fastest level.
+    fn zstd_level(&self) -> i32 {
+        match self {
+            CompressionLevel::Store | CompressionLevel::Fast => 1,
+            CompressionLevel::Normal => 3,
+            CompressionLevel::Best => 19,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchiveEntry {
+    /// Path of the entry relative to the archive root.
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// Listing of every entry of an archive, read once when the archive is opened.
+pub struct ArchiveIndex {
+    pub archive: PathBuf,
+    pub kind: ArchiveKind,
+    entries: BTreeMap<PathBuf, ArchiveEntry>,
+}
+
+impl ArchiveIndex {
+    pub fn read(archive: &Path) -> io::Result<Self> {
+        let Some(kind) = ArchiveKind::from_path(archive) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not a supported archive", archive),
+            ));
+        };
+
+        let mut entries = BTreeMap::new();
+        for entry in read_entries(archive, kind)? {
+            // Many archives do not store their directories explicitly,
+            // so every ancestor of an entry is added as a directory
+            for ancestor in entry.path.ancestors().skip(1) {
+                if ancestor.as_os_str().is_empty() {
+                    break;
+                }
+
+                entries
+                    .entry(ancestor.to_path_buf())
+                    .or_insert_with(|| ArchiveEntry {
+                        path: ancestor.to_path_buf(),
+                        is_dir: true,
+                        size: 0,
+                        modified: None,
+                    });
+            }
+
+            entries.insert(entry.path.clone(), entry);
+        }
+
+        Ok(Self {
+            archive: archive.into(),
+            kind,
+            entries,
+        })
+    }
+
+    pub fn entry(&self, inner: &Path) -> Option<&ArchiveEntry> {
+        self.entries.get(inner)
+    }
+
+    /// Entries placed directly inside `inner` (an empty path is the archive root).
+    pub fn children(&self, inner: &Path) -> Vec<&ArchiveEntry> {
+        self.entries
+            .values()
+            .filter(|entry| entry.path.parent() == Some(inner))
+            .collect()
+    }
+
+    /// Does `path` point inside this archive (the archive file itself excluded)?
+    pub fn contains(&self, path: &Path) -> bool {
+        path != self.archive && path.starts_with(&self.archive)
+    }
+
+    pub fn inner_path<'p>(&self, path: &'p Path) -> Option<&'p Path> {
+        path.strip_prefix(&self.archive).ok()
+    }
+}
+
+/// Splits a virtual path such as `/home/user/file.zip/dir/a.txt`
+/// into the archive on disk and the path inside it.
+pub fn split_archive_path(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    path.ancestors()
+        .find(|ancestor| ArchiveKind::from_path(ancestor).is_some() && ancestor.is_file())
+        .and_then(|archive| {
+            path.strip_prefix(archive)
+                .ok()
+                .map(|inner| (archive.to_path_buf(), inner.to_path_buf()))
+        })
+}
+
+/// Folder an archive is extracted into by "Extract here": `dir/photos.tar.gz` -> `dir/photos`.
+pub fn extraction_directory(archive: &Path) -> PathBuf {
+    const SUFFIXES: [&str; 6] = [".tar.gz", ".tgz", ".tar.zst", ".tzst", ".tar", ".zip"];
+
+    let parent = archive.parent().unwrap_or(Path::new(""));
+    let file_name = archive
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let lowercase_name = file_name.to_lowercase();
+
+    let stem = SUFFIXES
+        .iter()
+        .find(|suffix| lowercase_name.ends_with(*suffix))
+        .and_then(|suffix| file_name.get(..file_name.len() - suffix.len()))
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(file_name.as_str());
+
+    parent.join(stem)
+}
+
+/// `path` of an entry relative to the archive root, without the `./` prefix of archives
+/// made from `.` (e.g. `tar -C dir -czf x.tgz .`). None if it could point outside of the archive.
+fn inner_path(path: &Path) -> Option<PathBuf> {
+    let mut inner = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => inner.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    // `./` alone is the root itself
+    (!inner.as_os_str().is_empty()).then_some(inner)
+}
+
+/// Private folder where entries of an archive, or files of a server, are copied to be opened.
+/// There is one per `source` path so sources with the same name never share it. It lives in
+/// the user cache rather than a shared temporary folder, where another user could create
+/// the path first.
+pub fn open_directory(source: &Path) -> io::Result<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+
+    let directory = dirs::cache_dir()
+        .unwrap_or_else(env::temp_dir)
+        .join(CONFIG_DIRECTORY_NAME)
+        .join("opened")
+        .join(format!("{:016x}", hasher.finish()));
+
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(&directory)?;
+
+    // An existing folder may have been created by someone else
+    if directory.symlink_metadata()?.is_symlink() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{:?} is a symbolic link", directory),
+        ));
+    }
+    #[cfg(unix)]
+    fs::set_permissions(&directory, fs::Permissions::from_mode(0o700))?;
+
+    Ok(directory)
+}
+
+fn tar_reader(archive: &Path, kind: ArchiveKind) -> io::Result<Box<dyn Read>> {
+    let file = BufReader::new(File::open(archive)?);
+
+    Ok(match kind {
+        ArchiveKind::Tar => Box::new(file),
+        ArchiveKind::TarGz => Box::new(GzDecoder::new(file)),
+        ArchiveKind::TarZst => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        ArchiveKind::Zip => unreachable!("zip archives are not tar streams"),
+    })
+}
+
+fn read_entries(archive: &Path, kind: ArchiveKind) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+
+    if kind == ArchiveKind::Zip {
+        let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+        for idx in 0..zip.len() {
+            let file = zip.by_index(idx)?;
+            let Some(path) = file.enclosed_name() else {
+                continue;
+            };
+
+            entries.push(ArchiveEntry {
+                path,
+                is_dir: file.is_dir(),
+                size: file.size(),
+                modified: None,
+            });
+        }
+
+        return Ok(entries);
+    }
+
+    let mut tar = tar::Archive::new(tar_reader(archive, kind)?);
+    for entry in tar.entries()? {
+        let entry = entry?;
+        let Some(path) = inner_path(&entry.path()?) else {
+            continue;
+        };
+
+        let header = entry.header();
+        entries.push(ArchiveEntry {
+            path,
+            is_dir: header.entry_type().is_dir(),
+            size: header.size().unwrap_or(0),
+            modified: header
+                .mtime()
+                .ok()
+                .map(|secs| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs)),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Reads the content of a single file stored in `archive`.
+pub fn read_entry(archive: &Path, inner: &Path) -> io::Result<Vec<u8>> {
+    let Some(kind) = ArchiveKind::from_path(archive) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} is not a supported archive", archive),
+        ));
+    };
+
+    let mut bytes = Vec::new();
+
+    if kind == ArchiveKind::Zip {
+        let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+        let name = inner.to_string_lossy().replace('\\', "/");
+        zip.by_name(&name)?.read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+
+    let mut tar = tar::Archive::new(tar_reader(archive, kind)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if inner_path(&entry.path()?).as_deref() == Some(inner) {
+            entry.read_to_end(&mut bytes)?;
+            return Ok(bytes);
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{:?} does not exist inside {:?}", inner, archive),
+    ))
+}
+
+/// Where should `entry` be written, given the selection it was requested through?
+///
+/// Selecting `dir/sub` and extracting into `dest` produces `dest/sub/...`.
+/// An empty selection extracts the whole archive.
+fn extraction_target(entry: &Path, selection: &[PathBuf], destination: &Path) -> Option<PathBuf> {
+    if selection.is_empty() {
+        return Some(destination.join(entry));
+    }
+
+    selection
+        .iter()
+        .find(|selected| entry.starts_with(selected))
+        .and_then(|selected| {
+            let base = selected.parent().unwrap_or(Path::new(""));
+            entry
+                .strip_prefix(base)
+                .ok()
+                .map(|relative| destination.join(relative))
+        })
+}
+
+/// Fails if `path`, or one of its folders below `destination`, is a symbolic link.
+/// A crafted archive could otherwise store `dir -> /etc`, then write `dir/passwd`.
+fn check_no_links(destination: &Path, path: &Path) -> io::Result<()> {
+    let Ok(relative) = path.strip_prefix(destination) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} is outside of {:?}", path, destination),
+        ));
+    };
+
+    let mut current = destination.to_path_buf();
+    for component in relative.components() {
+        current.push(component);
+        if current
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.file_type().is_symlink())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "refusing to extract through the symbolic link {:?}",
+                    current
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the folders of `target` and returns where the entry is written.
+/// Existing files are never overwritten, the entry gets a free name next to them.
+fn free_target(destination: &Path, target: &Path) -> io::Result<PathBuf> {
+    let (Some(parent), Some(file_name)) = (target.parent(), target.file_name()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{:?} has no file name", target),
+        ));
+    };
+
+    check_no_links(destination, parent)?;
+    fs::create_dir_all(parent)?;
+
+    if target.symlink_metadata().is_err() {
+        return Ok(target.to_path_buf());
+    }
+    Ok(LocalVfs.unused_path(parent, &file_name.to_string_lossy()))
+}
+
+/// Extracts `selection` (paths relative to the archive root) into `destination`.
+/// Progress is reported through `progress`, this is meant to run on a background thread.
+pub fn extract(
+    archive: &Path,
+    selection: &[PathBuf],
+    destination: &Path,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    let Some(kind) = ArchiveKind::from_path(archive) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} is not a supported archive", archive),
+        ));
+    };
+
+    let total = read_entries(archive, kind)?
+        .iter()
+        .filter(|entry| extraction_target(&entry.path, selection, destination).is_some())
+        .map(|entry| entry.size)
+        .sum();
+    progress.total(total);
+
+    if kind == ArchiveKind::Zip {
+        let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+        for idx in 0..zip.len() {
+            let mut file = zip.by_index(idx)?;
+            let Some(path) = file.enclosed_name() else {
+                continue;
+            };
+            let Some(target) = extraction_target(&path, selection, destination) else {
+                continue;
+            };
+
+            if file.is_dir() {
+                check_no_links(destination, &target)?;
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+
+            // create_new also refuses to write through a link created meanwhile
+            let target = free_target(destination, &target)?;
+            let bytes = io::copy(&mut file, &mut BufWriter::new(File::create_new(&target)?))?;
+            progress.advance(bytes, &path)?;
+        }
+
+        return Ok(());
+    }
+
+    let mut tar = tar::Archive::new(tar_reader(archive, kind)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let Some(path) = inner_path(&entry.path()?) else {
+            continue;
+        };
+        let Some(target) = extraction_target(&path, selection, destination) else {
+            continue;
+        };
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            check_no_links(destination, &target)?;
+            fs::create_dir_all(&target)?;
+            continue;
+        }
+
+        let target = free_target(destination, &target)?;
+
+        // Hard links point to another entry of the archive, never to a file outside of it
+        if entry_type.is_hard_link() {
+            let source = entry
+                .link_name()?
+                .and_then(|link_name| inner_path(&link_name))
+                .and_then(|link_name| extraction_target(&link_name, selection, destination));
+            let Some(source) = source else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("the hard link {:?} points outside of the archive", path),
+                ));
+            };
+
+            check_no_links(destination, &source)?;
+            fs::hard_link(&source, &target)?;
+            progress.advance(0, &path)?;
+            continue;
+        }
+
+        let bytes = entry.size();
+        entry.unpack(&target)?;
+        progress.advance(bytes, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Every file below `files`, paired with the name it gets inside the archive.
+fn collect_archive_members(files: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    fn walk(path: &Path, name: PathBuf, members: &mut Vec<(PathBuf, PathBuf)>) {
+        members.push((path.to_path_buf(), name.clone()));
+
+        // Links are stored as links, following them could loop forever
+        let is_dir = path
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.is_dir());
+        if is_dir && let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                walk(&entry.path(), name.join(entry.file_name()), members);
+            }
+        }
+    }
+
+    let mut members = Vec::new();
+    for file in files {
+        if let Some(file_name) = file.file_name() {
+            walk(file, PathBuf::from(file_name), &mut members);
+        }
+    }
+
+    members
+}
+
+/// Creates `destination` from `files`, meant to run on a background thread.
+pub fn create(
+    files: &[PathBuf],
+    destination: &Path,
+    kind: ArchiveKind,
+    level: CompressionLevel,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    let members = collect_archive_members(files);
+    let total = members
+        .iter()
+        .filter_map(|(path, _)| path.symlink_metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum();
+    progress.total(total);
+
+    let output = BufWriter::new(File::create(destination)?);
+
+    if kind == ArchiveKind::Zip {
+        let method = match level {
+            CompressionLevel::Store => CompressionMethod::Stored,
+            _ => CompressionMethod::Deflated,
+        };
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .compression_level(Some(level.deflate_level() as i64));
+
+        let mut zip = ZipWriter::new(output);
+        for (path, name) in &members {
+            let name = name.to_string_lossy().replace('\\', "/");
+            let metadata = path.symlink_metadata()?;
+            if metadata.is_symlink() {
+                let target = fs::read_link(path)?;
+                zip.add_symlink(name, target.to_string_lossy(), options)?;
+                continue;
+            }
+            if metadata.is_dir() {
+                zip.add_directory(name, options)?;
+                continue;
+            }
+
+            zip.start_file(name, options)?;
+            let bytes = io::copy(&mut File::open(path)?, &mut zip)?;
+            progress.advance(bytes, path)?;
+        }
+        zip.finish()?.flush()?;
+
+        return Ok(());
+    }
+
+    fn append_members<W: Write>(
+        builder: &mut tar::Builder<W>,
+        members: &[(PathBuf, PathBuf)],
+        progress: &ProgressSender,
+    ) -> io::Result<()> {
+        // Links are stored as links instead of the file they point to
+        builder.follow_symlinks(false);
+
+        for (path, name) in members {
+            let metadata = path.symlink_metadata()?;
+            if metadata.is_dir() {
+                builder.append_dir(name, path)?;
+                continue;
+            }
+
+            builder.append_path_with_name(path, name)?;
+            let bytes = if metadata.is_file() {
+                metadata.len()
+            } else {
+                0
+            };
+            progress.advance(bytes, path)?;
+        }
+
+        Ok(())
+    }
+
+    match kind {
+        ArchiveKind::Tar => {
+            let mut builder = tar::Builder::new(output);
+            append_members(&mut builder, &members, progress)?;
+            builder.into_inner()?.flush()?;
+        }
+        ArchiveKind::TarGz => {
+            let encoder = GzEncoder::new(output, Compression::new(level.deflate_level()));
+            let mut builder = tar::Builder::new(encoder);
+            append_members(&mut builder, &members, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+        }
+        ArchiveKind::TarZst => {
+            let encoder = zstd::stream::write::Encoder::new(output, level.zstd_level())?;
+            let mut builder = tar::Builder::new(encoder);
+            append_members(&mut builder, &members, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+        }
+        ArchiveKind::Zip => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_archive_extensions() {
+        let kind = |name: &str| ArchiveKind::from_path(Path::new(name));
+
+        assert_eq!(kind("/a/b.zip"), Some(ArchiveKind::Zip));
+        assert_eq!(kind("b.TAR"), Some(ArchiveKind::Tar));
+        assert_eq!(kind("b.tar.gz"), Some(ArchiveKind::TarGz));
+        assert_eq!(kind("b.tgz"), Some(ArchiveKind::TarGz));
+        assert_eq!(kind("b.tar.zst"), Some(ArchiveKind::TarZst));
+        assert_eq!(kind("b.tzst"), Some(ArchiveKind::TarZst));
+        assert_eq!(kind("b.gz"), None);
+        assert_eq!(kind("zip"), None);
+    }
+
+    #[test]
+    fn splits_paths_inside_archives() {
+        let dir = std::env::temp_dir().join(format!("rocket_split_test_{}", std::process::id()));
+        fs::create_dir_all(dir.join("folder.zip")).unwrap();
+        File::create(dir.join("photos.tar.gz")).unwrap();
+
+        assert_eq!(
+            split_archive_path(&dir.join("photos.tar.gz/2024/a.jpg")),
+            Some((dir.join("photos.tar.gz"), PathBuf::from("2024/a.jpg")))
+        );
+        assert_eq!(
+            split_archive_path(&dir.join("photos.tar.gz")),
+            Some((dir.join("photos.tar.gz"), PathBuf::new()))
+        );
+        // A folder named like an archive is a plain folder
+        assert_eq!(split_archive_path(&dir.join("folder.zip/a.txt")), None);
+        assert_eq!(split_archive_path(&dir), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn places_extracted_entries() {
+        let destination = Path::new("/dest");
+
+        // Everything
+        assert_eq!(
+            extraction_target(Path::new("dir/sub/a.txt"), &[], destination),
+            Some(PathBuf::from("/dest/dir/sub/a.txt"))
+        );
+
+        // A selected folder keeps its own name, not its parents
+        let selection = [PathBuf::from("dir/sub")];
+        assert_eq!(
+            extraction_target(Path::new("dir/sub/a.txt"), &selection, destination),
+            Some(PathBuf::from("/dest/sub/a.txt"))
+        );
+        assert_eq!(
+            extraction_target(Path::new("dir/other.txt"), &selection, destination),
+            None
+        );
+        // "dir/subway" is not inside "dir/sub"
+        assert_eq!(
+            extraction_target(Path::new("dir/subway/a.txt"), &selection, destination),
+            None
+        );
+    }
+
+    #[test]
+    fn names_extraction_directories() {
+        assert_eq!(
+            extraction_directory(Path::new("/dir/photos.tar.gz")),
+            PathBuf::from("/dir/photos")
+        );
+        assert_eq!(
+            extraction_directory(Path::new("/dir/Backup.ZIP")),
+            PathBuf::from("/dir/Backup")
+        );
+        assert_eq!(
+            extraction_directory(Path::new("/dir/a.b.tzst")),
+            PathBuf::from("/dir/a.b")
+        );
+        // Nothing would be left of the name
+        assert_eq!(
+            extraction_directory(Path::new("/dir/.zip")),
+            PathBuf::from("/dir/.zip")
+        );
+    }
+    #[test]
+    fn reads_tar_entries_prefixed_with_dot() {
+        let dir = std::env::temp_dir().join(format!("rocket_dot_tar_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // `tar -C dir -cf x.tar .` names every entry `./...`, set raw so nothing normalizes it
+        let archive = dir.join("x.tar");
+        let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+        for (name, content) in [
+            ("./", None),
+            ("./docs/a.txt", Some("a")),
+            ("./../b.txt", Some("b")),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_mode(0o755);
+            match content {
+                Some(content) => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_size(content.len() as u64);
+                }
+                None => header.set_entry_type(tar::EntryType::Directory),
+            }
+            header.set_cksum();
+            builder
+                .append(&header, content.unwrap_or_default().as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().flush().unwrap();
+
+        let paths: Vec<_> = read_entries(&archive, ArchiveKind::Tar)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(paths, vec![PathBuf::from("docs/a.txt")]);
+        assert_eq!(read_entry(&archive, Path::new("docs/a.txt")).unwrap(), b"a");
+
+        let (sender, _receiver) = std::sync::mpsc::channel();
+        let progress = ProgressSender::new(sender, Default::default());
+        let destination = dir.join("out");
+        fs::create_dir_all(&destination).unwrap();
+        extract(&archive, &[PathBuf::from("docs")], &destination, &progress).unwrap();
+        assert_eq!(
+            fs::read_to_string(destination.join("docs/a.txt")).unwrap(),
+            "a"
+        );
+        assert!(!dir.join("b.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use crate::command_registry::CommandId;
+use crate::fuzzy::fuzzy_score;
+use crate::lang_string::LangString;
+
+/// Entry listed by the palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PaletteEntry {
+    Command(CommandId),
+
+    /// Index of the action in the user actions of the config.
+    UserAction(usize),
+}
+
+/// Fuzzy search over every command (Ctrl+Shift+P).
+pub struct CommandPalette {
+    pub visible: bool,
+    pub request_focus: bool,
+    pub query: String,
+
+    /// User actions applicable to the selection when the palette was opened, with their name.
+    pub user_actions: Vec<(usize, String)>,
+
+    /// Entries whose name matches query, best match first.
+    pub matches: Vec<PaletteEntry>,
+
+    /// Index inside matches highlighted with the arrow keys.
+    pub selected_idx: usize,
+}
+
+impl CommandPalette {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            request_focus: false,
+            query: String::new(),
+            user_actions: Vec::new(),
+            matches: Vec::new(),
+            selected_idx: 0,
+        }
+    }
+
+    pub fn open(&mut self, lang_string: &LangString, user_actions: Vec<(usize, String)>) {
+        self.visible = true;
+        self.user_actions = user_actions;
+        self.request_focus = true;
+        self.query.clear();
+        self.update_matches(lang_string);
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.matches.clear();
+        self.user_actions.clear();
+    }
+
+    /// Commands are searched by their translated name, user actions by the name from the config.
+    pub fn update_matches(&mut self, lang_string: &LangString) {
+        let mut scored: Vec<(i64, PaletteEntry)> = CommandId::ALL
+            .iter()
+            .filter(|command| **command != CommandId::CommandPalette)
+            .filter_map(|command| {
+                fuzzy_score(&self.query, &lang_string.get(command.name_key()))
+                    .map(|score| (score, PaletteEntry::Command(*command)))
+            })
+            .collect();
+        scored.extend(self.user_actions.iter().filter_map(|(idx, name)| {
+            fuzzy_score(&self.query, name).map(|score| (score, PaletteEntry::UserAction(*idx)))
+        }));
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        self.matches = scored.into_iter().map(|(_, entry)| entry).collect();
+        self.selected_idx = 0;
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected_idx = self.selected_idx.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected_idx + 1 < self.matches.len() {
+            self.selected_idx += 1;
+        }
+    }
+
+    pub fn selected(&self) -> Option<PaletteEntry> {
+        self.matches.get(self.selected_idx).copied()
+    }
+
+    /// Name displayed for `entry`.
+    pub fn entry_name(&self, lang_string: &LangString, entry: PaletteEntry) -> String {
+        match entry {
+            PaletteEntry::Command(command) => lang_string.get(command.name_key()),
+            PaletteEntry::UserAction(idx) => self
+                .user_actions
+                .iter()
+                .find(|(user_action_idx, _)| *user_action_idx == idx)
+                .map(|(_, name)| name.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use crate::lang_string::LangKeys;
+use egui::{Context, Key, KeyboardShortcut, Modifiers};
+use std::collections::BTreeMap;
+
+/// Every action which can be bound to a shortcut or run from the command palette.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum CommandId {
+    GoBack,
+    GoForward,
+    GoUp,
+    EditLocation,
+    FindLocation,
+    CommandPalette,
+    Settings,
+    ToggleHidden,
+    ListView,
+    GridView,
+    Refresh,
+    Copy,
+    Cut,
+    Paste,
+    Compress,
+    SelectAll,
+    InvertSelection,
+    SelectByPattern,
+    Rename,
+    MoveToTrash,
+    CopyPath,
+    Properties,
+    NewFolder,
+    NewFile,
+    CreateSymlink,
+    CreateHardLink,
+    GoToLinkTarget,
+    ToggleTerminal,
+    ToggleJobs,
+    ErrorLog,
+}
+
+impl CommandId {
+    pub const ALL: [CommandId; 30] = [
+        CommandId::GoBack,
+        CommandId::GoForward,
+        CommandId::GoUp,
+        CommandId::EditLocation,
+        CommandId::FindLocation,
+        CommandId::CommandPalette,
+        CommandId::Settings,
+        CommandId::ToggleHidden,
+        CommandId::ListView,
+        CommandId::GridView,
+        CommandId::Refresh,
+        CommandId::Copy,
+        CommandId::Cut,
+        CommandId::Paste,
+        CommandId::Compress,
+        CommandId::SelectAll,
+        CommandId::InvertSelection,
+        CommandId::SelectByPattern,
+        CommandId::Rename,
+        CommandId::MoveToTrash,
+        CommandId::CopyPath,
+        CommandId::Properties,
+        CommandId::NewFolder,
+        CommandId::NewFile,
+        CommandId::CreateSymlink,
+        CommandId::CreateHardLink,
+        CommandId::GoToLinkTarget,
+        CommandId::ToggleTerminal,
+        CommandId::ToggleJobs,
+        CommandId::ErrorLog,
+    ];
+
+    /// Stable name used for the bindings inside the config file.
+    pub fn id(&self) -> &'static str {
+        match self {
+            CommandId::GoBack => "navigation.back",
+            CommandId::GoForward => "navigation.forward",
+            CommandId::GoUp => "navigation.up",
+            CommandId::EditLocation => "navigation.edit_location",
+            CommandId::FindLocation => "navigation.find_location",
+            CommandId::CommandPalette => "app.command_palette",
+            CommandId::Settings => "app.settings",
+            CommandId::ToggleHidden => "view.toggle_hidden",
+            CommandId::ListView => "view.list",
+            CommandId::GridView => "view.grid",
+            CommandId::Refresh => "view.refresh",
+            CommandId::Copy => "file.copy",
+            CommandId::Cut => "file.cut",
+            CommandId::Paste => "file.paste",
+            CommandId::Compress => "file.compress",
+            CommandId::SelectAll => "selection.all",
+            CommandId::InvertSelection => "selection.invert",
+            CommandId::SelectByPattern => "selection.pattern",
+            CommandId::Rename => "file.rename",
+            CommandId::MoveToTrash => "file.trash",
+            CommandId::CopyPath => "file.copy_path",
+            CommandId::Properties => "file.properties",
+            CommandId::NewFolder => "file.new_folder",
+            CommandId::NewFile => "file.new_file",
+            CommandId::CreateSymlink => "file.create_symlink",
+            CommandId::CreateHardLink => "file.create_hard_link",
+            CommandId::GoToLinkTarget => "navigation.link_target",
+            CommandId::ToggleTerminal => "view.terminal",
+            CommandId::ToggleJobs => "view.jobs",
+            CommandId::ErrorLog => "view.errors",
+        }
+    }
+
+    pub fn name_key(&self) -> LangKeys {
+        match self {
+            CommandId::GoBack => LangKeys::GoBack,
+            CommandId::GoForward => LangKeys::GoForward,
+            CommandId::GoUp => LangKeys::GoUp,
+            CommandId::EditLocation => LangKeys::EditLocation,
+            CommandId::FindLocation => LangKeys::GoToLocation,
+            CommandId::CommandPalette => LangKeys::CommandPalette,
+            CommandId::Settings => LangKeys::Settings,
+            CommandId::ToggleHidden => LangKeys::ToggleHiddenFiles,
+            CommandId::ListView => LangKeys::ViewAsList,
+            CommandId::GridView => LangKeys::ViewAsGrid,
+            CommandId::Refresh => LangKeys::Refresh,
+            CommandId::Copy => LangKeys::Copy,
+            CommandId::Cut => LangKeys::Cut,
+            CommandId::Paste => LangKeys::Paste,
+            CommandId::Compress => LangKeys::Compress,
+            CommandId::SelectAll => LangKeys::SelectAll,
+            CommandId::InvertSelection => LangKeys::InvertSelection,
+            CommandId::SelectByPattern => LangKeys::SelectByPattern,
+            CommandId::Rename => LangKeys::Rename,
+            CommandId::MoveToTrash => LangKeys::MoveToTrash,
+            CommandId::CopyPath => LangKeys::CopyPath,
+            CommandId::Properties => LangKeys::Properties,
+            CommandId::NewFolder => LangKeys::NewFolder,
+            CommandId::NewFile => LangKeys::NewFile,
+            CommandId::CreateSymlink => LangKeys::CreateSymlink,
+            CommandId::CreateHardLink => LangKeys::CreateHardLink,
+            CommandId::GoToLinkTarget => LangKeys::GoToLinkTarget,
+            CommandId::ToggleTerminal => LangKeys::ToggleTerminal,
+            CommandId::ToggleJobs => LangKeys::ToggleJobs,
+            CommandId::ErrorLog => LangKeys::ShowErrorLog,
+        }
+    }
+
+    pub fn default_binding(&self) -> Option<KeyboardShortcut> {
+        let shortcut = |modifiers, key| Some(KeyboardShortcut::new(modifiers, key));
+
+        match self {
+            CommandId::GoBack => shortcut(Modifiers::ALT, Key::ArrowLeft),
+            CommandId::GoForward => shortcut(Modifiers::ALT, Key::ArrowRight),
+            CommandId::GoUp => shortcut(Modifiers::ALT, Key::ArrowUp),
+            CommandId::EditLocation => shortcut(Modifiers::COMMAND, Key::L),
+            CommandId::FindLocation => shortcut(Modifiers::COMMAND, Key::P),
+            CommandId::CommandPalette => shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::P),
+            CommandId::Settings => shortcut(Modifiers::COMMAND, Key::Comma),
+            CommandId::ToggleHidden => shortcut(Modifiers::COMMAND, Key::H),
+            CommandId::ListView => shortcut(Modifiers::COMMAND, Key::Num1),
+            CommandId::GridView => shortcut(Modifiers::COMMAND, Key::Num2),
+            CommandId::Refresh => shortcut(Modifiers::NONE, Key::F5),
+            CommandId::Copy => shortcut(Modifiers::COMMAND, Key::C),
+            CommandId::Cut => shortcut(Modifiers::COMMAND, Key::X),
+            CommandId::Paste => shortcut(Modifiers::COMMAND, Key::V),
+            CommandId::Compress => None,
+            CommandId::SelectAll => shortcut(Modifiers::COMMAND, Key::A),
+            CommandId::InvertSelection => shortcut(Modifiers::COMMAND, Key::I),
+            CommandId::SelectByPattern => shortcut(Modifiers::COMMAND, Key::S),
+            CommandId::Rename => shortcut(Modifiers::NONE, Key::F2),
+            CommandId::MoveToTrash => shortcut(Modifiers::NONE, Key::Delete),
+            CommandId::CopyPath => shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::C),
+            CommandId::Properties => shortcut(Modifiers::ALT, Key::Enter),
+            CommandId::NewFolder => shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::N),
+            CommandId::NewFile => None,
+            CommandId::CreateSymlink => None,
+            CommandId::CreateHardLink => None,
+            CommandId::GoToLinkTarget => shortcut(Modifiers::COMMAND, Key::J),
+            CommandId::ToggleTerminal => shortcut(Modifiers::COMMAND, Key::Backtick),
+            CommandId::ToggleJobs => shortcut(Modifiers::COMMAND | Modifiers::SHIFT, Key::J),
+            CommandId::ErrorLog => None,
+        }
+    }
+
+    /// Whether the shortcut still runs the command while a text field has the keyboard focus.
+    /// Copy, paste and the likes must reach the text field instead.
+    /// The terminal takes the keyboard focus too, it can be hidden from inside.
+    fn works_while_typing(&self) -> bool {
+        matches!(
+            self,
+            CommandId::FindLocation
+                | CommandId::CommandPalette
+                | CommandId::Settings
+                | CommandId::ToggleTerminal
+        )
+    }
+}
+
+/// Binding of every command: its default one unless the config overrides it.
+pub struct CommandRegistry {
+    bindings: Vec<(CommandId, Option<KeyboardShortcut>)>,
+}
+
+impl CommandRegistry {
+    /// `overrides` maps command ids to shortcuts such as "Ctrl+Shift+P", an empty string unbinds.
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        for id in overrides.keys() {
+            if !CommandId::ALL.iter().any(|command| command.id() == id) {
+                eprintln!("[CommandRegistry->new()] Unknown command {}", id);
+            }
+        }
+
+        let mut bindings: Vec<(CommandId, Option<KeyboardShortcut>)> = CommandId::ALL
+            .iter()
+            .map(|command| {
+                let binding = match overrides.get(command.id()) {
+                    Some(text) if text.trim().is_empty() => None,
+                    Some(text) => parse_shortcut(text).or_else(|| {
+                        eprintln!(
+                            "[CommandRegistry->new()->parse_shortcut] Invalid shortcut {} for {}",
+                            text,
+                            command.id()
+                        );
+                        command.default_binding()
+                    }),
+                    None => command.default_binding(),
+                };
+
+                (*command, binding)
+            })
+            .collect();
+
+        // egui ignores Shift when the shortcut does not need it, so Ctrl+P would also
+        // consume Ctrl+Shift+P: shortcuts with more modifiers must be checked first
+        bindings.sort_by_key(|(_, binding)| {
+            std::cmp::Reverse(binding.map(|binding| modifier_count(binding.modifiers)))
+        });
+
+        Self { bindings }
+    }
+
+    pub fn binding(&self, command: CommandId) -> Option<KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .find(|(id, _)| *id == command)
+            .and_then(|(_, binding)| *binding)
+    }
+
+    /// Consumes the shortcuts pressed this frame and returns their commands.
+    pub fn pressed_commands(&self, ctx: &Context) -> Vec<CommandId> {
+        let typing = ctx.wants_keyboard_input();
+
+        ctx.input_mut(|input_state| {
+            self.bindings
+                .iter()
+                .filter(|(command, _)| !typing || command.works_while_typing())
+                .filter_map(|(command, binding)| binding.map(|binding| (*command, binding)))
+                .filter(|(_, binding)| input_state.consume_shortcut(binding))
+                .map(|(command, _)| command)
+                .collect()
+        })
+    }
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    usize::from(modifiers.command || modifiers.ctrl)
+        + usize::from(modifiers.shift)
+        + usize::from(modifiers.alt)
+}
+
+/// Parses shortcuts such as "Ctrl+Shift+P", "Alt+Left" or "F5".
+/// Ctrl and Cmd both mean the platform command key.
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+
+    for part in text.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "cmd" | "command" => modifiers = modifiers | Modifiers::COMMAND,
+            "shift" => modifiers = modifiers | Modifiers::SHIFT,
+            "alt" | "option" => modifiers = modifiers | Modifiers::ALT,
+            _ if key.is_none() => key = Some(Key::from_name(part)?),
+            _ => return None,
+        }
+    }
+
+    key.map(|key| KeyboardShortcut::new(modifiers, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shortcuts() {
+        assert_eq!(
+            parse_shortcut("Ctrl+Shift+P"),
+            Some(KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::P))
+        );
+        assert_eq!(
+            parse_shortcut("alt + Left"),
+            Some(KeyboardShortcut::new(Modifiers::ALT, Key::ArrowLeft))
+        );
+        assert_eq!(
+            parse_shortcut("F5"),
+            Some(KeyboardShortcut::new(Modifiers::NONE, Key::F5))
+        );
+        assert_eq!(parse_shortcut("Ctrl+"), None);
+        assert_eq!(parse_shortcut("Ctrl+A+B"), None);
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let overrides = BTreeMap::from([
+            (String::from("navigation.up"), String::from("Backspace")),
+            (String::from("file.copy"), String::new()),
+        ]);
+        let registry = CommandRegistry::new(&overrides);
+
+        assert_eq!(
+            registry.binding(CommandId::GoUp),
+            Some(KeyboardShortcut::new(Modifiers::NONE, Key::Backspace))
+        );
+        assert_eq!(registry.binding(CommandId::Copy), None);
+        assert_eq!(
+            registry.binding(CommandId::GoBack),
+            CommandId::GoBack.default_binding()
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::error_manager::AppError;
+use crate::lang_string::{LangKeys, LangString, Languages};
+use crate::path_manager::PathManager;
+use crate::user_actions::UserAction;
+use crate::view_settings::ViewMode;
+use eframe::egui;
+use egui::{Context, TextStyle, ThemePreference};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const CONFIG_DIRECTORY_NAME: &str = "rocket";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// A config file which can not be parsed is moved aside under this name.
+const BROKEN_CONFIG_FILE_NAME: &str = "config.toml.bak";
+
+/// Changes are written once the user stops editing for this long (e.g. dragging a slider).
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    /// Follows the theme of the operating system.
+    System,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SizeUnits {
+    /// Powers of 1024: KiB, MiB, GiB...
+    Binary,
+    /// Powers of 1000: KB, MB, GB...
+    Decimal,
+}
+
+/// Operations which ask the user before they run.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    /// Files dropped on a folder.
+    pub move_files: bool,
+    pub delete_files: bool,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            move_files: true,
+            delete_files: true,
+        }
+    }
+}
+
+/// Everything the user can customize, stored as TOML inside the config directory.
+/// Missing fields fall back to their default, so older files keep working.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: Theme,
+    pub language: Languages,
+
+    /// Size of the body text, every other text style is scaled from it.
+    pub font_size: f32,
+
+    /// Dot files are hidden unless this is set.
+    pub show_hidden: bool,
+
+    /// View mode used when there is no previous session to restore.
+    pub default_view: ViewMode,
+    pub confirmations: Confirmations,
+
+    /// chrono strftime format used for creation/access/modification dates.
+    pub date_format: String,
+    pub size_units: SizeUnits,
+
+    /// Height of a file row in the list view.
+    pub row_height: f32,
+
+    /// Icon pack from `<config dir>/rocket/icons` or installed icon theme such as "Adwaita",
+    /// empty for the icons bundled with Rocket.
+    pub icon_theme: String,
+
+    /// Shortcuts replacing the default ones, e.g. `"navigation.up" = "Backspace"`.
+    /// An empty shortcut unbinds the command.
+    pub keybindings: BTreeMap<String, String>,
+
+    /// Shell commands offered in the context menu and the command palette.
+    pub user_actions: Vec<UserAction>,
+}
+
+pub const DEFAULT_DATE_FORMAT: &str = "%d/%m/%Y %T";
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            language: Languages::English,
+            font_size: 14.0,
+            show_hidden: false,
+            default_view: ViewMode::List,
+            confirmations: Confirmations::default(),
+            date_format: String::from(DEFAULT_DATE_FORMAT),
+            size_units: SizeUnits::Binary,
+            row_height: 30.0,
+            icon_theme: String::new(),
+            keybindings: BTreeMap::new(),
+            user_actions: Vec::new(),
+        }
+    }
+}
+
+pub struct ConfigManager {
+    pub config: Config,
+
+    /// None if the platform has no config directory, the config is then never saved.
+    config_path: Option<PathBuf>,
+
+    /// When the latest change not written yet was made.
+    changed_at: Option<Instant>,
+
+    /// Loading or saving errors since the last take_errors().
+    errors: Vec<AppError>,
+
+    /// Whether the settings window is open.
+    pub settings_visible: bool,
+}
+
+impl ConfigManager {
+    pub fn new() -> Self {
+        let mut config_path = dirs::config_dir().map(|config_dir| {
+            config_dir
+                .join(CONFIG_DIRECTORY_NAME)
+                .join(CONFIG_FILE_NAME)
+        });
+        let mut errors = Vec::new();
+
+        let config = match config_path.as_deref().map(Self::load) {
+            Some(Ok(config)) => config,
+            Some(Err(err)) => {
+                eprintln!("[ConfigManager->new()->load] {}", err);
+                let mut error = AppError::io(LangKeys::ConfigLoadError, &err);
+
+                // Saving the defaults would wipe the file, so it is kept aside
+                // or, when it can not even be moved, the config is never saved
+                if let Some(path) = config_path.take() {
+                    let backup = path.with_file_name(BROKEN_CONFIG_FILE_NAME);
+                    match fs::rename(&path, &backup) {
+                        Ok(()) => {
+                            error = error.path(&backup);
+                            config_path = Some(path);
+                        }
+                        Err(err) => eprintln!("[ConfigManager->new()->rename] {}", err),
+                    }
+                }
+
+                errors.push(error);
+                Config::default()
+            }
+            None => Config::default(),
+        };
+
+        Self {
+            config,
+            config_path,
+            changed_at: None,
+            errors,
+            settings_visible: false,
+        }
+    }
+
+    fn load(config_path: &Path) -> io::Result<Config> {
+        let text = match fs::read_to_string(config_path) {
+            Ok(text) => text,
+            // First launch
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err),
+        };
+
+        toml::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Schedules a save, so a burst of changes (e.g. dragging a slider) is written only once.
+    pub fn changed(&mut self) {
+        self.changed_at = Some(Instant::now());
+    }
+
+    /// Saves the pending changes once nothing changed for SAVE_DELAY.
+    pub fn save_when_idle(&mut self, ctx: &Context) {
+        let Some(changed_at) = self.changed_at else {
+            return;
+        };
+
+        let elapsed = changed_at.elapsed();
+        if elapsed < SAVE_DELAY {
+            // Nothing else may wake up the UI once the user stops editing
+            ctx.request_repaint_after(SAVE_DELAY - elapsed);
+            return;
+        }
+
+        self.save_now();
+    }
+
+    /// Saves the pending changes right away, e.g. when the app closes.
+    pub fn save_now(&mut self) {
+        if self.changed_at.take().is_none() {
+            return;
+        }
+
+        if let Err(err) = self.save() {
+            eprintln!("[ConfigManager->save_now()->save] {}", err);
+            self.errors
+                .push(AppError::io(LangKeys::ConfigSaveError, &err));
+        }
+    }
+
+    /// Errors met while loading or saving since the last call.
+    pub fn take_errors(&mut self) -> Vec<AppError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(config_path) = &self.config_path else {
+            return Ok(());
+        };
+
+        if let Some(config_dir) = config_path.parent() {
+            fs::create_dir_all(config_dir)?;
+        }
+
+        let text = toml::to_string_pretty(&self.config)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(config_path, text)
+    }
+
+    /// Applies the config to everything depending on it, on startup and after every change.
+    pub fn apply(
+        &self,
+        ctx: &Context,
+        lang_string: &mut LangString,
+        path_manager: &mut PathManager,
+    ) {
+        ctx.set_theme(match self.config.theme {
+            Theme::Dark => ThemePreference::Dark,
+            Theme::Light => ThemePreference::Light,
+            Theme::System => ThemePreference::System,
+        });
+
+        let font_size = self.config.font_size;
+        ctx.style_mut(|style| {
+            for (text_style, font_id) in style.text_styles.iter_mut() {
+                font_id.size = match text_style {
+                    TextStyle::Small => font_size * 0.65,
+                    TextStyle::Heading => font_size * 1.4,
+                    _ => font_size,
+                };
+            }
+        });
+
+        // Parsing the .ftl files is only worth it when the language actually changed
+        if lang_string.lang() != self.config.language {
+            *lang_string = LangString::from(self.config.language);
+        }
+
+        if path_manager.show_hidden != self.config.show_hidden {
+            path_manager.show_hidden = self.config.show_hidden;
+            path_manager.update_folder_content = true;
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::actions::file_operation::FileOperation;
+use crate::config_manager::Config;
+use crate::lang_string::{LangKeys, LangString};
+use crate::ui::additional_info_panel::format_date_time;
+use egui::{Align2, Area, Button, Context, Frame, Id, Order, RichText, ScrollArea, Ui, Window};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Errors kept inside the log, older ones are dropped.
+const LOG_LENGTH: usize = 500;
+
+/// How long a toast stays on screen, unless the pointer is over it.
+const TOAST_DURATION: Duration = Duration::from_secs(8);
+
+/// Toasts stacked at the same time, the older ones stay inside the log only.
+const MAX_TOASTS: usize = 4;
+
+/// What "Retry" does for an error.
+pub enum Retry {
+    /// Queued again on the actions.
+    Operation(FileOperation),
+
+    /// Lists the current folder again.
+    ReloadFolder,
+}
+
+/// Error reported to the user: what was being done, on which file, and why it failed.
+pub struct AppError {
+    /// What failed, e.g. `LangKeys::CopyError`.
+    pub operation: LangKeys,
+    pub path: Option<PathBuf>,
+
+    /// None for errors not coming from the file system (e.g. git).
+    pub kind: Option<io::ErrorKind>,
+    pub message: String,
+    pub retry: Option<Retry>,
+}
+
+impl AppError {
+    pub fn new(operation: LangKeys, message: impl ToString) -> Self {
+        Self {
+            operation,
+            path: None,
+            kind: None,
+            message: message.to_string(),
+            retry: None,
+        }
+    }
+
+    pub fn io(operation: LangKeys, err: &io::Error) -> Self {
+        Self {
+            kind: Some(err.kind()),
+            ..Self::new(operation, err)
+        }
+    }
+
+    pub fn path(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn retry(mut self, retry: Retry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Plain text copied by "Copy details", e.g. to paste it into a bug report.
+    pub fn details(&self, lang_string: &LangString) -> String {
+        let mut details = lang_string.get(self.operation);
+        if let Some(path) = &self.path {
+            details.push('\n');
+            details.push_str(&path.to_string_lossy());
+        }
+        details.push('\n');
+        details.push_str(&self.message);
+        if let Some(kind) = self.kind {
+            details.push_str(&format!(" ({:?})", kind));
+        }
+
+        details
+    }
+}
+
+struct LoggedError {
+    id: usize,
+    error: AppError,
+    time: SystemTime,
+
+    /// Until when the error is shown as a toast, None once it left the screen.
+    toast_until: Option<Instant>,
+}
+
+/// Every error of the session: the recent ones stacked as toasts,
+/// all of them inside the log window.
+pub struct ErrorManager {
+    /// Oldest first.
+    errors: Vec<LoggedError>,
+    pub log_visible: bool,
+    next_id: usize,
+}
+
+/// What the user clicked on an error.
+enum ErrorResponse {
+    Retry,
+    Dismiss,
+    ShowLog,
+}
+
+impl ErrorManager {
+    pub fn new() -> Self {
+        Self {
+            errors: Vec::new(),
+            log_visible: false,
+            next_id: 0,
+        }
+    }
+
+    /// Shows `error` as a toast and keeps it inside the log.
+    pub fn report(&mut self, error: AppError) {
+        self.push(error, Some(Instant::now() + TOAST_DURATION));
+    }
+
+    /// Keeps `error` inside the log only, for errors too frequent
+    /// to interrupt the user (e.g. unreadable folders while searching).
+    pub fn log(&mut self, error: AppError) {
+        self.push(error, None);
+    }
+
+    fn push(&mut self, error: AppError, toast_until: Option<Instant>) {
+        eprintln!(
+            "[ErrorManager->push()] {:?} {:?}: {}",
+            error.operation, error.path, error.message
+        );
+
+        self.next_id += 1;
+        self.errors.push(LoggedError {
+            id: self.next_id,
+            error,
+            time: SystemTime::now(),
+            toast_until,
+        });
+
+        if self.errors.len() > LOG_LENGTH {
+            self.errors.remove(0);
+        }
+    }
+
+    /// Removes the error with `id`, returning its retry action.
+    fn take_retry(&mut self, id: usize) -> Option<Retry> {
+        let idx = self.errors.iter().position(|logged| logged.id == id)?;
+        self.errors.remove(idx).error.retry
+    }
+
+    /// Renders the toasts and the log window. Returns the retry chosen by the user, if any.
+    pub fn render(
+        &mut self,
+        ctx: &Context,
+        lang_string: &LangString,
+        config: &Config,
+    ) -> Option<Retry> {
+        let now = Instant::now();
+        let mut responses = Vec::new();
+
+        let toasts: Vec<usize> = self
+            .errors
+            .iter()
+            .filter(|logged| logged.toast_until.is_some_and(|until| until > now))
+            .map(|logged| logged.id)
+            .collect();
+        let toasts = &toasts[toasts.len().saturating_sub(MAX_TOASTS)..];
+
+        if !toasts.is_empty() {
+            Area::new(Id::new("error_toasts"))
+                .order(Order::Foreground)
+                .anchor(Align2::RIGHT_BOTTOM, [-12.0, -48.0])
+                .show(ctx, |ui| {
+                    ui.set_max_width(360.0);
+                    for logged in self.errors.iter_mut() {
+                        if !toasts.contains(&logged.id) {
+                            continue;
+                        }
+
+                        let response = Frame::popup(ui.style())
+                            .show(ui, |ui| error_ui(ui, lang_string, &logged.error, true));
+                        if let Some(response) = response.inner {
+                            responses.push((logged.id, response));
+                        }
+
+                        // The toast stays while it is being read
+                        if response.response.contains_pointer() {
+                            logged.toast_until = Some(now + TOAST_DURATION);
+                        }
+                        ui.add_space(4.0);
+                    }
+                });
+
+            // Nothing else wakes up the UI when a toast expires
+            ctx.request_repaint_after(Duration::from_millis(500));
+        }
+
+        let mut clear = false;
+        let mut log_visible = self.log_visible;
+        Window::new(lang_string.get(LangKeys::ErrorLog))
+            .id(Id::new("error_log"))
+            .open(&mut log_visible)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            !self.errors.is_empty(),
+                            Button::new(lang_string.get(LangKeys::ClearLog)),
+                        )
+                        .clicked()
+                    {
+                        clear = true;
+                    }
+                });
+                ui.separator();
+
+                if self.errors.is_empty() {
+                    ui.weak(lang_string.get(LangKeys::NoErrors));
+                    return;
+                }
+
+                ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                    // Most recent first
+                    for logged in self.errors.iter().rev() {
+                        ui.weak(format_date_time(logged.time, &config.date_format));
+                        if let Some(response) = error_ui(ui, lang_string, &logged.error, false) {
+                            responses.push((logged.id, response));
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+        self.log_visible = log_visible;
+
+        if clear {
+            self.errors.clear();
+        }
+
+        let mut retry = None;
+        for (id, response) in responses {
+            match response {
+                ErrorResponse::Retry => retry = self.take_retry(id),
+                ErrorResponse::Dismiss => {
+                    if let Some(logged) = self.errors.iter_mut().find(|logged| logged.id == id) {
+                        logged.toast_until = None;
+                    }
+                }
+                ErrorResponse::ShowLog => self.log_visible = true,
+            }
+        }
+
+        retry
+    }
+}
+
+/// Operation, path and message of `error` with its buttons.
+/// A toast can also be closed and open the log.
+fn error_ui(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    error: &AppError,
+    toast: bool,
+) -> Option<ErrorResponse> {
+    let mut response = None;
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(lang_string.get(error.operation))
+                .strong()
+                .color(ui.visuals().error_fg_color),
+        );
+
+        if toast {
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("✖").clicked() {
+                    response = Some(ErrorResponse::Dismiss);
+                }
+            });
+        }
+    });
+
+    if let Some(path) = &error.path {
+        ui.small(path.to_string_lossy());
+    }
+    ui.label(&error.message);
+
+    ui.horizontal(|ui| {
+        if error.retry.is_some() && ui.button(lang_string.get(LangKeys::Retry)).clicked() {
+            response = Some(ErrorResponse::Retry);
+        }
+        if ui.button(lang_string.get(LangKeys::CopyDetails)).clicked() {
+            ui.ctx().copy_text(error.details(lang_string));
+        }
+        if toast && ui.button(lang_string.get(LangKeys::ErrorLog)).clicked() {
+            response = Some(ErrorResponse::ShowLog);
+        }
+    });
+
+    response
+}
@@ -0,0 +1,77 @@
+/// Scores how well `query` matches `candidate`, None if it does not match at all.
+///
+/// Every character of the query must appear in the candidate, in order (case-insensitive).
+/// Consecutive characters and characters starting a word (after `/`, `_`, `-`, `.`, ` `)
+/// are rewarded, so "dl" ranks "Downloads" below "doc_library".
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut candidate_idx = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars() {
+        if query_char.is_whitespace() {
+            continue;
+        }
+
+        let found = candidate[candidate_idx..]
+            .iter()
+            .position(|c| *c == query_char)
+            .map(|offset| candidate_idx + offset)?;
+
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(candidate[found - 1], '/' | '\\' | '_' | '-' | '.' | ' ') {
+            score += 3;
+        }
+
+        previous_match = Some(found);
+        candidate_idx = found + 1;
+    }
+
+    // Shorter candidates are usually what the user meant
+    Some(score * 100 - candidate.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_every_character_in_order() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("DOC", "documents").is_some());
+        assert!(fuzzy_score("d cs", "docs").is_some());
+        assert_eq!(fuzzy_score("cod", "docs"), None);
+        assert_eq!(fuzzy_score("docsx", "docs"), None);
+    }
+
+    #[test]
+    fn rewards_consecutive_characters() {
+        let consecutive = fuzzy_score("doc", "xdocxx").unwrap();
+        let scattered = fuzzy_score("doc", "xdxoxc").unwrap();
+
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn rewards_word_starts() {
+        let word_start = fuzzy_score("l", "doc_library").unwrap();
+        let inside_word = fuzzy_score("l", "docxlibrary").unwrap();
+        assert!(word_start > inside_word);
+
+        assert!(fuzzy_score("dl", "doc_library") > fuzzy_score("dl", "Downloads"));
+        assert!(fuzzy_score("pr", "/home/projects") > fuzzy_score("pr", "/home/xpr"));
+    }
+
+    #[test]
+    fn prefers_shorter_candidates() {
+        assert!(fuzzy_score("docs", "docs") > fuzzy_score("docs", "docs_old"));
+    }
+}
@@ -0,0 +1,516 @@
+use crate::error_manager::AppError;
+use crate::lang_string::LangKeys;
+use crate::vfs::VfsRouter;
+use egui::Context;
+use git2::build::CheckoutBuilder;
+use git2::{
+    Branch, DiffFormat, DiffOptions, IndexAddOption, ObjectType, Repository, Status, StatusOptions,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::thread;
+
+/// State of a file inside a git work tree, a staged file may be modified again.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct GitStatus {
+    /// Changes added to the index, waiting for the next commit.
+    pub staged: bool,
+
+    /// Changes of the work tree which are not staged.
+    pub modified: bool,
+    pub untracked: bool,
+    pub ignored: bool,
+
+    /// Left with conflict markers by a merge or a rebase.
+    pub conflicted: bool,
+}
+
+impl GitStatus {
+    fn from_status(status: Status) -> Self {
+        Self {
+            staged: status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            ),
+            modified: status.intersects(
+                Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE,
+            ),
+            untracked: status.contains(Status::WT_NEW),
+            ignored: status.contains(Status::IGNORED),
+            conflicted: status.contains(Status::CONFLICTED),
+        }
+    }
+
+    /// Adds the state of a file to the state of a folder containing it.
+    /// Ignored files do not make their folder ignored.
+    fn merge(&mut self, other: GitStatus) {
+        self.staged |= other.staged;
+        self.modified |= other.modified;
+        self.untracked |= other.untracked;
+        self.conflicted |= other.conflicted;
+    }
+
+    pub fn is_clean(&self) -> bool {
+        *self == GitStatus::default()
+    }
+
+    /// Has changes which can be staged.
+    pub fn is_stageable(&self) -> bool {
+        self.modified || self.untracked || self.conflicted
+    }
+
+    /// Has changes a diff can show.
+    pub fn has_changes(&self) -> bool {
+        self.staged || self.modified || self.untracked
+    }
+}
+
+/// Branch checked out in the repository of the current folder.
+pub struct BranchInfo {
+    /// Branch name, or the abbreviated commit id when HEAD is detached.
+    pub name: String,
+
+    /// Remote branch tracked by the branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
+
+    /// Commits not pushed to the upstream yet.
+    pub ahead: usize,
+
+    /// Commits of the upstream not pulled yet.
+    pub behind: usize,
+}
+
+/// Line of a diff, `origin` is `+`, `-` or ` ` for content and `F` or `H` for headers.
+pub struct DiffLine {
+    pub origin: char,
+    pub text: String,
+}
+
+/// Everything read from the repository of a folder, on a background thread.
+#[derive(Default)]
+struct GitSnapshot {
+    work_tree: Option<PathBuf>,
+    branch: Option<BranchInfo>,
+    files: HashMap<PathBuf, GitStatus>,
+    folders: HashMap<PathBuf, GitStatus>,
+    errors: Vec<AppError>,
+}
+
+impl GitSnapshot {
+    fn read(current_path: &Path) -> Self {
+        // Not being inside a repository is the usual case, not an error
+        let Some(repository) = Repository::discover(current_path)
+            .ok()
+            .filter(|repository| repository.workdir().is_some())
+        else {
+            return Self::default();
+        };
+
+        let mut errors = Vec::new();
+        let (files, folders) = read_statuses(&repository, current_path, &mut errors);
+        Self {
+            work_tree: repository.workdir().map(Path::to_path_buf),
+            branch: read_branch(&repository, &mut errors),
+            files,
+            folders,
+            errors,
+        }
+    }
+}
+
+/// Status of the git work tree containing the current folder, read through libgit2.
+/// Large repositories take a while to read, so it is done on a background thread.
+pub struct GitManager {
+    /// Root folder of the repository of the current folder.
+    work_tree: Option<PathBuf>,
+
+    /// Folder the repository and statuses were read for.
+    current_path: PathBuf,
+
+    pub branch: Option<BranchInfo>,
+
+    /// Files reported by git. Untracked and ignored folders are reported
+    /// as a whole, their content is not listed.
+    files: HashMap<PathBuf, GitStatus>,
+
+    /// Folders of the work tree, with the state of everything they contain.
+    folders: HashMap<PathBuf, GitStatus>,
+
+    /// Set by refresh, the statuses are read again on the next update.
+    outdated: bool,
+
+    /// Read in progress, the previous statuses are displayed meanwhile.
+    reading: Option<Receiver<GitSnapshot>>,
+
+    /// Errors of the finished reads, until the app takes them to report them.
+    errors: Vec<AppError>,
+}
+
+impl GitManager {
+    pub fn new() -> Self {
+        Self {
+            work_tree: None,
+            current_path: PathBuf::new(),
+            branch: None,
+            files: HashMap::new(),
+            folders: HashMap::new(),
+            outdated: true,
+            reading: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Files may have been changed, staged or committed since the statuses were read.
+    pub fn refresh(&mut self) {
+        self.outdated = true;
+    }
+
+    /// Starts reading the repository of `current_path` when it changed, or after a refresh,
+    /// and takes the statuses read in the background once they are ready.
+    /// Only local folders are looked at, archives and servers have no work tree.
+    pub fn update(&mut self, ctx: &Context, current_path: &Path, vfs: &VfsRouter) {
+        if let Some(reading) = &self.reading {
+            match reading.try_recv() {
+                Ok(snapshot) => {
+                    self.work_tree = snapshot.work_tree;
+                    self.branch = snapshot.branch;
+                    self.files = snapshot.files;
+                    self.folders = snapshot.folders;
+                    self.errors.extend(snapshot.errors);
+                    self.reading = None;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.reading = None,
+            }
+        }
+
+        if !self.outdated && self.current_path == current_path {
+            return;
+        }
+        self.outdated = false;
+        self.current_path = current_path.to_path_buf();
+
+        if !vfs.is_local(current_path) {
+            self.close();
+            return;
+        }
+
+        // A read started for the previous folder is dropped, its result is not wanted anymore
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        let current_path = current_path.to_path_buf();
+        thread::spawn(move || {
+            if sender.send(GitSnapshot::read(&current_path)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        self.reading = Some(receiver);
+    }
+
+    /// Errors met while reading the repository since the last call.
+    pub fn take_errors(&mut self) -> Vec<AppError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn close(&mut self) {
+        self.work_tree = None;
+        self.branch = None;
+        self.files.clear();
+        self.folders.clear();
+        self.reading = None;
+    }
+
+    /// Root folder of the repository of the current folder, None outside of a repository.
+    pub fn work_tree(&self) -> Option<&Path> {
+        self.work_tree.as_deref()
+    }
+
+    /// State of a file or folder of the current repository, clean outside of it.
+    pub fn status(&self, file: &Path) -> GitStatus {
+        if let Some(status) = self.files.get(file) {
+            return *status;
+        }
+
+        // Content of an untracked or ignored folder
+        if let Some(status) = file
+            .ancestors()
+            .skip(1)
+            .find_map(|ancestor| self.files.get(ancestor))
+        {
+            return *status;
+        }
+
+        self.folders.get(file).copied().unwrap_or_default()
+    }
+}
+
+fn read_branch(repository: &Repository, errors: &mut Vec<AppError>) -> Option<BranchInfo> {
+    let head = match repository.head() {
+        Ok(head) => head,
+        Err(_) => {
+            // No commit yet, HEAD points to a branch which does not exist
+            let head = repository.find_reference("HEAD").ok()?;
+            let name = head.symbolic_target()?.trim_start_matches("refs/heads/");
+            return Some(BranchInfo {
+                name: name.to_string(),
+                upstream: None,
+                ahead: 0,
+                behind: 0,
+            });
+        }
+    };
+
+    if !head.is_branch() {
+        let id = head.target()?.to_string();
+        return Some(BranchInfo {
+            name: id.chars().take(7).collect(),
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+        });
+    }
+
+    let name = head.shorthand().unwrap_or_default().to_string();
+    let local_id = head.target();
+    let upstream = Branch::wrap(head).upstream().ok();
+
+    let mut branch_info = BranchInfo {
+        name,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+    };
+    if let Some(upstream) = upstream {
+        branch_info.upstream = upstream.name().ok().flatten().map(String::from);
+
+        if let (Some(local_id), Some(upstream_id)) = (local_id, upstream.get().target()) {
+            match repository.graph_ahead_behind(local_id, upstream_id) {
+                Ok((ahead, behind)) => {
+                    branch_info.ahead = ahead;
+                    branch_info.behind = behind;
+                }
+                Err(err) => errors.push(AppError::new(LangKeys::GitError, err)),
+            }
+        }
+    }
+
+    Some(branch_info)
+}
+
+/// Statuses of the files reported by git, and of the folders containing them.
+fn read_statuses(
+    repository: &Repository,
+    current_path: &Path,
+    errors: &mut Vec<AppError>,
+) -> (HashMap<PathBuf, GitStatus>, HashMap<PathBuf, GitStatus>) {
+    let mut files = HashMap::new();
+    let mut folders: HashMap<PathBuf, GitStatus> = HashMap::new();
+    let Some(work_tree) = repository.workdir() else {
+        return (files, folders);
+    };
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false);
+
+    let statuses = match repository.statuses(Some(&mut options)) {
+        Ok(statuses) => statuses,
+        Err(err) => {
+            errors.push(AppError::new(LangKeys::GitError, err).path(work_tree));
+            return (files, folders);
+        }
+    };
+
+    for entry in statuses.iter() {
+        // Folders are reported with a trailing slash
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        let file = work_tree.join(path.trim_end_matches('/'));
+        let status = GitStatus::from_status(entry.status());
+
+        if !status.ignored {
+            for folder in file
+                .ancestors()
+                .skip(1)
+                .take_while(|ancestor| *ancestor != work_tree)
+            {
+                folders
+                    .entry(folder.to_path_buf())
+                    .or_default()
+                    .merge(status);
+            }
+        }
+        files.insert(file, status);
+    }
+
+    // Listing every ignored file of the work tree (build outputs, dependencies...) is slow,
+    // only the entries of the current folder are checked
+    for entry in fs::read_dir(current_path).into_iter().flatten().flatten() {
+        let file = entry.path();
+        let Ok(relative) = file.strip_prefix(work_tree) else {
+            continue;
+        };
+        // Rules such as `target/` only match folders written with a trailing slash
+        let is_dir = entry.file_type().is_ok_and(|file_type| file_type.is_dir());
+        let relative = if is_dir {
+            relative.join("")
+        } else {
+            relative.to_path_buf()
+        };
+
+        if !files.contains_key(&file) && repository.is_path_ignored(&relative).unwrap_or(false) {
+            files.insert(
+                file,
+                GitStatus {
+                    ignored: true,
+                    ..GitStatus::default()
+                },
+            );
+        }
+    }
+
+    (files, folders)
+}
+
+/// Repository containing `files` and their paths relative to its work tree.
+fn open_repository(files: &[PathBuf]) -> Result<(Repository, Vec<PathBuf>), git2::Error> {
+    let first = files
+        .first()
+        .ok_or_else(|| git2::Error::from_str("no file given"))?;
+    let repository = Repository::discover(first.parent().unwrap_or(first))?;
+    let work_tree = repository
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("the repository has no work tree"))?
+        .to_path_buf();
+
+    let paths = files
+        .iter()
+        .map(|file| {
+            file.strip_prefix(&work_tree)
+                .map(Path::to_path_buf)
+                .map_err(|_| {
+                    git2::Error::from_str(&format!("{:?} is outside of the repository", file))
+                })
+        })
+        .collect::<Result<Vec<PathBuf>, git2::Error>>()?;
+
+    Ok((repository, paths))
+}
+
+/// Adds the changes of `files` to the index, deleted files included.
+pub fn stage(files: &[PathBuf]) -> Result<(), git2::Error> {
+    let (repository, paths) = open_repository(files)?;
+    let mut index = repository.index()?;
+
+    index.add_all(
+        paths.iter().map(PathBuf::as_path),
+        IndexAddOption::DEFAULT,
+        None,
+    )?;
+    index.update_all(paths.iter().map(PathBuf::as_path), None)?;
+    index.write()
+}
+
+/// Puts the index entries of `files` back to their state in HEAD.
+pub fn unstage(files: &[PathBuf]) -> Result<(), git2::Error> {
+    let (repository, paths) = open_repository(files)?;
+
+    match repository.head() {
+        Ok(head) => {
+            let head = head.peel(ObjectType::Commit)?;
+            repository.reset_default(Some(&head), paths.iter().map(PathBuf::as_path))
+        }
+        Err(_) => {
+            // Before the first commit, every staged file is new
+            let mut index = repository.index()?;
+            index.remove_all(paths.iter().map(PathBuf::as_path), None)?;
+            index.write()
+        }
+    }
+}
+
+/// Replaces the unstaged changes of `files` with their staged content.
+/// Untracked files and staged changes are kept.
+pub fn discard(files: &[PathBuf]) -> Result<(), git2::Error> {
+    let (repository, paths) = open_repository(files)?;
+
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    for path in paths {
+        checkout.path(path);
+    }
+
+    repository.checkout_index(None, Some(&mut checkout))
+}
+
+/// Changes of `file` since the last commit, staged or not.
+pub fn diff(file: &Path) -> Result<Vec<DiffLine>, git2::Error> {
+    let (repository, paths) = open_repository(&[file.to_path_buf()])?;
+
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(true)
+        .show_untracked_content(true)
+        .recurse_untracked_dirs(true);
+    for path in paths {
+        options.pathspec(path);
+    }
+
+    // No tree before the first commit, everything is new
+    let head_tree = repository
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok());
+    let diff =
+        repository.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))?;
+
+    let mut lines = Vec::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        lines.push(DiffLine {
+            origin: line.origin(),
+            text: String::from_utf8_lossy(line.content())
+                .trim_end_matches('\n')
+                .to_string(),
+        });
+        true
+    })?;
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_status() {
+        let status = GitStatus::from_status(Status::INDEX_MODIFIED | Status::WT_MODIFIED);
+        assert!(status.staged && status.modified);
+        assert!(!status.untracked && !status.ignored && !status.conflicted);
+
+        assert!(GitStatus::from_status(Status::CURRENT).is_clean());
+        assert!(GitStatus::from_status(Status::WT_NEW).is_stageable());
+        assert!(!GitStatus::from_status(Status::IGNORED).has_changes());
+    }
+
+    #[test]
+    fn folders_ignore_ignored_files() {
+        let mut folder = GitStatus::default();
+        folder.merge(GitStatus::from_status(Status::IGNORED));
+        assert!(folder.is_clean());
+
+        folder.merge(GitStatus::from_status(Status::WT_NEW));
+        assert!(folder.untracked);
+    }
+}
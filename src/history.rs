@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+
+/// Oldest locations are forgotten past this many entries.
+const MAX_HISTORY_ENTRIES: usize = 100;
+
+/// Linear browser-like history: going somewhere new after going back
+/// drops every location that was ahead.
+pub struct History {
+    entries: Vec<PathBuf>,
+
+    /// Index of the current location inside entries.
+    current: usize,
+}
+
+impl History {
+    pub fn new(start: PathBuf) -> Self {
+        Self {
+            entries: vec![start],
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Path {
+        &self.entries[self.current]
+    }
+
+    pub fn push(&mut self, path: PathBuf) {
+        if self.current() == path {
+            return;
+        }
+
+        self.entries.truncate(self.current + 1);
+        self.entries.push(path);
+
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.current = self.entries.len() - 1;
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.current + 1 < self.entries.len()
+    }
+
+    pub fn back(&mut self) -> Option<&Path> {
+        if !self.can_go_back() {
+            return None;
+        }
+
+        self.current -= 1;
+        Some(self.current())
+    }
+
+    pub fn forward(&mut self) -> Option<&Path> {
+        if !self.can_go_forward() {
+            return None;
+        }
+
+        self.current += 1;
+        Some(self.current())
+    }
+
+    /// Jumps to an entry listed by back_entries() or forward_entries().
+    pub fn go_to(&mut self, idx: usize) -> Option<&Path> {
+        if idx >= self.entries.len() {
+            return None;
+        }
+
+        self.current = idx;
+        Some(self.current())
+    }
+
+    /// Locations behind the current one, most recent first.
+    pub fn back_entries(&self) -> impl Iterator<Item = (usize, &Path)> {
+        self.entries[..self.current]
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(idx, path)| (idx, path.as_path()))
+    }
+
+    /// Locations ahead of the current one, closest first.
+    pub fn forward_entries(&self) -> impl Iterator<Item = (usize, &Path)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(self.current + 1)
+            .map(|(idx, path)| (idx, path.as_path()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(paths: &[&str]) -> History {
+        let mut history = History::new(PathBuf::from(paths[0]));
+        for path in &paths[1..] {
+            history.push(PathBuf::from(path));
+        }
+        history
+    }
+
+    #[test]
+    fn push_after_back_drops_forward_entries() {
+        let mut history = history(&["/a", "/b", "/c"]);
+
+        assert_eq!(history.back(), Some(Path::new("/b")));
+        assert_eq!(history.back(), Some(Path::new("/a")));
+        assert!(history.can_go_forward());
+
+        history.push(PathBuf::from("/d"));
+
+        assert_eq!(history.current(), Path::new("/d"));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.forward(), None);
+        assert_eq!(
+            history.back_entries().collect::<Vec<_>>(),
+            vec![(0, Path::new("/a"))]
+        );
+    }
+
+    #[test]
+    fn pushing_the_current_location_does_nothing() {
+        let mut history = history(&["/a", "/b"]);
+        history.push(PathBuf::from("/b"));
+
+        assert_eq!(history.back(), Some(Path::new("/a")));
+        assert!(!history.can_go_back());
+    }
+
+    #[test]
+    fn forgets_the_oldest_entries_past_the_cap() {
+        let mut history = History::new(PathBuf::from("/0"));
+        for idx in 1..MAX_HISTORY_ENTRIES + 5 {
+            history.push(PathBuf::from(format!("/{}", idx)));
+        }
+
+        assert_eq!(history.back_entries().count(), MAX_HISTORY_ENTRIES - 1);
+        assert_eq!(history.back_entries().last(), Some((0, Path::new("/5"))));
+        assert_eq!(
+            history.current(),
+            PathBuf::from(format!("/{}", MAX_HISTORY_ENTRIES + 4))
+        );
+    }
+
+    #[test]
+    fn goes_to_listed_entries() {
+        let mut history = history(&["/a", "/b", "/c", "/d"]);
+
+        let (idx, path) = history.back_entries().nth(1).unwrap();
+        assert_eq!(path, Path::new("/b"));
+        assert_eq!(history.go_to(idx), Some(Path::new("/b")));
+        assert_eq!(
+            history.forward_entries().collect::<Vec<_>>(),
+            vec![(2, Path::new("/c")), (3, Path::new("/d"))]
+        );
+
+        assert_eq!(history.go_to(3), Some(Path::new("/d")));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.go_to(4), None);
+        assert_eq!(history.current(), Path::new("/d"));
+    }
+}
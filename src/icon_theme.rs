@@ -0,0 +1,293 @@
+use crate::xdg;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Looked up last, every theme implicitly inherits from it.
+const FALLBACK_THEME: &str = "hicolor";
+pub const ICON_EXTENSIONS: [&str; 2] = ["png", "svg"];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum DirKind {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// Subdirectory of a theme holding icons of one size, e.g. `48x48/mimetypes`.
+#[derive(Debug)]
+struct ThemeDir {
+    path: String,
+    size: u32,
+    kind: DirKind,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDir {
+    /// 0 when the icons of the directory fit `size`, the bigger the worse otherwise.
+    fn size_distance(&self, size: u32) -> u32 {
+        let (min_size, max_size) = match self.kind {
+            DirKind::Fixed => (self.size, self.size),
+            DirKind::Scalable => (self.min_size, self.max_size),
+            DirKind::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+
+        min_size.saturating_sub(size) + size.saturating_sub(max_size)
+    }
+}
+
+/// `index.theme` content used for the lookup.
+struct ThemeIndex {
+    inherits: Vec<String>,
+    dirs: Vec<ThemeDir>,
+}
+
+/// Freedesktop icon theme such as Adwaita or Papirus, see the Icon Theme Specification.
+pub struct IconTheme {
+    /// Every location the theme is installed in, e.g. `~/.icons/Papirus` and `/usr/share/icons/Papirus`.
+    base_dirs: Vec<PathBuf>,
+    dirs: Vec<ThemeDir>,
+
+    /// Themes looked up when this one has no icon, hicolor last.
+    parents: Vec<IconTheme>,
+}
+
+impl IconTheme {
+    /// None if no theme with this name is installed.
+    pub fn load(name: &str) -> Option<Self> {
+        Self::load_inherited(name, &mut Vec::new())
+    }
+
+    /// `loaded` prevents loading a theme twice when several parents share it.
+    fn load_inherited(name: &str, loaded: &mut Vec<String>) -> Option<Self> {
+        loaded.push(name.to_string());
+
+        let base_dirs: Vec<PathBuf> = icon_dirs()
+            .into_iter()
+            .map(|dir| dir.join(name))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let index = base_dirs
+            .iter()
+            .find_map(|dir| fs::read_to_string(dir.join("index.theme")).ok())?;
+        let index = parse_index(&index);
+
+        let mut parents = Vec::new();
+        for parent in index
+            .inherits
+            .iter()
+            .map(String::as_str)
+            .chain([FALLBACK_THEME])
+        {
+            if !loaded.iter().any(|loaded| loaded == parent) {
+                parents.extend(Self::load_inherited(parent, loaded));
+            }
+        }
+
+        Some(Self {
+            base_dirs,
+            dirs: index.dirs,
+            parents,
+        })
+    }
+
+    /// File of the icon `name` whose size is the closest to `size`,
+    /// from this theme or else from the themes it inherits from.
+    pub fn lookup(&self, name: &str, size: u32) -> Option<PathBuf> {
+        self.lookup_here(name, size).or_else(|| {
+            self.parents
+                .iter()
+                .find_map(|parent| parent.lookup(name, size))
+        })
+    }
+
+    fn lookup_here(&self, name: &str, size: u32) -> Option<PathBuf> {
+        // Among the directories which fit, the one drawn at the requested size wins:
+        // a 512px icon scaled down to 64px looks and loads worse than a 64px one
+        let mut best: Option<((u32, u32), PathBuf)> = None;
+
+        for dir in &self.dirs {
+            let score = (dir.size_distance(size), dir.size.abs_diff(size));
+            if best
+                .as_ref()
+                .is_some_and(|(best_score, _)| *best_score <= score)
+            {
+                continue;
+            }
+
+            let found = self
+                .base_dirs
+                .iter()
+                .flat_map(|base_dir| {
+                    ICON_EXTENSIONS.iter().map(move |extension| {
+                        base_dir
+                            .join(&dir.path)
+                            .join(format!("{}.{}", name, extension))
+                    })
+                })
+                .find(|path| path.is_file());
+
+            if let Some(path) = found {
+                best = Some((score, path));
+                if score == (0, 0) {
+                    break;
+                }
+            }
+        }
+
+        best.map(|(_, path)| path)
+    }
+}
+
+/// `~/.icons` then the `icons` directory of every XDG data directory.
+fn icon_dirs() -> Vec<PathBuf> {
+    dirs::home_dir()
+        .map(|home_dir| home_dir.join(".icons"))
+        .into_iter()
+        .chain(xdg::data_dirs().into_iter().map(|dir| dir.join("icons")))
+        .collect()
+}
+
+/// Names of the installed icon themes, cursor themes and hidden ones excluded.
+pub fn installed_themes() -> Vec<String> {
+    let mut themes: Vec<String> = icon_dirs()
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| {
+            fs::read_to_string(entry.path().join("index.theme")).is_ok_and(|index| {
+                let has_icons = index.lines().any(|line| line.starts_with("Directories="));
+                has_icons && !index.lines().any(|line| line.trim() == "Hidden=true")
+            })
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+
+    themes.sort_by_key(|theme| theme.to_lowercase());
+    themes.dedup();
+    themes
+}
+
+/// Only the directories without a scale factor are kept, icons are drawn at 1x.
+fn parse_index(content: &str) -> ThemeIndex {
+    let mut sections: HashMap<&str, HashMap<&str, &str>> = HashMap::new();
+    let mut section = "";
+
+    for line in content.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = name;
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(section)
+                .or_default()
+                .insert(key.trim(), value.trim());
+        }
+    }
+
+    let list = |value: Option<&&str>| -> Vec<String> {
+        value
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let theme = sections.get("Icon Theme");
+    let inherits = list(theme.and_then(|theme| theme.get("Inherits")));
+    let dirs = list(theme.and_then(|theme| theme.get("Directories")))
+        .into_iter()
+        .filter_map(|path| {
+            let keys = sections.get(path.as_str())?;
+            let number = |key: &str| keys.get(key).and_then(|value| value.parse::<u32>().ok());
+
+            if number("Scale").is_some_and(|scale| scale != 1) {
+                return None;
+            }
+
+            let size = number("Size")?;
+            let kind = match keys.get("Type").copied() {
+                Some("Fixed") => DirKind::Fixed,
+                Some("Scalable") => DirKind::Scalable,
+                _ => DirKind::Threshold,
+            };
+
+            Some(ThemeDir {
+                path,
+                size,
+                kind,
+                min_size: number("MinSize").unwrap_or(size),
+                max_size: number("MaxSize").unwrap_or(size),
+                threshold: number("Threshold").unwrap_or(2),
+            })
+        })
+        .collect();
+
+    ThemeIndex { inherits, dirs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = "[Icon Theme]\n\
+                         Name=Test\n\
+                         Inherits=Adwaita, gnome\n\
+                         Directories=16x16/mimetypes,48x48/mimetypes,48x48@2/mimetypes,scalable/mimetypes\n\
+                         \n\
+                         [16x16/mimetypes]\n\
+                         Size=16\n\
+                         Type=Fixed\n\
+                         \n\
+                         [48x48/mimetypes]\n\
+                         Size=48\n\
+                         \n\
+                         [48x48@2/mimetypes]\n\
+                         Size=48\n\
+                         Scale=2\n\
+                         \n\
+                         [scalable/mimetypes]\n\
+                         Size=64\n\
+                         MinSize=8\n\
+                         MaxSize=512\n\
+                         Type=Scalable\n";
+
+    #[test]
+    fn parses_index() {
+        let index = parse_index(INDEX);
+
+        assert_eq!(index.inherits, vec!["Adwaita", "gnome"]);
+        let paths: Vec<&str> = index.dirs.iter().map(|dir| dir.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["16x16/mimetypes", "48x48/mimetypes", "scalable/mimetypes"]
+        );
+        assert_eq!(index.dirs[1].kind, DirKind::Threshold);
+        assert_eq!(index.dirs[2].max_size, 512);
+    }
+
+    #[test]
+    fn measures_size_distance() {
+        let index = parse_index(INDEX);
+        let distances: Vec<u32> = index.dirs.iter().map(|dir| dir.size_distance(64)).collect();
+
+        assert_eq!(distances, vec![48, 14, 0]);
+        assert_eq!(index.dirs[1].size_distance(47), 0);
+        assert_eq!(index.dirs[0].size_distance(16), 0);
+    }
+}
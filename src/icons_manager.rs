@@ -1,41 +1,86 @@
+use crate::config_manager::CONFIG_DIRECTORY_NAME;
+use crate::error_manager::AppError;
+use crate::icon_theme;
+use crate::icon_theme::{ICON_EXTENSIONS, IconTheme};
+use crate::lang_string::LangKeys;
+use crate::mime_manager::DIRECTORY_MIME_TYPE;
+use crate::special_folders::{FolderKind, SpecialFolders};
+use crate::vfs::VfsRouter;
+use crate::xdg;
 use egui::{Image, include_image};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fs;
-use std::fs::{ReadDir, read};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Size the theme icons are looked up at, the grid view draws them at 64x64.
+const ICON_SIZE: u32 = 64;
+
+/// Directory of the user icon packs, inside the config directory.
+const ICON_PACKS_DIRECTORY_NAME: &str = "icons";
+
+/// Icons embedded in the binary, named after a MIME subtype or an extension.
+const BUNDLED_ICONS: [(&str, &[u8]); 1] = [("pdf", include_bytes!("resources/icons/pdf.png"))];
 
 pub struct IconsManager<'a> {
     pub folder_icon: Image<'a>,
     file_icon: Image<'a>,
     icons: HashMap<String, Image<'a>>,
+
+    /// Icons of the user pack, named after a MIME icon name (`text-x-python`),
+    /// a MIME subtype or an extension. `folder` and `file` replace the default icons.
+    pack_icons: HashMap<String, Image<'a>>,
+
+    /// Freedesktop icon theme chosen in the settings.
+    theme: Option<IconTheme>,
+
+    /// Icons already looked up inside the theme, None when the theme has none.
+    theme_icons: RefCell<HashMap<String, Option<Image<'a>>>>,
+
+    special_folders: SpecialFolders,
+
+    /// Icon names shared by several MIME types, e.g. `text-x-script` for every script.
+    generic_icons: HashMap<String, String>,
+
+    /// Icon theme or pack name from the config, empty for the bundled icons only.
+    pub theme_name: String,
+
+    /// User packs and installed icon themes, offered in the settings.
+    pub themes: Vec<String>,
+
+    /// Errors met while loading icons, until the app takes them to report them.
+    errors: RefCell<Vec<AppError>>,
 }
 
-impl IconsManager<'_> {
-    fn load_icons<'a>(entries: &mut ReadDir) -> HashMap<String, Image<'a>> {
+impl<'a> IconsManager<'a> {
+    /// Reads a user icon pack, entries which are not PNG or SVG files are skipped.
+    fn load_pack(pack_dir: &Path, errors: &mut Vec<AppError>) -> HashMap<String, Image<'a>> {
         let mut icons = HashMap::new();
+        let entries = match fs::read_dir(pack_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                errors.push(AppError::io(LangKeys::IconPackError, &err).path(pack_dir));
+                return icons;
+            }
+        };
+
         for entry in entries.flatten() {
             let file_path = entry.path();
+            if !file_path.is_file() {
+                continue;
+            }
 
-            let file_path_str = file_path.to_str();
-            let extension = file_path.extension().and_then(|ext| ext.to_str());
-            let file_stem = file_path.file_stem().and_then(|stem| stem.to_str());
-            let is_file = entry.metadata().map(|metadata| metadata.is_file());
-
-            if let (Some(extension), Some(file_stem), Ok(is_file), Some(file_path_str)) =
-                (extension, file_stem, is_file, file_path_str)
-            {
-                assert!(is_file, "All files inside icons must be FILES");
-                assert_eq!(extension, "png", "Extension must be png");
-
-                match read(&file_path) {
-                    Ok(raw_bytes) => {
-                        let uri = format!("bytes://{}", file_path_str);
-                        icons.insert(file_stem.into(), Image::from_bytes(uri, raw_bytes));
-                    }
-
-                    Err(err) => {
-                        eprintln!("[IconsManager->load_icons<'a>()->read(&file_path)] Cannot read file ({:?}): {}", file_path, err);
-                    }
+            let Some(file_stem) = file_path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            match load_icon(&file_path) {
+                Ok(Some(icon)) => {
+                    icons.insert(file_stem.to_string(), icon);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    errors.push(AppError::io(LangKeys::IconLoadError, &err).path(&file_path))
                 }
             }
         }
@@ -43,49 +88,193 @@ impl IconsManager<'_> {
         icons
     }
 
-    pub fn new() -> Self {
-        #[cfg(windows)]
-        let (folder_icon, file_icon) = (
-            include_image!(".\\resources\\icons\\default_folder.png"),
-            include_image!(".\\resources\\icons\\default_file.png"),
-        );
-        #[cfg(unix)]
-        let (folder_icon, file_icon) = (
-            Image::new(include_image!("./resources/icons/default_folder.png")),
-            Image::new(include_image!("./resources/icons/default_file.png")),
-        );
+    /// `theme_name` is a user pack, else an installed freedesktop theme, or empty for the bundled icons.
+    pub fn new(theme_name: &str) -> Self {
+        let folder_icon = Image::new(include_image!("resources/icons/default_folder.png"));
+        let file_icon = Image::new(include_image!("resources/icons/default_file.png"));
 
-        let mut icons = HashMap::new();
-        let mut icons_folder = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        icons_folder.push("src");
-        icons_folder.push("resources");
-        icons_folder.push("icons");
-
-        match fs::read_dir(&icons_folder) {
-            Ok(mut entries) => {
-                icons = Self::load_icons(&mut entries);
-            }
+        let icons = BUNDLED_ICONS
+            .iter()
+            .map(|(name, bytes)| {
+                let uri = format!("bytes://rocket/{}.png", name);
+                (name.to_string(), Image::from_bytes(uri, *bytes))
+            })
+            .collect();
 
-            Err(err) => {
-                eprintln!("[IconsManager->new()->fs::read_dir()] Cannot read icons_folder ({:?}): {}", icons_folder, err);
+        let mut pack_icons = HashMap::new();
+        let mut theme = None;
+        let mut errors = Vec::new();
+        if !theme_name.is_empty() {
+            match packs_dir().map(|packs_dir| packs_dir.join(theme_name)) {
+                Some(pack_dir) if pack_dir.is_dir() => {
+                    pack_icons = Self::load_pack(&pack_dir, &mut errors)
+                }
+                _ => {
+                    theme = IconTheme::load(theme_name);
+                    if theme.is_none() {
+                        errors.push(AppError::new(LangKeys::UnknownIconTheme, theme_name));
+                    }
+                }
             }
         }
 
+        // A pack hides the theme of the same name
+        let mut themes = installed_packs();
+        let installed_themes: Vec<String> = icon_theme::installed_themes()
+            .into_iter()
+            .filter(|theme| !themes.contains(theme))
+            .collect();
+        themes.extend(installed_themes);
+
         Self {
             folder_icon,
             file_icon,
             icons,
+            pack_icons,
+            theme,
+            theme_icons: RefCell::new(HashMap::new()),
+            special_folders: SpecialFolders::new(),
+            generic_icons: load_generic_icons(),
+            theme_name: theme_name.to_string(),
+            themes,
+            errors: RefCell::new(errors),
         }
     }
 
-    pub fn get_icon(&'_ self, file: &PathBuf) -> &'_ Image<'_> {
-        if file.is_dir() {
-            return &self.folder_icon;
+    /// The user pack wins over the theme, which wins over the bundled icons.
+    pub fn get_icon(&self, file: &Path, mime_type: &str) -> Image<'a> {
+        if mime_type == DIRECTORY_MIME_TYPE {
+            return self
+                .pack_icons
+                .get("folder")
+                .cloned()
+                .or_else(|| self.theme_icon("folder"))
+                .unwrap_or_else(|| self.folder_icon.clone());
         }
 
-        file.extension()
+        // text/x-python is drawn with text-x-python, or text-x-script, or text-x-generic
+        let icon_name = mime_type.replace('/', "-");
+        let (media, subtype) = mime_type.split_once('/').unwrap_or((mime_type, ""));
+        let generic_name = self
+            .generic_icons
+            .get(mime_type)
+            .cloned()
+            .unwrap_or_else(|| format!("{}-x-generic", media));
+        let extension = file
+            .extension()
             .and_then(|extension| extension.to_str())
-            .and_then(|extension| self.icons.get(extension))
-            .unwrap_or(&self.file_icon)
+            .unwrap_or_default();
+
+        let names = [icon_name.as_str(), subtype, extension];
+        names
+            .iter()
+            .find_map(|name| self.pack_icons.get(*name).cloned())
+            .or_else(|| self.theme_icon(&icon_name))
+            .or_else(|| names.iter().find_map(|name| self.icons.get(*name).cloned()))
+            .or_else(|| self.pack_icons.get(&generic_name).cloned())
+            .or_else(|| self.theme_icon(&generic_name))
+            .or_else(|| self.pack_icons.get("file").cloned())
+            .unwrap_or_else(|| self.file_icon.clone())
+    }
+
+    pub fn folder_kind(&self, dir: &Path, vfs: &VfsRouter) -> FolderKind {
+        self.special_folders.kind(dir, vfs)
+    }
+
+    /// Icon of a special folder, None if neither the pack nor the theme has one:
+    /// the plain folder icon is then drawn with the glyph of the kind.
+    pub fn get_folder_icon(&self, kind: FolderKind) -> Option<Image<'a>> {
+        self.pack_icons
+            .get(kind.icon_name())
+            .cloned()
+            .or_else(|| self.theme_icon(kind.icon_name()))
+    }
+
+    pub fn refresh(&self) {
+        self.special_folders.refresh();
+    }
+
+    /// Errors met while loading icons since the last call.
+    pub fn take_errors(&self) -> Vec<AppError> {
+        self.errors.take()
+    }
+
+    fn theme_icon(&self, name: &str) -> Option<Image<'a>> {
+        let theme = self.theme.as_ref()?;
+        if let Some(icon) = self.theme_icons.borrow().get(name) {
+            return icon.clone();
+        }
+
+        let icon = match theme.lookup(name, ICON_SIZE) {
+            Some(path) => load_icon(&path).unwrap_or_else(|err| {
+                self.errors
+                    .borrow_mut()
+                    .push(AppError::io(LangKeys::IconLoadError, &err).path(&path));
+                None
+            }),
+            None => None,
+        };
+        self.theme_icons
+            .borrow_mut()
+            .insert(name.to_string(), icon.clone());
+
+        icon
+    }
+}
+
+/// PNG or SVG file, None if it is another format.
+fn load_icon<'a>(path: &Path) -> io::Result<Option<Image<'a>>> {
+    let supported = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| ICON_EXTENSIONS.contains(&extension));
+    if !supported {
+        return Ok(None);
+    }
+
+    let raw_bytes = fs::read(path)?;
+    // The extension of the uri picks the image loader
+    let uri = format!("bytes://{}", path.to_string_lossy());
+    Ok(Some(Image::from_bytes(uri, raw_bytes)))
+}
+
+/// `<config dir>/rocket/icons`, one subdirectory per pack.
+fn packs_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|config_dir| {
+        config_dir
+            .join(CONFIG_DIRECTORY_NAME)
+            .join(ICON_PACKS_DIRECTORY_NAME)
+    })
+}
+
+fn installed_packs() -> Vec<String> {
+    let Some(entries) = packs_dir().and_then(|packs_dir| fs::read_dir(packs_dir).ok()) else {
+        return Vec::new();
+    };
+
+    let mut packs: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    packs.sort_by_key(|pack| pack.to_lowercase());
+    packs
+}
+
+/// `mime/type:icon-name` lines of the shared-mime-info `generic-icons` files.
+fn load_generic_icons() -> HashMap<String, String> {
+    let mut generic_icons = HashMap::new();
+
+    // The most important directory comes first and wins
+    for dir in xdg::data_dirs().into_iter().rev() {
+        let Ok(content) = fs::read_to_string(dir.join("mime").join("generic-icons")) else {
+            continue;
+        };
+
+        for (mime_type, icon_name) in content.lines().filter_map(|line| line.split_once(':')) {
+            generic_icons.insert(mime_type.to_string(), icon_name.to_string());
+        }
     }
+
+    generic_icons
 }
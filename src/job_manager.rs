@@ -0,0 +1,209 @@
+use crate::actions::background_action::BackgroundAction;
+use crate::config_manager::Config;
+use crate::error_manager::{AppError, ErrorManager};
+use crate::lang_string::{LangKeys, LangString};
+use egui::Context;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Finished jobs kept inside the history, older ones are dropped.
+const HISTORY_LENGTH: usize = 100;
+
+/// The speed is averaged over the progress of the last seconds.
+const SPEED_WINDOW: Duration = Duration::from_secs(5);
+
+/// How a job ended.
+#[derive(Clone, PartialEq, Debug)]
+pub enum JobOutcome {
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+/// Finished job, as listed inside the history.
+pub struct JobRecord {
+    pub title: String,
+    pub outcome: JobOutcome,
+    pub finished_at: SystemTime,
+    pub elapsed: Duration,
+
+    /// Amount processed out of the total, e.g. "1.2 MiB / 4.0 MiB".
+    pub amount: String,
+}
+
+/// Samples of the amount done over time, the speed is computed from.
+pub struct Speedometer {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl Speedometer {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, now: Instant, done: u64) {
+        self.samples.push_back((now, done));
+        while let Some((time, _)) = self.samples.front()
+            && now.duration_since(*time) > SPEED_WINDOW
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Amount per second, None until the samples span half a second.
+    pub fn per_second(&self) -> Option<f64> {
+        let (first_time, first_done) = self.samples.front()?;
+        let (last_time, last_done) = self.samples.back()?;
+
+        let elapsed = last_time.duration_since(*first_time).as_secs_f64();
+        if elapsed < 0.5 {
+            return None;
+        }
+
+        Some(last_done.saturating_sub(*first_done) as f64 / elapsed)
+    }
+
+    /// Time left to process `remaining` at `per_second`, None while nothing moves.
+    pub fn eta(remaining: u64, per_second: f64) -> Option<Duration> {
+        if per_second <= 0.0 {
+            return None;
+        }
+
+        Some(Duration::from_secs_f64(remaining as f64 / per_second))
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` past an hour.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Background operations (copy, move, trash, extraction...) running at the same time,
+/// and the history of the finished ones.
+pub struct JobManager {
+    /// Running jobs, and finished ones whose modal is still open.
+    pub jobs: Vec<BackgroundAction>,
+
+    /// Finished jobs, most recent first.
+    pub history: VecDeque<JobRecord>,
+
+    /// Whether the jobs panel is shown.
+    pub visible: bool,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            history: VecDeque::new(),
+            visible: false,
+        }
+    }
+
+    pub fn push(&mut self, job: BackgroundAction) {
+        self.jobs.push(job);
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.jobs.iter().filter(|job| !job.is_finished()).count()
+    }
+
+    /// Drains the progress of every job. Returns true if one of them finished.
+    pub fn poll(&mut self, error_manager: &mut ErrorManager) -> bool {
+        let mut finished = false;
+        for job in &mut self.jobs {
+            if !job.poll() {
+                continue;
+            }
+            finished = true;
+
+            // A hidden job has no modal to show its error
+            if !job.visible
+                && let Some(err) = job.error()
+            {
+                error_manager.report(AppError::new(
+                    LangKeys::JobFailed,
+                    format!("{}: {}", job.title, err),
+                ));
+            }
+        }
+
+        finished
+    }
+
+    /// Renders the modal of the jobs which have one,
+    /// finished jobs without a modal move to the history.
+    pub fn render(&mut self, ctx: &Context, lang_string: &LangString, config: &Config) {
+        for job in self.jobs.iter_mut().filter(|job| job.visible) {
+            job.render(ctx, lang_string, config);
+        }
+
+        let (finished, jobs): (Vec<BackgroundAction>, Vec<BackgroundAction>) = self
+            .jobs
+            .drain(..)
+            .partition(|job| job.is_finished() && !job.visible);
+        self.jobs = jobs;
+
+        for job in finished {
+            if let Some(record) = job.record(config) {
+                self.history.push_front(record);
+            }
+        }
+        self.history.truncate(HISTORY_LENGTH);
+
+        // Background threads cannot wake up the UI, keep polling while working
+        if self.running_count() > 0 {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_speed_over_the_window() {
+        let start = Instant::now();
+        let mut speedometer = Speedometer::new();
+
+        speedometer.push(start, 0);
+        assert_eq!(speedometer.per_second(), None);
+
+        speedometer.push(start + Duration::from_secs(2), 200);
+        assert_eq!(speedometer.per_second(), Some(100.0));
+
+        // The first sample leaves the window, only the last seconds count
+        speedometer.push(start + Duration::from_secs(6), 1000);
+        assert_eq!(speedometer.per_second(), Some(200.0));
+
+        speedometer.reset();
+        assert_eq!(speedometer.per_second(), None);
+        assert_eq!(Speedometer::eta(1000, 100.0), Some(Duration::from_secs(10)));
+        assert_eq!(Speedometer::eta(1000, 0.0), None);
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2:05");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+}
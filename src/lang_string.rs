@@ -1,4 +1,14 @@
-#[allow(dead_code)]
+use crate::config_manager::CONFIG_DIRECTORY_NAME;
+use crate::xdg;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use unic_langid::LanguageIdentifier;
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Languages {
     English,
     Romanian,
@@ -6,59 +16,473 @@ pub enum Languages {
 
 pub const DEFAULT_LANGUAGE: Languages = Languages::English;
 
-pub enum LangKeys {
-    GoBack,
-    GoForward,
-    DeletedFolder,
-    EmptyFolder,
-    NothingSelected,
-    Extension,
-    CreatedAt,
-    AccessedAt,
-    ModifiedAt,
+/// English compiled into the binary, for messages missing from the files found at runtime.
+const FALLBACK_RESOURCE: &str = include_str!("resources/locales/en/rocket.ftl");
+
+const RESOURCE_FILE_NAME: &str = "rocket.ftl";
+
+impl Languages {
+    pub const ALL: [Languages; 2] = [Languages::English, Languages::Romanian];
+
+    pub fn locale(&self) -> &'static str {
+        match self {
+            Languages::English => "en",
+            Languages::Romanian => "ro",
+        }
+    }
+
+    /// Languages are always listed in their own language.
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Languages::English => "English",
+            Languages::Romanian => "Română",
+        }
+    }
+
+    /// Content of the .ftl file, read when the language is selected so translations
+    /// can be fixed without a rebuild. None when no locale folder has it.
+    fn resource(&self) -> Option<String> {
+        for dir in locale_dirs() {
+            let path = dir.join(self.locale()).join(RESOURCE_FILE_NAME);
+            match fs::read_to_string(&path) {
+                Ok(content) => return Some(content),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    eprintln!(
+                        "[Languages->resource()->read_to_string] Cannot read {:?}: {}",
+                        path, err
+                    );
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Folders with a `<locale>/rocket.ftl` file per language, most important first:
+/// `rocket/locales` inside the XDG data folders, `locales` next to the executable,
+/// then the source tree so a build run from the repository is translated too.
+fn locale_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = xdg::data_dirs()
+        .into_iter()
+        .map(|dir| dir.join(CONFIG_DIRECTORY_NAME).join("locales"))
+        .collect();
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(exe_dir.join("locales"));
+    }
+    dirs.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("src/resources/locales"));
+
+    dirs
+}
+
+/// Declares LangKeys together with the id of its message inside the .ftl files.
+macro_rules! lang_keys {
+    ($($key:ident => $id:literal,)*) => {
+        #[derive(Copy, Clone, PartialEq, Debug)]
+        pub enum LangKeys {
+            $($key,)*
+        }
+
+        impl LangKeys {
+            #[cfg(test)]
+            pub const ALL: &'static [LangKeys] = &[$(LangKeys::$key,)*];
+
+            pub fn id(&self) -> &'static str {
+                match self {
+                    $(LangKeys::$key => $id,)*
+                }
+            }
+        }
+    };
+}
+
+lang_keys! {
+    GoBack => "go-back",
+    GoForward => "go-forward",
+    GoUp => "go-up",
+    DeletedFolder => "deleted-folder",
+    EmptyFolder => "empty-folder",
+    NothingSelected => "nothing-selected",
+    FileType => "file-type",
+    CreatedAt => "created-at",
+    AccessedAt => "accessed-at",
+    ModifiedAt => "modified-at",
+    Extract => "extract",
+    ExtractHere => "extract-here",
+    Compress => "compress",
+    ArchiveName => "archive-name",
+    ArchiveFormat => "archive-format",
+    CompressionLevel => "compression-level",
+    LevelStore => "level-store",
+    LevelFast => "level-fast",
+    LevelNormal => "level-normal",
+    LevelBest => "level-best",
+    Create => "create",
+    Cancel => "cancel",
+    Close => "close",
+    Extracting => "extracting",
+    Compressing => "compressing",
+    ActionDone => "action-done",
+    ConnectToServer => "connect-to-server",
+    Disconnect => "disconnect",
+    PathDoesNotExist => "path-does-not-exist",
+    PathNotAFolder => "path-not-a-folder",
+    PathNotAbsolute => "path-not-absolute",
+    Copying => "copying",
+    Moving => "moving",
+    ListView => "list-view",
+    GridView => "grid-view",
+    SortByName => "sort-by-name",
+    SortBySize => "sort-by-size",
+    SortByModified => "sort-by-modified",
+    SortByExtension => "sort-by-extension",
+    Ascending => "ascending",
+    Descending => "descending",
+    GoToLocation => "go-to-location",
+    NoRecentLocations => "no-recent-locations",
+    MoveFiles => "move-files",
+    ConfirmMoveFiles => "confirm-move-files",
+    Move => "move",
+    Settings => "settings",
+    Theme => "theme",
+    ThemeDark => "theme-dark",
+    ThemeLight => "theme-light",
+    ThemeSystem => "theme-system",
+    Language => "language",
+    FontSize => "font-size",
+    ShowHiddenFiles => "show-hidden-files",
+    DefaultView => "default-view",
+    Confirmations => "confirmations",
+    AskBeforeMoving => "ask-before-moving",
+    AskBeforeDeleting => "ask-before-deleting",
+    DateFormat => "date-format",
+    SizeUnits => "size-units",
+    SizeUnitsBinary => "size-units-binary",
+    SizeUnitsDecimal => "size-units-decimal",
+    RowHeight => "row-height",
+    FilesSelected => "files-selected",
+    SearchFor => "search-for",
+    QuickAccess => "quick-access",
+    ReadFolderError => "read-folder-error",
+    OpenFileError => "open-file-error",
+    MoveError => "move-error",
+    EditLocation => "edit-location",
+    CommandPalette => "command-palette",
+    ToggleHiddenFiles => "toggle-hidden-files",
+    ViewAsList => "view-as-list",
+    ViewAsGrid => "view-as-grid",
+    Refresh => "refresh",
+    Copy => "copy",
+    Cut => "cut",
+    Paste => "paste",
+    NoMatchingCommands => "no-matching-commands",
+    SelectAll => "select-all",
+    InvertSelection => "invert-selection",
+    SelectByPattern => "select-by-pattern",
+    SelectPatternHint => "select-pattern-hint",
+    NoFilesMatch => "no-files-match",
+    Select => "select",
+    Linking => "linking",
+    DropFilesHere => "drop-files-here",
+    Open => "open",
+    OpenWith => "open-with",
+    NoApplications => "no-applications",
+    Rename => "rename",
+    MoveToTrash => "move-to-trash",
+    CopyPath => "copy-path",
+    Properties => "properties",
+    NewFolder => "new-folder",
+    NewFile => "new-file",
+    Name => "name",
+    Location => "location",
+    Kind => "kind",
+    Folder => "folder",
+    Contents => "contents",
+    Size => "size",
+    Created => "created",
+    Accessed => "accessed",
+    Modified => "modified",
+    ReadOnly => "read-only",
+    RenameError => "rename-error",
+    TrashError => "trash-error",
+    CreateError => "create-error",
+    LaunchError => "launch-error",
+    ItemCount => "item-count",
+    CopyError => "copy-error",
+    LinkError => "link-error",
+    NewFromTemplate => "new-from-template",
+    NoTemplates => "no-templates",
+    Owner => "owner",
+    Group => "group",
+    Others => "others",
+    Permissions => "permissions",
+    Read => "read",
+    Write => "write",
+    Execute => "execute",
+    SetUserId => "set-user-id",
+    SetGroupId => "set-group-id",
+    Sticky => "sticky",
+    ApplyRecursively => "apply-recursively",
+    LinkTarget => "link-target",
+    Inode => "inode",
+    Device => "device",
+    HardLinks => "hard-links",
+    ExtendedAttributes => "extended-attributes",
+    Value => "value",
+    Add => "add",
+    Remove => "remove",
+    Apply => "apply",
+    UnknownUser => "unknown-user",
+    UnknownGroup => "unknown-group",
+    ChangingPermissions => "changing-permissions",
+    CreateSymlink => "create-symlink",
+    CreateHardLink => "create-hard-link",
+    GoToLinkTarget => "go-to-link-target",
+    LinkTo => "link-to",
+    PointsTo => "points-to",
+    BrokenLink => "broken-link",
+    IconTheme => "icon-theme",
+    BundledIcons => "bundled-icons",
+    Git => "git",
+    GitStage => "git-stage",
+    GitUnstage => "git-unstage",
+    GitDiscard => "git-discard",
+    GitShowDiff => "git-show-diff",
+    GitError => "git-error",
+    ConfirmGitDiscard => "confirm-git-discard",
+    GitDiffOf => "git-diff-of",
+    GitNoChanges => "git-no-changes",
+    GitAheadBehind => "git-ahead-behind",
+    GitNoUpstream => "git-no-upstream",
+    Terminal => "terminal",
+    ToggleTerminal => "toggle-terminal",
+    NewTerminal => "new-terminal",
+    CloseTerminal => "close-terminal",
+    InsertSelection => "insert-selection",
+    TerminalExited => "terminal-exited",
+    TerminalStartError => "terminal-start-error",
+    UserActions => "user-actions",
+    CommandRunning => "command-running",
+    CommandExitStatus => "command-exit-status",
+    CommandKilled => "command-killed",
+    NoOutput => "no-output",
+    Jobs => "jobs",
+    ToggleJobs => "toggle-jobs",
+    JobsRunning => "jobs-running",
+    NoRunningJobs => "no-running-jobs",
+    JobHistory => "job-history",
+    ClearHistory => "clear-history",
+    ShowJob => "show-job",
+    JobSpeed => "job-speed",
+    JobPaused => "job-paused",
+    JobCancelled => "job-cancelled",
+    JobPause => "job-pause",
+    JobResume => "job-resume",
+    RunInBackground => "run-in-background",
+    RunInBackgroundHint => "run-in-background-hint",
+    MovingToTrash => "moving-to-trash",
+    JobFinishedIn => "job-finished-in",
+    ErrorLog => "error-log",
+    ShowErrorLog => "show-error-log",
+    Retry => "retry",
+    CopyDetails => "copy-details",
+    ClearLog => "clear-log",
+    NoErrors => "no-errors",
+    JobFailed => "job-failed",
+    IconLoadError => "icon-load-error",
+    IconPackError => "icon-pack-error",
+    UnknownIconTheme => "unknown-icon-theme",
+    SearchError => "search-error",
+    Connecting => "connecting",
+    UnknownServer => "unknown-server",
+    UnknownServerQuestion => "unknown-server-question",
+    TrustServer => "trust-server",
+    ConfigLoadError => "config-load-error",
+    ConfigSaveError => "config-save-error",
+    ConfirmTrashFiles => "confirm-trash-files",
+    DeletePermanently => "delete-permanently",
+    ConfirmDeleteFiles => "confirm-delete-files",
+    DeletingFiles => "deleting-files",
+    DeleteError => "delete-error",
+    TemplatesError => "templates-error",
+    MimeDatabaseError => "mime-database-error",
+    ApplicationLoadError => "application-load-error",
 }
 
 pub struct LangString {
     lang: Languages,
+    /// Messages of the .ftl file of `lang`, empty when it was not found.
+    bundle: FluentBundle<FluentResource>,
+
+    /// Compiled-in English, used for messages missing from `bundle`.
+    fallback: FluentBundle<FluentResource>,
 }
 
 impl LangString {
     pub fn new() -> Self {
+        Self::from(DEFAULT_LANGUAGE)
+    }
+
+    pub fn from(lang: Languages) -> Self {
+        let resource = lang.resource().unwrap_or_else(|| {
+            eprintln!(
+                "[LangString->from()->resource] No {} file for {}, English is used",
+                RESOURCE_FILE_NAME,
+                lang.locale()
+            );
+            String::new()
+        });
+
         Self {
-            lang: DEFAULT_LANGUAGE,
+            lang,
+            bundle: load_bundle(lang, resource),
+            fallback: load_bundle(Languages::English, FALLBACK_RESOURCE.to_string()),
         }
     }
 
-    #[allow(dead_code)]
-    pub fn from(lang: Languages) -> Self {
-        Self { lang }
+    pub fn lang(&self) -> Languages {
+        self.lang
     }
 
     pub fn get(&self, key: LangKeys) -> String {
-        match self.lang {
-            Languages::English => match key {
-                LangKeys::GoBack => String::from("Go back"),
-                LangKeys::GoForward => String::from("Go forward"),
-                LangKeys::DeletedFolder => String::from("Folder has been deleted."),
-                LangKeys::EmptyFolder => String::from("Folder is empty."),
-                LangKeys::NothingSelected => String::from("No file is selected."),
-                LangKeys::Extension => String::from("Extension: "),
-                LangKeys::CreatedAt => String::from("Created at: "),
-                LangKeys::AccessedAt => String::from("Accessed at: "),
-                LangKeys::ModifiedAt => String::from("Modified at: "),
-            },
-
-            Languages::Romanian => match key {
-                LangKeys::GoBack => String::from("Înapoi"),
-                LangKeys::GoForward => String::from("Înainte"),
-                LangKeys::DeletedFolder => String::from("Folderul a fost șters."),
-                LangKeys::EmptyFolder => String::from("Folderul este gol."),
-                LangKeys::NothingSelected => String::from("Niciun fișier selectat."),
-                LangKeys::Extension => String::from("Extensie: "),
-                LangKeys::CreatedAt => String::from("Creat la: "),
-                LangKeys::AccessedAt => String::from("Accesat la: "),
-                LangKeys::ModifiedAt => String::from("Modificat la: "),
-            },
+        self.format(key, &[])
+    }
+
+    /// Message with arguments, e.g. `format(LangKeys::ItemCount, &[("count", 3.into())])`.
+    pub fn format(&self, key: LangKeys, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+
+        for bundle in [&self.bundle, &self.fallback] {
+            let Some(pattern) = bundle
+                .get_message(key.id())
+                .and_then(|message| message.value())
+            else {
+                continue;
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                eprintln!(
+                    "[LangString->format()->format_pattern] {}: {:?}",
+                    key.id(),
+                    errors
+                );
+            }
+
+            return text.into_owned();
+        }
+
+        eprintln!("[LangString->format()->get_message] Missing message {}", key.id());
+        key.id().to_string()
+    }
+}
+
+fn load_bundle(lang: Languages, resource: String) -> FluentBundle<FluentResource> {
+    let lang_id: LanguageIdentifier = lang
+        .locale()
+        .parse()
+        .expect("Locales of Languages must be valid language identifiers");
+    let mut bundle = FluentBundle::new(vec![lang_id]);
+
+    // Unicode isolation marks are displayed as boxes by egui
+    bundle.set_use_isolating(false);
+
+    // A syntax error only drops the broken messages, the rest is still usable
+    let resource = match FluentResource::try_new(resource) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+            eprintln!(
+                "[LangString->load_bundle()->try_new] {}: {:?}",
+                lang.locale(),
+                errors
+            );
+            resource
         }
+    };
+
+    if let Err(errors) = bundle.add_resource(resource) {
+        eprintln!(
+            "[LangString->load_bundle()->add_resource] {}: {:?}",
+            lang.locale(),
+            errors
+        );
+    }
+
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// .ftl file of `lang` inside the source tree.
+    fn source_resource(lang: Languages) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/resources/locales")
+            .join(lang.locale())
+            .join(RESOURCE_FILE_NAME);
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn every_locale_parses() {
+        for lang in Languages::ALL {
+            assert!(
+                FluentResource::try_new(source_resource(lang)).is_ok(),
+                "{} has syntax errors",
+                lang.locale()
+            );
+        }
+    }
+
+    #[test]
+    fn every_key_exists_in_every_locale() {
+        for lang in Languages::ALL {
+            let bundle = load_bundle(lang, source_resource(lang));
+            for key in LangKeys::ALL {
+                assert!(
+                    bundle.has_message(key.id()),
+                    "{} is missing from {}",
+                    key.id(),
+                    lang.locale()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn plural_rules_and_arguments() {
+        let lang_string = LangString::from(Languages::English);
+
+        assert_eq!(
+            lang_string.format(LangKeys::FilesSelected, &[("count", 1usize.into())]),
+            "One item selected"
+        );
+        assert_eq!(
+            lang_string.format(LangKeys::FilesSelected, &[("count", 3usize.into())]),
+            "3 items selected"
+        );
+    }
+
+    #[test]
+    fn missing_files_fall_back_to_english() {
+        let lang_string = LangString {
+            lang: Languages::Romanian,
+            bundle: load_bundle(Languages::Romanian, String::new()),
+            fallback: load_bundle(Languages::English, FALLBACK_RESOURCE.to_string()),
+        };
+
+        assert_eq!(
+            lang_string.format(LangKeys::FilesSelected, &[("count", 3usize.into())]),
+            "3 items selected"
+        );
     }
 }
@@ -0,0 +1,103 @@
+use crate::archive_manager;
+use crate::fuzzy::fuzzy_score;
+use crate::recent_locations::RecentLocations;
+use crate::vfs::sftp_vfs;
+use std::path::{Path, PathBuf};
+
+/// At most this many matches are listed.
+const MAX_MATCHES: usize = 50;
+
+/// Fuzzy finder over the recently visited directories (Ctrl+P).
+pub struct LocationFinder {
+    pub visible: bool,
+    pub request_focus: bool,
+    pub query: String,
+
+    /// Recent locations matching query, best match first.
+    pub matches: Vec<PathBuf>,
+
+    /// Index inside matches highlighted with the arrow keys.
+    pub selected_idx: usize,
+}
+
+impl LocationFinder {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            request_focus: false,
+            query: String::new(),
+            matches: Vec::new(),
+            selected_idx: 0,
+        }
+    }
+
+    pub fn open(&mut self, recent_locations: &mut RecentLocations) {
+        // Local directories removed since they were visited are forgotten
+        let removed: Vec<PathBuf> = recent_locations
+            .ranked()
+            .into_iter()
+            .map(|location| location.path.clone())
+            .filter(|path| is_local_location_removed(path))
+            .collect();
+        for path in &removed {
+            recent_locations.forget(path);
+        }
+
+        self.visible = true;
+        self.request_focus = true;
+        self.query.clear();
+        self.update_matches(recent_locations);
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.matches.clear();
+    }
+
+    /// Ranks by how well the query matches, ties are broken by frecency
+    /// since ranked() is already sorted that way and the sort is stable.
+    pub fn update_matches(&mut self, recent_locations: &RecentLocations) {
+        let mut scored: Vec<(i64, PathBuf)> = recent_locations
+            .ranked()
+            .into_iter()
+            .filter_map(|location| {
+                fuzzy_score(&self.query, &location.path.to_string_lossy())
+                    .map(|score| (score, location.path.clone()))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        self.matches = scored
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|(_, path)| path)
+            .collect();
+        self.selected_idx = 0;
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected_idx = self.selected_idx.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected_idx + 1 < self.matches.len() {
+            self.selected_idx += 1;
+        }
+    }
+
+    pub fn selected(&self) -> Option<&PathBuf> {
+        self.matches.get(self.selected_idx)
+    }
+}
+
+/// Remote locations can not be checked without connecting, so they are never considered removed.
+fn is_local_location_removed(path: &Path) -> bool {
+    if sftp_vfs::is_sftp_path(path) {
+        return false;
+    }
+
+    match archive_manager::split_archive_path(path) {
+        Some((archive, _)) => !archive.exists(),
+        None => !path.exists(),
+    }
+}
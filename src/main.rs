@@ -1,31 +1,75 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod actions;
-mod error_modal;
+mod address_bar;
+mod applications_manager;
+mod archive_manager;
+mod command_palette;
+mod command_registry;
+mod config_manager;
+mod error_manager;
+mod fuzzy;
+mod git_manager;
+mod history;
+mod icon_theme;
 mod icons_manager;
+mod job_manager;
 mod lang_string;
+mod location_finder;
+mod mime_manager;
 mod path_manager;
+#[cfg(unix)]
+mod permissions_manager;
+mod progress;
+mod recent_locations;
 mod search_manager;
+mod session;
+mod special_folders;
+mod templates;
+mod terminal_manager;
+mod transfer_manager;
 mod ui;
+mod user_actions;
+mod vfs;
+mod view_settings;
+mod xdg;
 
 use crate::actions::Actions;
+use crate::address_bar::AddressBar;
+use crate::applications_manager::ApplicationsManager;
+use crate::command_palette::{CommandPalette, PaletteEntry};
+use crate::command_registry::{CommandId, CommandRegistry};
+use crate::config_manager::ConfigManager;
 use crate::icons_manager::IconsManager;
-use crate::lang_string::LangString;
+use crate::lang_string::{LangKeys, LangString};
+use crate::location_finder::LocationFinder;
+use crate::git_manager::GitManager;
+use crate::mime_manager::MimeManager;
 use crate::path_manager::PathManager;
 use crate::search_manager::SearchManager;
+use crate::terminal_manager::TerminalManager;
+use crate::transfer_manager::TransferMode;
 use crate::ui::{
-    additional_info_panel, central_panel, display_path_panel, navigation_bar_panel,
-    quick_access_panel,
+    additional_info_panel, central_panel, command_palette_window, display_path_panel,
+    host_key_window, jobs_panel, location_finder_window, navigation_bar_panel, quick_access_panel,
+    select_pattern_window, settings_window, terminal_panel,
 };
+use crate::view_settings::ViewMode;
 use eframe::egui;
 use egui::{Context, Id};
-use crate::error_modal::ErrorModal;
+use crate::error_manager::{ErrorManager, Retry};
+use std::path::PathBuf;
 
 const APP_NAME: &str = "Rocket [0.0.19]";
 
+/// Kept apart from APP_NAME so the saved session survives version bumps.
+const APP_ID: &str = "rocket";
+
 fn main() -> eframe::Result {
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([1280.0, 720.0]),
+        viewport: egui::ViewportBuilder::default()
+            .with_app_id(APP_ID)
+            .with_inner_size([1280.0, 720.0]),
         ..Default::default()
     };
 
@@ -35,47 +79,297 @@ fn main() -> eframe::Result {
         Box::new(|cc| {
             egui_extras::install_image_loaders(&cc.egui_ctx);
 
-            cc.egui_ctx.set_theme(egui::Theme::Dark);
-
-            Ok(Box::<Rocket>::default())
+            Ok(Box::new(Rocket::new(cc)))
         }),
     )
 }
 
 struct Rocket<'a> {
+    config_manager: ConfigManager,
+    command_registry: CommandRegistry,
+    command_palette: CommandPalette,
     lang_string: LangString,
     path_manager: PathManager,
+    address_bar: AddressBar,
+    location_finder: LocationFinder,
     search_manager: SearchManager,
     actions: Actions,
     icons_manager: IconsManager<'a>,
-    general_error_modal: ErrorModal,
+    mime_manager: MimeManager,
+    git_manager: GitManager,
+    terminal_manager: TerminalManager,
+    applications_manager: ApplicationsManager,
+    error_manager: ErrorManager,
 }
 
-impl Default for Rocket<'_> {
-    fn default() -> Self {
-        let lang_string: LangString = LangString::new();
-        let path_manager = PathManager::new();
+impl Rocket<'_> {
+    fn new(cc: &eframe::CreationContext) -> Self {
+        let config_manager = ConfigManager::new();
+        let command_registry = CommandRegistry::new(&config_manager.config.keybindings);
+        let command_palette = CommandPalette::new();
+        let mut lang_string: LangString = LangString::new();
+        let mut path_manager = PathManager::new();
+        path_manager.view_settings.view_mode = config_manager.config.default_view;
+        config_manager.apply(&cc.egui_ctx, &mut lang_string, &mut path_manager);
+        if let Some(storage) = cc.storage {
+            session::load(storage, &mut path_manager);
+        }
+        let address_bar = AddressBar::new();
+        let location_finder = LocationFinder::new();
         let search_manager = SearchManager::new();
         let actions = Actions::new();
-        let icons_manager = IconsManager::new();
-        let general_error_modal = ErrorModal::new();
+        let icons_manager = IconsManager::new(&config_manager.config.icon_theme);
+        let mime_manager = MimeManager::new();
+        let git_manager = GitManager::new();
+        let terminal_manager = TerminalManager::new();
+        let applications_manager = ApplicationsManager::new();
+        let error_manager = ErrorManager::new();
         Self {
+            config_manager,
+            command_registry,
+            command_palette,
             lang_string,
             path_manager,
+            address_bar,
+            location_finder,
             search_manager,
             actions,
             icons_manager,
-            general_error_modal
+            mime_manager,
+            git_manager,
+            terminal_manager,
+            applications_manager,
+            error_manager,
+        }
+    }
+
+    /// Applies the config after it was changed, it is saved once the user stops editing.
+    fn config_changed(&mut self, ctx: &Context) {
+        self.config_manager.apply(ctx, &mut self.lang_string, &mut self.path_manager);
+        self.command_registry = CommandRegistry::new(&self.config_manager.config.keybindings);
+        if self.icons_manager.theme_name != self.config_manager.config.icon_theme {
+            self.icons_manager = IconsManager::new(&self.config_manager.config.icon_theme);
+        }
+
+        self.config_manager.changed();
+    }
+
+    /// Selected files, sorted so that `%f` expands the same way every time.
+    fn selected_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.actions.select_action.files.iter().cloned().collect();
+        files.sort();
+        files
+    }
+
+    fn applicable_user_actions(&self) -> Vec<usize> {
+        user_actions::applicable(
+            &self.config_manager.config.user_actions,
+            &self.selected_files(),
+            &self.path_manager.current_path,
+            &self.path_manager.vfs,
+            &self.mime_manager,
+        )
+    }
+
+    fn run_command(&mut self, ctx: &Context, command: CommandId) {
+        let path_manager = &mut self.path_manager;
+
+        match command {
+            CommandId::GoBack => path_manager.go_back(),
+            CommandId::GoForward => path_manager.go_forward(),
+            CommandId::GoUp => path_manager.go_up(),
+            CommandId::EditLocation => self.address_bar.start_editing(&path_manager.current_path),
+            CommandId::FindLocation => {
+                if self.location_finder.visible {
+                    self.location_finder.close();
+                } else {
+                    self.location_finder.open(&mut path_manager.recent_locations);
+                }
+            }
+            CommandId::CommandPalette => {
+                if self.command_palette.visible {
+                    self.command_palette.close();
+                } else {
+                    let user_actions = self
+                        .applicable_user_actions()
+                        .into_iter()
+                        .map(|idx| {
+                            (
+                                idx,
+                                self.config_manager.config.user_actions[idx].name.clone(),
+                            )
+                        })
+                        .collect();
+                    self.command_palette.open(&self.lang_string, user_actions);
+                }
+            }
+            CommandId::Settings => {
+                self.config_manager.settings_visible = !self.config_manager.settings_visible;
+            }
+            CommandId::ToggleHidden => {
+                self.config_manager.config.show_hidden = !self.config_manager.config.show_hidden;
+                self.config_changed(ctx);
+            }
+            CommandId::ListView => path_manager.view_settings.view_mode = ViewMode::List,
+            CommandId::GridView => path_manager.view_settings.view_mode = ViewMode::Grid,
+            CommandId::Refresh => {
+                path_manager.vfs.refresh();
+                self.mime_manager.refresh();
+                self.icons_manager.refresh();
+                self.git_manager.refresh();
+                path_manager.update_folder_content = true;
+            }
+            CommandId::Copy => self.actions.set_clipboard(TransferMode::Copy),
+            CommandId::Cut => self.actions.set_clipboard(TransferMode::Move),
+            CommandId::Paste => {
+                // Archives are read-only
+                if path_manager.vfs.archive(&path_manager.current_path).is_none() {
+                    self.actions.paste(&path_manager.current_path);
+                }
+            }
+            CommandId::Compress => {
+                // Archives are created on the local disk only
+                if !self.actions.select_action.files.is_empty()
+                    && path_manager.vfs.is_local(&path_manager.current_path)
+                {
+                    let files = self.actions.select_action.files.iter().cloned().collect();
+                    self.actions.open_compress_dialog(files, &path_manager.current_path);
+                }
+            }
+            CommandId::SelectAll => self
+                .actions
+                .select_action
+                .select_all(&path_manager.directory_content),
+            CommandId::InvertSelection => self
+                .actions
+                .select_action
+                .invert_selection(&path_manager.directory_content),
+            CommandId::SelectByPattern => {
+                self.actions.select_action.select_pattern = Some(String::new());
+            }
+            CommandId::Rename => self.actions.rename_selection(&path_manager.vfs),
+            CommandId::MoveToTrash => self.actions.trash_selection(
+                &path_manager.vfs,
+                self.config_manager.config.confirmations.delete_files,
+            ),
+            CommandId::CopyPath => {
+                let mut paths: Vec<String> = self
+                    .actions
+                    .select_action
+                    .files
+                    .iter()
+                    .map(|file| file.to_string_lossy().into_owned())
+                    .collect();
+                paths.sort();
+
+                if !paths.is_empty() {
+                    ctx.copy_text(paths.join("\n"));
+                }
+            }
+            CommandId::Properties => {
+                let files = self.actions.select_action.files.iter().cloned().collect();
+                self.actions
+                    .open_properties(files, &path_manager.vfs, &self.mime_manager);
+            }
+            CommandId::NewFolder | CommandId::NewFile => {
+                // Archives are read-only
+                if path_manager.vfs.archive(&path_manager.current_path).is_none() {
+                    let directory = path_manager.current_path.clone();
+                    if command == CommandId::NewFolder {
+                        self.actions
+                            .new_folder(&self.lang_string, &path_manager.vfs, &directory);
+                    } else {
+                        self.actions
+                            .new_file(&self.lang_string, &path_manager.vfs, &directory);
+                    }
+                }
+            }
+            CommandId::CreateSymlink => self.actions.create_links(&path_manager.vfs, false),
+            CommandId::CreateHardLink => self.actions.create_links(&path_manager.vfs, true),
+            CommandId::GoToLinkTarget => {
+                let directory = self
+                    .actions
+                    .select_action
+                    .cursor
+                    .clone()
+                    .and_then(|link| self.actions.go_to_link_target(&path_manager.vfs, &link));
+                if let Some(directory) = directory {
+                    self.actions.select_action.clear_selection();
+                    path_manager.update_current_directory(&directory);
+                }
+            }
+            CommandId::ToggleTerminal => {
+                self.terminal_manager
+                    .toggle(ctx, &path_manager.current_path, &path_manager.vfs);
+            }
+            CommandId::ToggleJobs => {
+                self.actions.job_manager.visible = !self.actions.job_manager.visible;
+            }
+            CommandId::ErrorLog => {
+                self.error_manager.log_visible = !self.error_manager.log_visible;
+            }
         }
     }
 }
 
 impl eframe::App for Rocket<'_> {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        session::save(storage, &self.path_manager);
+        self.config_manager.save_now();
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+        for command in self.command_registry.pressed_commands(ctx) {
+            self.run_command(ctx, command);
+        }
+
+        if let Some(entry) = command_palette_window::show(
+            ctx,
+            &self.lang_string,
+            &mut self.command_palette,
+            &self.command_registry,
+        ) {
+            match entry {
+                PaletteEntry::Command(command) => self.run_command(ctx, command),
+                PaletteEntry::UserAction(idx) => {
+                    // The selection may have changed while the palette was open
+                    if self.applicable_user_actions().contains(&idx) {
+                        let files = self.selected_files();
+                        self.actions.run_user_action(
+                            &self.config_manager.config.user_actions[idx],
+                            &files,
+                            &self.path_manager.current_path,
+                        );
+                    }
+                }
+            }
+        }
+
+        location_finder_window::show(
+            ctx,
+            &self.lang_string,
+            &mut self.location_finder,
+            &mut self.path_manager,
+        );
+
+        select_pattern_window::show(
+            ctx,
+            &self.lang_string,
+            &mut self.actions.select_action,
+            &self.path_manager,
+        );
+        host_key_window::show(ctx, &self.lang_string, &mut self.path_manager);
+
         egui::SidePanel::left(Id::new("quick_access"))
             .resizable(true)
             .show(ctx, |ui| {
-                quick_access_panel::show(ui);
+                quick_access_panel::show(
+                    ui,
+                    &self.lang_string,
+                    &self.config_manager.config,
+                    &mut self.path_manager,
+                    &mut self.actions,
+                );
             });
 
         egui::TopBottomPanel::top(Id::new("navigation_bar")).show(ctx, |ui| {
@@ -84,13 +378,58 @@ impl eframe::App for Rocket<'_> {
                 &self.lang_string,
                 &mut self.path_manager,
                 &mut self.search_manager,
+                &mut self.config_manager,
             );
         });
 
+        self.git_manager
+            .update(ctx, &self.path_manager.current_path, &self.path_manager.vfs);
+
         egui::TopBottomPanel::bottom(Id::new("display_path")).show(ctx, |ui| {
-            display_path_panel::show(ui, &mut self.path_manager, &self.icons_manager.folder_icon);
+            display_path_panel::show(
+                ui,
+                &self.lang_string,
+                &self.config_manager.config,
+                &mut self.path_manager,
+                &mut self.address_bar,
+                &mut self.actions,
+                &self.git_manager,
+                &self.icons_manager.folder_icon,
+            );
         });
 
+        if self.actions.job_manager.visible {
+            egui::TopBottomPanel::bottom(Id::new("jobs"))
+                .resizable(true)
+                .default_height(200.0)
+                .show(ctx, |ui| {
+                    jobs_panel::show(
+                        ui,
+                        &self.lang_string,
+                        &self.config_manager.config,
+                        &mut self.actions.job_manager,
+                    );
+                });
+        }
+
+        if self.terminal_manager.visible {
+            self.terminal_manager
+                .follow(&self.path_manager.current_path, &self.path_manager.vfs);
+
+            egui::TopBottomPanel::bottom(Id::new("terminal"))
+                .resizable(true)
+                .default_height(250.0)
+                .show(ctx, |ui| {
+                    terminal_panel::show(
+                        ui,
+                        &self.lang_string,
+                        &mut self.terminal_manager,
+                        &self.path_manager,
+                        &self.actions.select_action,
+                    );
+                });
+        }
+
         egui::SidePanel::right(Id::new("additional_info"))
             .max_width(300.0)
             .min_width(200.0)
@@ -98,8 +437,12 @@ impl eframe::App for Rocket<'_> {
                 additional_info_panel::show(
                     ui,
                     &self.lang_string,
+                    &self.config_manager.config,
                     &self.actions.select_action,
+                    &mut self.actions.file_diff,
+                    &self.path_manager.vfs,
                     &self.icons_manager,
+                    &self.mime_manager,
                 );
             });
 
@@ -107,13 +450,78 @@ impl eframe::App for Rocket<'_> {
             central_panel::show(
                 ui,
                 &self.lang_string,
-                &mut self.general_error_modal,
+                &self.config_manager.config,
+                &mut self.error_manager,
                 &mut self.path_manager,
                 &mut self.actions,
                 &self.icons_manager,
+                &self.mime_manager,
+                &self.git_manager,
+                &self.applications_manager,
             );
         });
 
-        self.general_error_modal.render(ctx);
+        let finished = self.actions.render(
+            ctx,
+            &self.lang_string,
+            &self.config_manager.config,
+            &self.path_manager.vfs,
+            &mut self.error_manager,
+        );
+        if let Some(command) = self.actions.terminal_command.take() {
+            self.terminal_manager.run_command(
+                ctx,
+                &command,
+                &self.path_manager.current_path,
+                &self.path_manager.vfs,
+            );
+        }
+        // Failed operations may have changed files too before failing
+        if finished {
+            self.path_manager.vfs.refresh();
+            self.mime_manager.refresh();
+            self.icons_manager.refresh();
+            self.git_manager.refresh();
+            self.path_manager.update_folder_content = true;
+        }
+
+        if settings_window::show(
+            ctx,
+            &self.lang_string,
+            &mut self.config_manager,
+            &self.icons_manager.themes,
+        ) {
+            self.config_changed(ctx);
+        }
+
+        self.config_manager.save_when_idle(ctx);
+        for error in self.config_manager.take_errors() {
+            self.error_manager.report(error);
+        }
+        for error in self.icons_manager.take_errors() {
+            self.error_manager.report(error);
+        }
+        for error in self.mime_manager.take_errors() {
+            self.error_manager.report(error);
+        }
+        for error in self.applications_manager.take_errors() {
+            self.error_manager.report(error);
+        }
+        for error in self.git_manager.take_errors() {
+            self.error_manager.report(error);
+        }
+        // A search meets many unreadable folders, they are not worth a toast each
+        for error in self.search_manager.take_errors() {
+            self.error_manager.log(error);
+        }
+
+        let retry = self
+            .error_manager
+            .render(ctx, &self.lang_string, &self.config_manager.config);
+        match retry {
+            Some(Retry::Operation(operation)) => self.actions.queue(operation),
+            Some(Retry::ReloadFolder) => self.path_manager.update_folder_content = true,
+            None => {}
+        }
     }
 }
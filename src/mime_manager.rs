@@ -0,0 +1,612 @@
+use crate::error_manager::AppError;
+use crate::lang_string::LangKeys;
+use crate::vfs::Vfs;
+use crate::xdg;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const DIRECTORY_MIME_TYPE: &str = "inode/directory";
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+/// Bytes read from the start of a file to detect its type by content.
+const SNIFF_LEN: usize = 4096;
+
+/// File name pattern from `globs2`, e.g. `50:text/x-python:*.py`.
+struct Glob {
+    weight: u32,
+    mime_type: String,
+    pattern: String,
+    case_sensitive: bool,
+}
+
+impl Glob {
+    fn matches(&self, file_name: &str) -> bool {
+        if self.case_sensitive {
+            glob_match(&self.pattern, file_name)
+        } else {
+            glob_match(&self.pattern, &file_name.to_lowercase())
+        }
+    }
+
+    /// Names without wildcards, such as `Makefile`, win over every other pattern.
+    fn is_literal(&self) -> bool {
+        !self.pattern.contains(['*', '?', '['])
+    }
+}
+
+/// One line of a `magic` section. The rules following a rule with a bigger
+/// indent are its children: one of them must match too.
+struct MagicRule {
+    indent: u32,
+    offset: usize,
+
+    /// Number of offsets the value is looked for at, starting at `offset`.
+    range: usize,
+    value: Vec<u8>,
+    mask: Option<Vec<u8>>,
+}
+
+impl MagicRule {
+    fn matches(&self, data: &[u8]) -> bool {
+        (self.offset..self.offset + self.range).any(|start| {
+            let Some(bytes) = data.get(start..start + self.value.len()) else {
+                return false;
+            };
+
+            match &self.mask {
+                Some(mask) => bytes
+                    .iter()
+                    .zip(&self.value)
+                    .zip(mask)
+                    .all(|((byte, value), mask)| byte & mask == value & mask),
+                None => bytes == self.value.as_slice(),
+            }
+        })
+    }
+}
+
+/// `[priority:mime/type]` section of the `magic` file.
+struct Magic {
+    priority: u32,
+    mime_type: String,
+    rules: Vec<MagicRule>,
+}
+
+/// Detects the MIME type of files with the freedesktop shared-mime-info database:
+/// the name is matched against its globs first, then the content against its magic rules.
+/// Without a database (Windows, macOS) the extension is looked up in a built-in table.
+pub struct MimeManager {
+    globs: Vec<Glob>,
+
+    /// Sorted by decreasing priority.
+    magic: Vec<Magic>,
+
+    /// Parent types, e.g. text/x-python is a text/plain file.
+    subclasses: HashMap<String, Vec<String>>,
+
+    /// Types already detected, content detection reads the file.
+    cache: RefCell<HashMap<PathBuf, String>>,
+
+    /// Unreadable database files, until the app takes them to report them.
+    errors: Vec<AppError>,
+}
+
+impl MimeManager {
+    pub fn new() -> Self {
+        let mut globs = Vec::new();
+        let mut magic = Vec::new();
+        let mut subclasses: HashMap<String, Vec<String>> = HashMap::new();
+        let mut errors = Vec::new();
+
+        for dir in xdg::data_dirs() {
+            let dir = dir.join("mime");
+
+            if let Ok(content) = fs::read_to_string(dir.join("globs2")) {
+                globs.extend(parse_globs(&content));
+            }
+
+            if let Ok(content) = fs::read_to_string(dir.join("subclasses")) {
+                for (mime_type, parent) in content.lines().filter_map(|line| line.split_once(' ')) {
+                    subclasses
+                        .entry(mime_type.to_string())
+                        .or_default()
+                        .push(parent.to_string());
+                }
+            }
+
+            let magic_path = dir.join("magic");
+            match fs::read(&magic_path) {
+                Ok(content) => magic.extend(parse_magic(&magic_path, &content, &mut errors)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    errors.push(AppError::io(LangKeys::MimeDatabaseError, &err).path(&magic_path))
+                }
+            }
+        }
+        magic.sort_by_key(|magic| std::cmp::Reverse(magic.priority));
+
+        Self {
+            globs,
+            magic,
+            subclasses,
+            cache: RefCell::new(HashMap::new()),
+            errors,
+        }
+    }
+
+    /// Errors met while reading the database since the last call.
+    pub fn take_errors(&mut self) -> Vec<AppError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Forgets the detected types, files may have been rewritten.
+    pub fn refresh(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Type of `file`, such as "text/x-python", or `DIRECTORY_MIME_TYPE` for folders.
+    pub fn mime_type(&self, file: &Path, vfs: &dyn Vfs) -> String {
+        if let Some(mime_type) = self.cache.borrow().get(file) {
+            return mime_type.clone();
+        }
+
+        let mime_type = if vfs.is_dir(file) {
+            DIRECTORY_MIME_TYPE.to_string()
+        } else {
+            self.by_name(file)
+                .unwrap_or_else(|| self.by_content(&read_start(file, vfs)))
+        };
+
+        self.cache
+            .borrow_mut()
+            .insert(file.to_path_buf(), mime_type.clone());
+
+        mime_type
+    }
+
+    /// `mime_type` followed by every type it is a subclass of, used to find the
+    /// applications able to open it: text/x-python also opens as text/plain.
+    pub fn with_parents(&self, mime_type: &str) -> Vec<String> {
+        let mut mime_types = vec![mime_type.to_string()];
+
+        let mut idx = 0;
+        while idx < mime_types.len() {
+            let parents = self.subclasses.get(&mime_types[idx]).into_iter().flatten();
+            for parent in parents {
+                if !mime_types.contains(parent) {
+                    mime_types.push(parent.clone());
+                }
+            }
+            idx += 1;
+        }
+
+        // Every text type is implicitly plain text
+        if mime_type.starts_with("text/")
+            && !mime_types.iter().any(|mime_type| mime_type == "text/plain")
+        {
+            mime_types.push(String::from("text/plain"));
+        }
+
+        mime_types
+    }
+
+    fn by_name(&self, file: &Path) -> Option<String> {
+        let file_name = file.file_name()?.to_string_lossy();
+
+        if self.globs.is_empty() {
+            return mime_guess::from_path(file)
+                .first()
+                .map(|mime_type| mime_type.essence_str().to_string());
+        }
+
+        let matching = || self.globs.iter().filter(|glob| glob.matches(&file_name));
+
+        // A literal name wins, then the heaviest, case sensitive and longest pattern
+        matching()
+            .find(|glob| glob.is_literal())
+            .or_else(|| {
+                matching().max_by_key(|glob| (glob.weight, glob.case_sensitive, glob.pattern.len()))
+            })
+            .map(|glob| glob.mime_type.clone())
+    }
+
+    fn by_content(&self, data: &[u8]) -> String {
+        self.magic
+            .iter()
+            .find(|magic| rules_match(&magic.rules, data))
+            .map(|magic| magic.mime_type.clone())
+            .unwrap_or_else(|| sniff(data).to_string())
+    }
+}
+
+/// First bytes of the file, empty if it can not be read.
+fn read_start(file: &Path, vfs: &dyn Vfs) -> Vec<u8> {
+    let mut data = Vec::new();
+    if let Ok(reader) = vfs.open(file) {
+        let _ = reader.take(SNIFF_LEN as u64).read_to_end(&mut data);
+    }
+
+    data
+}
+
+/// Any rule of `rules` matches along with one of its children, if it has some.
+fn rules_match(rules: &[MagicRule], data: &[u8]) -> bool {
+    let mut idx = 0;
+    while idx < rules.len() {
+        let rule = &rules[idx];
+        let children_end = rules[idx + 1..]
+            .iter()
+            .position(|child| child.indent <= rule.indent)
+            .map_or(rules.len(), |position| idx + 1 + position);
+        let children = &rules[idx + 1..children_end];
+
+        if rule.matches(data) && (children.is_empty() || rules_match(children, data)) {
+            return true;
+        }
+        idx = children_end;
+    }
+
+    false
+}
+
+/// Signatures common enough to be recognized without the magic database.
+fn sniff(data: &[u8]) -> &'static str {
+    const SIGNATURES: [(&[u8], &str); 8] = [
+        (b"\x7fELF", "application/x-executable"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    ];
+
+    if data.is_empty() {
+        return "application/x-zerosize";
+    }
+
+    if let Some((_, mime_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| data.starts_with(signature))
+    {
+        return mime_type;
+    }
+
+    if let Some(interpreter) = shebang_interpreter(data) {
+        return match interpreter {
+            "sh" | "bash" | "dash" | "zsh" | "ksh" | "fish" => "application/x-shellscript",
+            "perl" => "application/x-perl",
+            "ruby" => "application/x-ruby",
+            "node" => "application/javascript",
+            interpreter if interpreter.starts_with("python") => "text/x-python",
+            _ => "text/plain",
+        };
+    }
+
+    // The last character may be cut by SNIFF_LEN
+    let is_text = !data.contains(&0)
+        && std::str::from_utf8(data).map_or_else(|err| err.error_len().is_none(), |_| true);
+    if is_text {
+        "text/plain"
+    } else {
+        DEFAULT_MIME_TYPE
+    }
+}
+
+/// `python3` for both `#!/usr/bin/python3` and `#!/usr/bin/env python3`.
+fn shebang_interpreter(data: &[u8]) -> Option<&str> {
+    let line = data
+        .strip_prefix(b"#!")?
+        .split(|byte| *byte == b'\n')
+        .next()?;
+    let mut words = std::str::from_utf8(line).ok()?.split_whitespace();
+
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    Some(program)
+}
+
+/// Lines of `globs2`: `weight:mime/type:pattern[:flags]`, `#` starts a comment.
+fn parse_globs(content: &str) -> Vec<Glob> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, ':');
+            let weight = fields.next()?.parse().ok()?;
+            let mime_type = fields.next()?;
+            let pattern = fields.next()?;
+            let case_sensitive = fields
+                .next()
+                .is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+
+            // Removes the globs of the less important directories, not supported
+            if pattern == "__NOGLOBS__" {
+                return None;
+            }
+
+            Some(Glob {
+                weight,
+                mime_type: mime_type.to_string(),
+                pattern: if case_sensitive {
+                    pattern.to_string()
+                } else {
+                    pattern.to_lowercase()
+                },
+                case_sensitive,
+            })
+        })
+        .collect()
+}
+
+/// Parses the binary `magic` file, a bad section ends the parsing.
+fn parse_magic(path: &Path, content: &[u8], errors: &mut Vec<AppError>) -> Vec<Magic> {
+    let mut sections = Vec::new();
+    let Some(mut rest) = content.strip_prefix(b"MIME-Magic\0\n") else {
+        errors.push(
+            AppError::new(LangKeys::MimeDatabaseError, "unknown magic file header").path(path),
+        );
+        return sections;
+    };
+
+    while let Some(header) = rest.strip_prefix(b"[") {
+        let Some(header_end) = header.iter().position(|byte| *byte == b'\n') else {
+            break;
+        };
+        let Some((priority, mime_type)) = std::str::from_utf8(&header[..header_end])
+            .ok()
+            .and_then(|header| header.strip_suffix(']'))
+            .and_then(|header| header.split_once(':'))
+        else {
+            break;
+        };
+        let Ok(priority) = priority.parse() else {
+            break;
+        };
+        rest = &header[header_end + 1..];
+
+        let mut rules = Vec::new();
+        while !rest.is_empty() && !rest.starts_with(b"[") {
+            let Some((rule, after)) = parse_magic_rule(rest) else {
+                let message = format!("bad rule for {}", mime_type);
+                errors.push(AppError::new(LangKeys::MimeDatabaseError, message).path(path));
+                return sections;
+            };
+            rules.push(rule);
+            rest = after;
+        }
+
+        sections.push(Magic {
+            priority,
+            mime_type: mime_type.to_string(),
+            rules,
+        });
+    }
+
+    sections
+}
+
+/// `[indent]>offset=<u16 length><value>[&mask][~word size][+range]\n`
+fn parse_magic_rule(line: &[u8]) -> Option<(MagicRule, &[u8])> {
+    let (indent, rest) = parse_number(line);
+    let rest = rest.strip_prefix(b">")?;
+    let (offset, rest) = parse_number(rest);
+    let rest = rest.strip_prefix(b"=")?;
+
+    let value_len = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]) as usize;
+    let value = rest.get(2..2 + value_len)?.to_vec();
+    let mut rest = &rest[2 + value_len..];
+
+    let mut mask = None;
+    if let Some(after) = rest.strip_prefix(b"&") {
+        mask = Some(after.get(..value_len)?.to_vec());
+        rest = &after[value_len..];
+    }
+
+    // The word size only matters for byte swapping, values are compared as stored
+    if let Some(after) = rest.strip_prefix(b"~") {
+        rest = parse_number(after).1;
+    }
+
+    let mut range = 1;
+    if let Some(after) = rest.strip_prefix(b"+") {
+        let (parsed, after) = parse_number(after);
+        range = parsed.unwrap_or(1).max(1);
+        rest = after;
+    }
+
+    let rest = rest.strip_prefix(b"\n")?;
+    Some((
+        MagicRule {
+            indent: indent.unwrap_or(0) as u32,
+            offset: offset?,
+            range,
+            value,
+            mask,
+        },
+        rest,
+    ))
+}
+
+/// Leading decimal number, None if there is none.
+fn parse_number(data: &[u8]) -> (Option<usize>, &[u8]) {
+    let digits = data.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let number = std::str::from_utf8(&data[..digits])
+        .ok()
+        .and_then(|digits| digits.parse().ok());
+
+    (number, &data[digits..])
+}
+
+/// Shell glob with `*`, `?` and `[...]` character classes.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Text position to retry from and the pattern position after the last `*`
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(p + 1),
+            Some('[') => class_match(&pattern, p, text[t]),
+            Some(c) if *c == text[t] => Some(p + 1),
+            _ => None,
+        };
+
+        match (step, star) {
+            (Some(next), _) => {
+                p = next;
+                t += 1;
+            }
+            (None, Some((star_p, star_t))) => {
+                p = star_p;
+                t = star_t + 1;
+                star = Some((star_p, star_t + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches `c` against the class starting at `pattern[start]`, returns the position after it.
+fn class_match(pattern: &[char], start: usize, c: char) -> Option<usize> {
+    let end = start + 1 + pattern[start + 1..].iter().position(|c| *c == ']')?;
+    let class = &pattern[start + 1..end];
+
+    let mut matched = false;
+    let mut idx = 0;
+    while idx < class.len() {
+        if class.get(idx + 1) == Some(&'-') && idx + 2 < class.len() {
+            matched |= (class[idx]..=class[idx + 2]).contains(&c);
+            idx += 3;
+        } else {
+            matched |= class[idx] == c;
+            idx += 1;
+        }
+    }
+
+    matched.then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("*.py", "setup.py"));
+        assert!(!glob_match("*.py", "setup.pyc"));
+        assert!(glob_match("makefile", "makefile"));
+        assert!(glob_match("*.[1-9]", "ls.1"));
+        assert!(!glob_match("*.[1-9]", "ls.a"));
+        assert!(glob_match("*.tar.*", "backup.tar.gz"));
+        assert!(glob_match("?ake*", "cakefile"));
+    }
+
+    #[test]
+    fn picks_best_glob() {
+        let manager = MimeManager {
+            globs: parse_globs(
+                "# comment\n\
+                 50:text/x-makefile:makefile\n\
+                 50:application/gzip:*.gz\n\
+                 60:application/x-compressed-tar:*.tar.gz\n\
+                 50:text/x-csrc:*.c\n\
+                 50:text/x-c++src:*.C:cs\n",
+            ),
+            magic: Vec::new(),
+            subclasses: HashMap::new(),
+            cache: RefCell::new(HashMap::new()),
+            errors: Vec::new(),
+        };
+        let by_name = |name: &str| manager.by_name(Path::new(name));
+
+        assert_eq!(by_name("Makefile").as_deref(), Some("text/x-makefile"));
+        assert_eq!(
+            by_name("backup.tar.gz").as_deref(),
+            Some("application/x-compressed-tar")
+        );
+        assert_eq!(by_name("notes.GZ").as_deref(), Some("application/gzip"));
+        assert_eq!(by_name("main.C").as_deref(), Some("text/x-c++src"));
+        assert_eq!(by_name("README"), None);
+    }
+
+    #[test]
+    fn follows_subclasses() {
+        let manager = MimeManager {
+            globs: Vec::new(),
+            magic: Vec::new(),
+            subclasses: HashMap::from([
+                (
+                    String::from("text/x-python3"),
+                    vec![String::from("text/x-python")],
+                ),
+                (
+                    String::from("text/x-python"),
+                    vec![
+                        String::from("application/x-executable"),
+                        String::from("text/plain"),
+                    ],
+                ),
+            ]),
+            cache: RefCell::new(HashMap::new()),
+            errors: Vec::new(),
+        };
+
+        assert_eq!(
+            manager.with_parents("text/x-python3"),
+            vec![
+                "text/x-python3",
+                "text/x-python",
+                "application/x-executable",
+                "text/plain"
+            ]
+        );
+        assert_eq!(
+            manager.with_parents("text/x-rust"),
+            vec!["text/x-rust", "text/plain"]
+        );
+        assert_eq!(manager.with_parents("image/png"), vec!["image/png"]);
+    }
+
+    #[test]
+    fn parses_magic() {
+        let mut content = b"MIME-Magic\0\n[50:image/png]\n>0=\0\x04\x89PNG\n".to_vec();
+        content.extend(b"[40:text/x-custom]\n>0=\0\x02ab+4\n1>8=\0\x01Z&\xdf\n");
+        let mut errors = Vec::new();
+        let magic = parse_magic(Path::new("magic"), &content, &mut errors);
+
+        assert!(errors.is_empty());
+        assert_eq!(magic.len(), 2);
+        assert!(rules_match(&magic[0].rules, b"\x89PNG\r\n"));
+        assert!(rules_match(&magic[1].rules, b"xxab____z"));
+        assert!(!rules_match(&magic[1].rules, b"xxab____y"));
+        assert!(!rules_match(&magic[1].rules, b"xxxxxab_Z"));
+    }
+
+    #[test]
+    fn sniffs_content() {
+        assert_eq!(sniff(b"#!/usr/bin/env python3\nprint()"), "text/x-python");
+        assert_eq!(sniff(b"#!/bin/bash\necho"), "application/x-shellscript");
+        assert_eq!(sniff(b"%PDF-1.7"), "application/pdf");
+        assert_eq!(sniff("all: build\n\tcargo bä".as_bytes()), "text/plain");
+        assert_eq!(sniff(b"\x00\x01\x02"), DEFAULT_MIME_TYPE);
+        assert_eq!(sniff(b""), "application/x-zerosize");
+    }
+}
@@ -1,5 +1,9 @@
+use crate::history::History;
+use crate::recent_locations::RecentLocations;
+use crate::vfs::sftp_vfs::SftpLocation;
+use crate::vfs::{Vfs, VfsRouter};
+use crate::view_settings::ViewSettings;
 use std::env::home_dir;
-use std::fs;
 use std::path::{Path, PathBuf};
 
 #[derive(PartialEq)]
@@ -12,11 +16,8 @@ pub struct PathManager {
     /// The current directory path being viewed in the application.
     pub current_path: PathBuf,
 
-    /// A stack of previously visited paths, used for "Go Back" navigation.
-    pub previous_paths: Vec<PathBuf>,
-
-    /// A stack of paths used for "Go Forward" navigation after moving backward in history.
-    pub next_paths: Vec<PathBuf>,
+    /// Visited paths, used for "Go Back" and "Go Forward" navigation.
+    pub history: History,
 
     /// A list of files and folders contained within the current directory.
     pub directory_content: Vec<PathBuf>,
@@ -34,6 +35,22 @@ pub struct PathManager {
 
     /// A flag used to signal that the mouse cursor icon should be updated (e.g., to a loading or pointer state).
     pub update_cursor_icon: bool,
+
+    /// Every file system access goes through here, so archives and other
+    /// locations are browsed exactly like local folders.
+    pub vfs: VfsRouter,
+
+    /// Text typed inside the "Connect to server" field.
+    pub remote_location_query: String,
+
+    /// View mode and sort order of directory_content.
+    pub view_settings: ViewSettings,
+
+    /// Every directory visited, kept between sessions.
+    pub recent_locations: RecentLocations,
+
+    /// Dot files are left out of directory_content unless this is set.
+    pub show_hidden: bool,
 }
 
 impl PathManager {
@@ -48,51 +65,122 @@ impl PathManager {
 
         PathManager {
             current_path: home_path.clone(),
-            previous_paths: Vec::new(),
-            next_paths: Vec::new(),
+            history: History::new(home_path.clone()),
             directory_content: Vec::new(),
             directory_action: DirectoryActions::DisplayDirectory,
             deleted_folder: home_path.exists(),
             update_folder_content: true,
             update_cursor_icon: true,
+            vfs: VfsRouter::new(),
+            remote_location_query: String::new(),
+            view_settings: ViewSettings::default(),
+            recent_locations: RecentLocations::default(),
+            show_hidden: false,
         }
     }
 
-    pub fn update_current_directory(&mut self, path: &Path) {
-        self.previous_paths.push(self.current_path.clone());
+    /// Reopens the location of the previous session, if it can still be browsed.
+    /// Servers are not reconnected on startup.
+    pub fn restore_location(&mut self, path: &Path) {
+        if SftpLocation::parse(path).is_some() {
+            return;
+        }
+
+        let is_dir = self
+            .vfs
+            .mount_for(path)
+            .map(|_| self.vfs.is_dir(path))
+            .unwrap_or(false);
+        if is_dir {
+            self.history = History::new(path.to_path_buf());
+            self.set_current_directory(path);
+        }
+    }
+
+    fn set_current_directory(&mut self, path: &Path) {
         self.current_path = path.into();
+        self.recent_locations.record(path);
         self.update_folder_content = true;
         self.directory_action = DirectoryActions::DisplayDirectory;
     }
 
+    /// Every navigation to a new location goes through here, so the history stays consistent.
+    pub fn update_current_directory(&mut self, path: &Path) {
+        self.history.push(path.into());
+        self.set_current_directory(path);
+    }
+
+    pub fn go_back(&mut self) {
+        if let Some(path) = self.history.back().map(Path::to_path_buf) {
+            self.set_current_directory(&path);
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        if let Some(path) = self.history.forward().map(Path::to_path_buf) {
+            self.set_current_directory(&path);
+        }
+    }
+
+    /// Jumps to an entry of the history without changing it.
+    pub fn go_to_history_entry(&mut self, idx: usize) {
+        if let Some(path) = self.history.go_to(idx).map(Path::to_path_buf) {
+            self.set_current_directory(&path);
+        }
+    }
+
+    /// Parent of the current_path, None at the root of a disk or of a server.
+    pub fn parent_directory(&self) -> Option<PathBuf> {
+        if let Some(location) = SftpLocation::parse(&self.current_path)
+            && location.path.parent().is_none()
+        {
+            return None;
+        }
+
+        self.current_path.parent().map(Path::to_path_buf)
+    }
+
+    pub fn go_up(&mut self) {
+        if let Some(parent) = self.parent_directory() {
+            self.update_current_directory(&parent);
+        }
+    }
+
     pub fn fill_directory_content(&mut self) -> std::io::Result<()> {
         self.directory_content.clear();
         self.update_folder_content = false;
 
-        let entries = match fs::read_dir(&self.current_path) {
+        let entries = match self
+            .vfs
+            .mount_for(&self.current_path)
+            .and_then(|_| self.vfs.list(&self.current_path))
+        {
             Ok(entries) => {
                 self.deleted_folder = false;
                 entries
             }
+            // Listed on a later frame, once the server is connected
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                self.update_folder_content = true;
+                return Err(err);
+            }
             Err(err) => {
                 self.deleted_folder = true;
                 return Err(err);
             }
         };
 
-        self.directory_content.extend(
-            entries
-                .filter_map(|entry| entry.ok())
-                .map(|entry| entry.path()),
-        );
-
-        self.directory_content.sort();
-        self.directory_content.sort_by_key(|key| {
-            key.metadata()
-                .map(|metadata| metadata.is_file())
-                .unwrap_or(true)
-        });
+        let show_hidden = self.show_hidden;
+        self.directory_content
+            .extend(entries.into_iter().filter(|entry| show_hidden || !is_hidden(entry)));
+
+        self.view_settings.sort(&mut self.directory_content, &self.vfs);
 
         Ok(())
     }
 }
+
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'))
+}
@@ -0,0 +1,127 @@
+use crate::progress::ProgressSender;
+use nix::unistd::{Gid, Group, Uid, User};
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// Execute bits of the owner, group and others.
+const EXECUTE_BITS: u32 = 0o111;
+
+/// Ownership and mode requested from the Properties dialog, None fields are left unchanged.
+pub struct PermissionChange {
+    pub file: PathBuf,
+    pub mode: Option<u32>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+
+    /// Also applied to everything inside the folder.
+    pub recursive: bool,
+}
+
+pub fn user_name(uid: u32) -> Option<String> {
+    User::from_uid(Uid::from_raw(uid))
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+}
+
+pub fn group_name(gid: u32) -> Option<String> {
+    Group::from_gid(Gid::from_raw(gid))
+        .ok()
+        .flatten()
+        .map(|group| group.name)
+}
+
+/// Accepts a user name or a numeric id.
+pub fn parse_user(text: &str) -> Option<u32> {
+    let text = text.trim();
+    text.parse().ok().or_else(|| {
+        User::from_name(text)
+            .ok()
+            .flatten()
+            .map(|user| user.uid.as_raw())
+    })
+}
+
+/// Accepts a group name or a numeric id.
+pub fn parse_group(text: &str) -> Option<u32> {
+    let text = text.trim();
+    text.parse().ok().or_else(|| {
+        Group::from_name(text)
+            .ok()
+            .flatten()
+            .map(|group| group.gid.as_raw())
+    })
+}
+
+/// Number of entries a recursive change goes through.
+fn count_entries(path: &Path) -> u64 {
+    let is_dir = path
+        .symlink_metadata()
+        .is_ok_and(|metadata| metadata.is_dir());
+    if !is_dir {
+        return 1;
+    }
+
+    1 + fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| count_entries(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn apply_to(
+    path: &Path,
+    change: &PermissionChange,
+    is_root: bool,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    let metadata = path.symlink_metadata()?;
+
+    if change.uid.is_some() || change.gid.is_some() {
+        // Links are changed themselves, not their target
+        std::os::unix::fs::lchown(path, change.uid, change.gid)?;
+    }
+
+    // Symbolic links have no mode of their own
+    if let Some(mode) = change.mode
+        && !metadata.file_type().is_symlink()
+    {
+        // Inside the folder, files keep their own execute bits so that
+        // making a folder traversable does not make every file executable
+        let mode = if is_root || metadata.is_dir() {
+            mode
+        } else {
+            (mode & !EXECUTE_BITS) | (metadata.permissions().mode() & EXECUTE_BITS)
+        };
+
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+
+    // Entries are counted instead of bytes
+    progress.advance(1, path)?;
+
+    if change.recursive && metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            apply_to(&entry?.path(), change, false, progress)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies `change`, meant to run on a background thread.
+pub fn apply(change: &PermissionChange, progress: &ProgressSender) -> io::Result<()> {
+    let total = if change.recursive {
+        count_entries(&change.file)
+    } else {
+        1
+    };
+    progress.total(total);
+
+    apply_to(&change.file, change, true, progress)
+}
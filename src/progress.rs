@@ -0,0 +1,88 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+/// Messages sent by a background operation to the modal displaying it.
+pub enum Progress {
+    /// Number of bytes the whole operation is going to process.
+    Total(u64),
+    Advance { bytes: u64, file: PathBuf },
+    Finished(io::Result<()>),
+}
+
+/// Pause and cancel requests of the user, read by the background thread.
+#[derive(Default)]
+pub struct JobControl {
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl JobControl {
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Handed to the work of a background operation to report its progress.
+/// Reporting is also where the work waits while paused and stops once cancelled.
+pub struct ProgressSender {
+    sender: Sender<Progress>,
+    control: Arc<JobControl>,
+}
+
+impl ProgressSender {
+    pub fn new(sender: Sender<Progress>, control: Arc<JobControl>) -> Self {
+        Self { sender, control }
+    }
+
+    pub fn total(&self, total: u64) {
+        let _ = self.sender.send(Progress::Total(total));
+    }
+
+    /// Reports `bytes` processed in `file`, then checks for a pause or a cancellation.
+    pub fn advance(&self, bytes: u64, file: &Path) -> io::Result<()> {
+        let _ = self.sender.send(Progress::Advance {
+            bytes,
+            file: file.to_path_buf(),
+        });
+
+        self.checkpoint()
+    }
+
+    /// Blocks while the operation is paused.
+    /// Fails with `ErrorKind::Interrupted` once it is cancelled, so `?` stops the work.
+    pub fn checkpoint(&self) -> io::Result<()> {
+        while self.control.is_paused() && !self.control.is_cancelled() {
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        if self.control.is_cancelled() {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "cancelled by the user",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn finished(&self, result: io::Result<()>) {
+        let _ = self.sender.send(Progress::Finished(result));
+    }
+}
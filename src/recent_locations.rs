@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Least relevant locations are forgotten past this many entries.
+const MAX_RECENT_LOCATIONS: usize = 200;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecentLocation {
+    pub path: PathBuf,
+    pub visits: u32,
+
+    /// Seconds since the unix epoch.
+    pub last_visit: u64,
+}
+
+impl RecentLocation {
+    /// Frecency: visit count weighted by how long ago the last visit was.
+    pub fn frecency(&self, now: u64) -> u64 {
+        const DAY: u64 = 24 * 60 * 60;
+
+        let weight = match now.saturating_sub(self.last_visit) {
+            age if age < 4 * DAY => 100,
+            age if age < 14 * DAY => 70,
+            age if age < 31 * DAY => 50,
+            age if age < 90 * DAY => 30,
+            _ => 10,
+        };
+
+        self.visits as u64 * weight
+    }
+}
+
+/// Directories visited across sessions, ranked by frecency.
+#[derive(Default, Serialize, Deserialize)]
+pub struct RecentLocations {
+    entries: Vec<RecentLocation>,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+impl RecentLocations {
+    pub fn record(&mut self, path: &Path) {
+        let now = unix_now();
+
+        match self.entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => {
+                entry.visits += 1;
+                entry.last_visit = now;
+            }
+            None => self.entries.push(RecentLocation {
+                path: path.to_path_buf(),
+                visits: 1,
+                last_visit: now,
+            }),
+        }
+
+        if self.entries.len() > MAX_RECENT_LOCATIONS {
+            self.entries
+                .sort_by_key(|entry| std::cmp::Reverse(entry.frecency(now)));
+            self.entries.truncate(MAX_RECENT_LOCATIONS);
+        }
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        self.entries.retain(|entry| entry.path != path);
+    }
+
+    /// Every location, most relevant first.
+    pub fn ranked(&self) -> Vec<&RecentLocation> {
+        let now = unix_now();
+        let mut ranked: Vec<_> = self.entries.iter().collect();
+        ranked.sort_by_key(|entry| std::cmp::Reverse(entry.frecency(now)));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    fn location(path: &str, visits: u32, last_visit: u64) -> RecentLocation {
+        RecentLocation {
+            path: PathBuf::from(path),
+            visits,
+            last_visit,
+        }
+    }
+
+    #[test]
+    fn frecency_decays_with_age() {
+        let now = 1000 * DAY;
+        let frecency = |age: u64| location("/a", 2, now - age).frecency(now);
+
+        assert_eq!(frecency(0), 200);
+        assert_eq!(frecency(5 * DAY), 140);
+        assert_eq!(frecency(20 * DAY), 100);
+        assert_eq!(frecency(60 * DAY), 60);
+        assert_eq!(frecency(365 * DAY), 20);
+
+        // A last visit in the future (clock change) counts as recent
+        assert_eq!(location("/a", 2, now + DAY).frecency(now), 200);
+    }
+
+    #[test]
+    fn ranks_recent_visits_above_old_frequent_ones() {
+        let now = unix_now();
+        let locations = RecentLocations {
+            entries: vec![
+                location("/old", 8, now - 100 * DAY),
+                location("/recent", 2, now),
+                location("/weekly", 4, now - 7 * DAY),
+            ],
+        };
+
+        let ranked: Vec<_> = locations
+            .ranked()
+            .into_iter()
+            .map(|entry| entry.path.clone())
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                PathBuf::from("/weekly"),
+                PathBuf::from("/recent"),
+                PathBuf::from("/old")
+            ]
+        );
+    }
+
+    #[test]
+    fn recording_counts_visits() {
+        let mut locations = RecentLocations::default();
+        locations.record(Path::new("/a"));
+        locations.record(Path::new("/b"));
+        locations.record(Path::new("/a"));
+
+        let ranked = locations.ranked();
+        assert_eq!(ranked[0].path, Path::new("/a"));
+        assert_eq!(ranked[0].visits, 2);
+        assert_eq!(ranked[1].visits, 1);
+    }
+}
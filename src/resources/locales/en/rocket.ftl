@@ -0,0 +1,227 @@
+# English, also used for every message missing from another locale.
+
+go-back = Go back
+go-forward = Go forward
+go-up = Up
+deleted-folder = Folder has been deleted.
+empty-folder = Folder is empty.
+nothing-selected = No file is selected.
+file-type = Type: { $mime_type }
+created-at = Created at: { $date }
+accessed-at = Accessed at: { $date }
+modified-at = Modified at: { $date }
+extract = Extract
+extract-here = Extract here
+compress = Compress...
+archive-name = Archive name
+archive-format = Format
+compression-level = Compression level
+level-store = Store
+level-fast = Fast
+level-normal = Normal
+level-best = Best
+create = Create
+cancel = Cancel
+close = Close
+extracting = Extracting { $name }
+compressing = Compressing { $name }
+action-done = Done.
+connect-to-server = Connect to server
+disconnect = Disconnect
+path-does-not-exist = This path does not exist.
+path-not-a-folder = This path is not a folder.
+path-not-absolute = The path must be absolute.
+copying = Copying to { $destination }
+moving = Moving to { $destination }
+list-view = List
+grid-view = Grid
+sort-by-name = Name
+sort-by-size = Size
+sort-by-modified = Modified
+sort-by-extension = Type
+ascending = Ascending
+descending = Descending
+go-to-location = Go to location
+no-recent-locations = No recent locations
+move-files = Move files
+confirm-move-files = { $count ->
+        [one] Move one item to { $destination }?
+       *[other] Move { $count } items to { $destination }?
+    }
+move = Move
+settings = Settings
+theme = Theme
+theme-dark = Dark
+theme-light = Light
+theme-system = System
+language = Language
+font-size = Font size
+show-hidden-files = Show hidden files
+default-view = Default view
+confirmations = Confirmations
+ask-before-moving = Ask before moving dropped files
+ask-before-deleting = Ask before deleting files
+date-format = Date format
+size-units = Size units
+size-units-binary = Binary (KiB, MiB)
+size-units-decimal = Decimal (KB, MB)
+row-height = Row height
+files-selected = { $count ->
+        [one] One item selected
+       *[other] { $count } items selected
+    }
+search-for = Search for ...
+quick-access = Quick access
+read-folder-error = Could not open the folder
+open-file-error = Could not open the file
+move-error = Could not move the files
+edit-location = Edit location
+command-palette = Command palette
+toggle-hidden-files = Show or hide hidden files
+view-as-list = View as list
+view-as-grid = View as grid
+refresh = Refresh
+copy = Copy
+cut = Cut
+paste = Paste
+no-matching-commands = No matching commands
+select-all = Select all
+invert-selection = Invert selection
+select-by-pattern = Select by pattern
+select-pattern-hint = e.g. *.log; *.txt
+no-files-match = No files match this pattern
+select = Select
+linking = Linking into { $destination }
+drop-files-here = Drop the files to copy them here
+open = Open
+open-with = Open with
+no-applications = No applications found
+rename = Rename
+move-to-trash = Move to trash
+copy-path = Copy path
+properties = Properties
+new-folder = New Folder
+new-file = New File
+name = Name
+location = Location
+kind = Type
+folder = Folder
+contents = Contents
+size = Size
+created = Created
+accessed = Accessed
+modified = Modified
+read-only = Read-only
+rename-error = Could not rename the file
+trash-error = Could not move the files to the trash
+create-error = Could not create the file
+launch-error = Could not start the application
+item-count = { $count ->
+        [one] One item
+       *[other] { $count } items
+    }
+copy-error = Could not copy the files
+link-error = Could not create the links
+new-from-template = New from template
+no-templates = Put files inside { $directory } to use them as templates
+owner = Owner
+group = Group
+others = Others
+permissions = Permissions
+read = Read
+write = Write
+execute = Execute
+set-user-id = Set user ID
+set-group-id = Set group ID
+sticky = Sticky
+apply-recursively = Apply to everything inside
+link-target = Link target
+inode = Inode
+device = Device
+hard-links = Hard links
+extended-attributes = Extended attributes
+value = Value
+add = Add
+remove = Remove
+apply = Apply
+unknown-user = Unknown user
+unknown-group = Unknown group
+changing-permissions = Changing the permissions of { $name }
+create-symlink = Create symbolic link
+create-hard-link = Create hard link
+go-to-link-target = Go to link target
+link-to = Link to { $name }
+points-to = Points to { $target }
+broken-link = Broken link to { $target }
+icon-theme = Icon theme
+bundled-icons = Built-in
+git = Git
+git-stage = Stage
+git-unstage = Unstage
+git-discard = Discard changes
+git-show-diff = Show diff
+git-error = Git operation failed
+confirm-git-discard = Discard the unstaged changes of the selected files? This can not be undone.
+git-diff-of = Diff of { $name }
+git-no-changes = No changes
+git-ahead-behind = { $ahead } ahead, { $behind } behind { $upstream }
+git-no-upstream = No upstream branch
+terminal = Terminal
+toggle-terminal = Toggle terminal
+new-terminal = New terminal
+close-terminal = Close terminal
+insert-selection = Insert selected paths
+terminal-exited = The process has exited
+terminal-start-error = Cannot start the terminal
+user-actions = Custom actions
+command-running = Running…
+command-exit-status = Exited with status { $code }
+command-killed = Terminated by a signal
+no-output = No output
+jobs = Jobs
+toggle-jobs = Show or hide the jobs panel
+jobs-running = { $count } running
+no-running-jobs = No job is running
+job-history = History
+clear-history = Clear history
+show-job = Show
+job-speed = { $speed }, { $eta } left
+job-paused = Paused
+job-cancelled = Cancelled.
+job-pause = Pause
+job-resume = Resume
+run-in-background = Run in background
+run-in-background-hint = Hides this window, the job stays in the jobs panel
+moving-to-trash = Moving to the trash ({ $count })
+job-finished-in = Took { $duration }
+error-log = Error log
+show-error-log = Show the error log
+retry = Retry
+copy-details = Copy details
+clear-log = Clear
+no-errors = No errors
+job-failed = An operation failed
+icon-load-error = Could not load an icon
+icon-pack-error = Could not read the icon pack
+unknown-icon-theme = Unknown icon theme
+search-error = Could not search a folder
+connecting = Connecting…
+unknown-server = Unknown server
+unknown-server-question = { $host } is not in ~/.ssh/known_hosts. Check that its key fingerprint is:
+trust-server = Trust and connect
+config-load-error = Could not read the settings, the defaults are used
+config-save-error = Could not save the settings
+confirm-trash-files = { $count ->
+        [one] Move one item to the trash?
+       *[other] Move { $count } items to the trash?
+    }
+delete-permanently = Delete permanently
+confirm-delete-files = { $count ->
+        [one] Delete one item permanently? This can not be undone.
+       *[other] Delete { $count } items permanently? This can not be undone.
+    }
+deleting-files = Deleting ({ $count })
+delete-error = Could not delete the files
+templates-error = Could not read the templates folder
+mime-database-error = Could not read the file type database
+application-load-error = Could not read the application entry
@@ -0,0 +1,232 @@
+# Romanian
+
+go-back = Înapoi
+go-forward = Înainte
+go-up = Sus
+deleted-folder = Folderul a fost șters.
+empty-folder = Folderul este gol.
+nothing-selected = Niciun fișier selectat.
+file-type = Tip: { $mime_type }
+created-at = Creat la: { $date }
+accessed-at = Accesat la: { $date }
+modified-at = Modificat la: { $date }
+extract = Extrage
+extract-here = Extrage aici
+compress = Arhivează...
+archive-name = Numele arhivei
+archive-format = Format
+compression-level = Nivel de compresie
+level-store = Fără compresie
+level-fast = Rapid
+level-normal = Normal
+level-best = Maxim
+create = Creează
+cancel = Anulează
+close = Închide
+extracting = Se extrage { $name }
+compressing = Se arhivează { $name }
+action-done = Gata.
+connect-to-server = Conectare la server
+disconnect = Deconectare
+path-does-not-exist = Această cale nu există.
+path-not-a-folder = Această cale nu este un folder.
+path-not-absolute = Calea trebuie să fie absolută.
+copying = Se copiază în { $destination }
+moving = Se mută în { $destination }
+list-view = Listă
+grid-view = Grilă
+sort-by-name = Nume
+sort-by-size = Mărime
+sort-by-modified = Modificat
+sort-by-extension = Tip
+ascending = Crescător
+descending = Descrescător
+go-to-location = Mergi la locație
+no-recent-locations = Nicio locație recentă
+move-files = Mutare fișiere
+confirm-move-files = { $count ->
+        [one] Mutați un element în { $destination }?
+        [few] Mutați { $count } elemente în { $destination }?
+       *[other] Mutați { $count } de elemente în { $destination }?
+    }
+move = Mută
+settings = Setări
+theme = Temă
+theme-dark = Întunecată
+theme-light = Luminoasă
+theme-system = Sistem
+language = Limbă
+font-size = Mărime font
+show-hidden-files = Afișează fișierele ascunse
+default-view = Vizualizare implicită
+confirmations = Confirmări
+ask-before-moving = Întreabă înainte de a muta fișierele trase
+ask-before-deleting = Întreabă înainte de a șterge fișiere
+date-format = Format dată
+size-units = Unități de mărime
+size-units-binary = Binare (KiB, MiB)
+size-units-decimal = Zecimale (KB, MB)
+row-height = Înălțime rând
+files-selected = { $count ->
+        [one] Un element selectat
+        [few] { $count } elemente selectate
+       *[other] { $count } de elemente selectate
+    }
+search-for = Caută ...
+quick-access = Acces rapid
+read-folder-error = Folderul nu a putut fi deschis
+open-file-error = Fișierul nu a putut fi deschis
+move-error = Fișierele nu au putut fi mutate
+edit-location = Editează locația
+command-palette = Paleta de comenzi
+toggle-hidden-files = Afișează sau ascunde fișierele ascunse
+view-as-list = Vizualizare ca listă
+view-as-grid = Vizualizare ca grilă
+refresh = Reîmprospătează
+copy = Copiază
+cut = Decupează
+paste = Lipește
+no-matching-commands = Nicio comandă găsită
+select-all = Selectează tot
+invert-selection = Inversează selecția
+select-by-pattern = Selectează după model
+select-pattern-hint = ex. *.log; *.txt
+no-files-match = Niciun fișier nu se potrivește cu acest model
+select = Selectează
+linking = Se creează legături în { $destination }
+drop-files-here = Lăsați fișierele pentru a le copia aici
+open = Deschide
+open-with = Deschide cu
+no-applications = Nicio aplicație găsită
+rename = Redenumește
+move-to-trash = Mută la coșul de gunoi
+copy-path = Copiază calea
+properties = Proprietăți
+new-folder = Dosar nou
+new-file = Fișier nou
+name = Nume
+location = Locație
+kind = Tip
+folder = Dosar
+contents = Conținut
+size = Dimensiune
+created = Creat
+accessed = Accesat
+modified = Modificat
+read-only = Doar citire
+rename-error = Fișierul nu a putut fi redenumit
+trash-error = Fișierele nu au putut fi mutate la coșul de gunoi
+create-error = Fișierul nu a putut fi creat
+launch-error = Aplicația nu a putut fi pornită
+item-count = { $count ->
+        [one] Un element
+        [few] { $count } elemente
+       *[other] { $count } de elemente
+    }
+copy-error = Fișierele nu au putut fi copiate
+link-error = Legăturile nu au putut fi create
+new-from-template = Nou din șablon
+no-templates = Puneți fișiere în { $directory } pentru a le folosi ca șabloane
+owner = Proprietar
+group = Grup
+others = Alții
+permissions = Permisiuni
+read = Citire
+write = Scriere
+execute = Execuție
+set-user-id = Setează ID-ul utilizatorului
+set-group-id = Setează ID-ul grupului
+sticky = Lipicios
+apply-recursively = Aplică pentru tot conținutul
+link-target = Ținta legăturii
+inode = Inod
+device = Dispozitiv
+hard-links = Legături fizice
+extended-attributes = Atribute extinse
+value = Valoare
+add = Adaugă
+remove = Elimină
+apply = Aplică
+unknown-user = Utilizator necunoscut
+unknown-group = Grup necunoscut
+changing-permissions = Se schimbă permisiunile pentru { $name }
+create-symlink = Creează legătură simbolică
+create-hard-link = Creează legătură fizică
+go-to-link-target = Mergi la ținta legăturii
+link-to = Legătură către { $name }
+points-to = Indică spre { $target }
+broken-link = Legătură ruptă către { $target }
+icon-theme = Temă de pictograme
+bundled-icons = Încorporate
+git = Git
+git-stage = Adaugă în index
+git-unstage = Scoate din index
+git-discard = Renunță la modificări
+git-show-diff = Arată diferențele
+git-error = Operația git a eșuat
+confirm-git-discard = Renunți la modificările neadăugate în index ale fișierelor selectate? Acțiunea nu poate fi anulată.
+git-diff-of = Diferențele pentru { $name }
+git-no-changes = Nicio modificare
+git-ahead-behind = { $ahead } înainte, { $behind } în urmă față de { $upstream }
+git-no-upstream = Nicio ramură urmărită
+terminal = Terminal
+toggle-terminal = Comută terminalul
+new-terminal = Terminal nou
+close-terminal = Închide terminalul
+insert-selection = Inserează căile selectate
+terminal-exited = Procesul s-a încheiat
+terminal-start-error = Terminalul nu poate fi pornit
+user-actions = Acțiuni personalizate
+command-running = Se execută…
+command-exit-status = Încheiat cu codul { $code }
+command-killed = Oprit de un semnal
+no-output = Niciun rezultat
+jobs = Operațiuni
+toggle-jobs = Afișează sau ascunde panoul de operațiuni
+jobs-running = { $count } în desfășurare
+no-running-jobs = Nicio operațiune în desfășurare
+job-history = Istoric
+clear-history = Șterge istoricul
+show-job = Afișează
+job-speed = { $speed }, { $eta } rămase
+job-paused = În pauză
+job-cancelled = Anulat.
+job-pause = Pauză
+job-resume = Continuă
+run-in-background = Rulează în fundal
+run-in-background-hint = Ascunde fereastra, operațiunea rămâne în panoul de operațiuni
+moving-to-trash = Mutare în coșul de gunoi ({ $count })
+job-finished-in = A durat { $duration }
+error-log = Jurnal de erori
+show-error-log = Afișează jurnalul de erori
+retry = Reîncearcă
+copy-details = Copiază detaliile
+clear-log = Golește
+no-errors = Nicio eroare
+job-failed = O operațiune a eșuat
+icon-load-error = Pictograma nu a putut fi încărcată
+icon-pack-error = Pachetul de pictograme nu a putut fi citit
+unknown-icon-theme = Temă de pictograme necunoscută
+search-error = Un folder nu a putut fi căutat
+connecting = Se conectează…
+unknown-server = Server necunoscut
+unknown-server-question = { $host } nu se află în ~/.ssh/known_hosts. Verificați că amprenta cheii sale este:
+trust-server = Acordă încredere și conectează
+config-load-error = Setările nu au putut fi citite, se folosesc valorile implicite
+config-save-error = Setările nu au putut fi salvate
+confirm-trash-files = { $count ->
+        [one] Mutați un element în coșul de gunoi?
+        [few] Mutați { $count } elemente în coșul de gunoi?
+       *[other] Mutați { $count } de elemente în coșul de gunoi?
+    }
+delete-permanently = Șterge definitiv
+confirm-delete-files = { $count ->
+        [one] Ștergeți definitiv un element? Acțiunea nu poate fi anulată.
+        [few] Ștergeți definitiv { $count } elemente? Acțiunea nu poate fi anulată.
+       *[other] Ștergeți definitiv { $count } de elemente? Acțiunea nu poate fi anulată.
+    }
+deleting-files = Ștergere ({ $count })
+delete-error = Fișierele nu au putut fi șterse
+templates-error = Dosarul de șabloane nu a putut fi citit
+mime-database-error = Baza de date a tipurilor de fișiere nu a putut fi citită
+application-load-error = Intrarea aplicației nu a putut fi citită
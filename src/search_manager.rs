@@ -1,3 +1,5 @@
+use crate::error_manager::AppError;
+use crate::lang_string::LangKeys;
 use rayon::prelude::*;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
@@ -11,11 +13,16 @@ pub struct SearchManager {
     pub searching: bool,
     pub search_thread_sender: Sender<PathBuf>,
     pub search_thread_receiver: Receiver<PathBuf>,
+
+    /// Folders which could not be read while searching.
+    pub search_error_sender: Sender<AppError>,
+    pub search_error_receiver: Receiver<AppError>,
 }
 
 impl SearchManager {
     pub fn new() -> Self {
         let search_channels = mpsc::channel();
+        let search_error_channels = mpsc::channel();
 
         SearchManager {
             search_content: Vec::new(),
@@ -23,6 +30,8 @@ impl SearchManager {
             searching: false,
             search_thread_sender: search_channels.0,
             search_thread_receiver: search_channels.1,
+            search_error_sender: search_error_channels.0,
+            search_error_receiver: search_error_channels.1,
         }
     }
 
@@ -58,11 +67,17 @@ impl SearchManager {
         self.searching = false;
     }
 
+    /// Errors sent by the search threads since the last call.
+    pub fn take_errors(&self) -> Vec<AppError> {
+        self.search_error_receiver.try_iter().collect()
+    }
+
     // Internal function, used by search()
     pub fn search_starting_from(
         directory: &Path,
         search_query: &String,
         search_thread_sender: &Sender<PathBuf>,
+        search_error_sender: &Sender<AppError>,
     ) {
         match directory.read_dir() {
             Ok(entries) => {
@@ -86,12 +101,14 @@ impl SearchManager {
                             &entry.path(),
                             search_query,
                             search_thread_sender,
+                            search_error_sender,
                         );
                     }
                 });
             }
             Err(e) => {
-                println!("Error reading directory: {}", e);
+                let _ = search_error_sender
+                    .send(AppError::io(LangKeys::SearchError, &e).path(directory));
             }
         }
     }
@@ -0,0 +1,37 @@
+use crate::path_manager::PathManager;
+use crate::view_settings::ViewSettings;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// eframe storage keys.
+const SESSION_KEY: &str = "session";
+const RECENT_LOCATIONS_KEY: &str = "recent_locations";
+
+/// What is restored on the next launch.
+/// Window size and panel widths are persisted by eframe and egui themselves.
+#[derive(Serialize, Deserialize)]
+struct Session {
+    current_path: PathBuf,
+    view_settings: ViewSettings,
+}
+
+pub fn load(storage: &dyn eframe::Storage, path_manager: &mut PathManager) {
+    if let Some(recent_locations) = eframe::get_value(storage, RECENT_LOCATIONS_KEY) {
+        path_manager.recent_locations = recent_locations;
+    }
+
+    if let Some(session) = eframe::get_value::<Session>(storage, SESSION_KEY) {
+        path_manager.view_settings = session.view_settings;
+        path_manager.restore_location(&session.current_path);
+    }
+}
+
+pub fn save(storage: &mut dyn eframe::Storage, path_manager: &PathManager) {
+    let session = Session {
+        current_path: path_manager.current_path.clone(),
+        view_settings: path_manager.view_settings,
+    };
+
+    eframe::set_value(storage, SESSION_KEY, &session);
+    eframe::set_value(storage, RECENT_LOCATIONS_KEY, &path_manager.recent_locations);
+}
@@ -0,0 +1,168 @@
+use crate::vfs::VfsRouter;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Folders drawn with an icon of their own.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FolderKind {
+    Plain,
+    Home,
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Public,
+    Templates,
+    Videos,
+
+    /// Work tree of a git repository.
+    Repository,
+
+    /// Root of a file system: a disk, a partition or a network share mounted locally.
+    MountPoint,
+
+    /// Root of a remote server.
+    Remote,
+}
+
+impl FolderKind {
+    /// Freedesktop icon name, looked up in the icon pack or theme.
+    pub fn icon_name(&self) -> &'static str {
+        match self {
+            FolderKind::Plain => "folder",
+            FolderKind::Home => "user-home",
+            FolderKind::Desktop => "user-desktop",
+            FolderKind::Documents => "folder-documents",
+            FolderKind::Downloads => "folder-download",
+            FolderKind::Music => "folder-music",
+            FolderKind::Pictures => "folder-pictures",
+            FolderKind::Public => "folder-publicshare",
+            FolderKind::Templates => "folder-templates",
+            FolderKind::Videos => "folder-videos",
+            FolderKind::Repository => "folder-git",
+            FolderKind::MountPoint => "drive-harddisk",
+            FolderKind::Remote => "folder-remote",
+        }
+    }
+
+    /// Symbol painted over the bundled folder icon, which has no variants.
+    pub fn glyph(&self) -> Option<&'static str> {
+        match self {
+            FolderKind::Plain => None,
+            FolderKind::Home => Some("🏠"),
+            FolderKind::Desktop => Some("🖥"),
+            FolderKind::Documents => Some("📄"),
+            FolderKind::Downloads => Some("⬇"),
+            FolderKind::Music => Some("🎵"),
+            FolderKind::Pictures => Some("🖼"),
+            FolderKind::Public => Some("👥"),
+            FolderKind::Templates => Some("📋"),
+            FolderKind::Videos => Some("🎞"),
+            FolderKind::Repository => Some("🔀"),
+            FolderKind::MountPoint => Some("💾"),
+            FolderKind::Remote => Some("🌐"),
+        }
+    }
+}
+
+/// Recognizes the home folder, the XDG user folders, repositories, mount points and servers.
+pub struct SpecialFolders {
+    user_dirs: Vec<(PathBuf, FolderKind)>,
+
+    /// Kinds already detected, repositories and mount points are found with a few stats.
+    kinds: RefCell<HashMap<PathBuf, FolderKind>>,
+}
+
+impl SpecialFolders {
+    pub fn new() -> Self {
+        let user_dirs = [
+            (dirs::home_dir(), FolderKind::Home),
+            (dirs::desktop_dir(), FolderKind::Desktop),
+            (dirs::document_dir(), FolderKind::Documents),
+            (dirs::download_dir(), FolderKind::Downloads),
+            (dirs::audio_dir(), FolderKind::Music),
+            (dirs::picture_dir(), FolderKind::Pictures),
+            (dirs::public_dir(), FolderKind::Public),
+            (dirs::template_dir(), FolderKind::Templates),
+            (dirs::video_dir(), FolderKind::Videos),
+        ]
+        .into_iter()
+        .filter_map(|(dir, kind)| Some((dir?, kind)))
+        // Unset XDG folders point to the home folder itself
+        .filter(|(dir, kind)| *kind == FolderKind::Home || Some(dir) != dirs::home_dir().as_ref())
+        .collect();
+
+        Self {
+            user_dirs,
+            kinds: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Folders may have become repositories or mount points since they were looked at.
+    pub fn refresh(&self) {
+        self.kinds.borrow_mut().clear();
+    }
+
+    pub fn kind(&self, dir: &Path, vfs: &VfsRouter) -> FolderKind {
+        if let Some(kind) = self.kinds.borrow().get(dir) {
+            return *kind;
+        }
+
+        let kind = self.detect(dir, vfs);
+        self.kinds.borrow_mut().insert(dir.to_path_buf(), kind);
+
+        kind
+    }
+
+    fn detect(&self, dir: &Path, vfs: &VfsRouter) -> FolderKind {
+        if let Some((_, kind)) = self.user_dirs.iter().find(|(user_dir, _)| user_dir == dir) {
+            return *kind;
+        }
+
+        if !vfs.is_local(dir) {
+            return if vfs.remote_roots().any(|root| root == dir) {
+                FolderKind::Remote
+            } else {
+                FolderKind::Plain
+            };
+        }
+
+        // Archives are mounted on top of local files but have no repositories or mounts
+        if vfs.archive(dir).is_some() {
+            return FolderKind::Plain;
+        }
+
+        if dir.join(".git").exists() {
+            return FolderKind::Repository;
+        }
+
+        if is_mount_point(dir) {
+            return FolderKind::MountPoint;
+        }
+
+        FolderKind::Plain
+    }
+}
+
+/// The folder is on another device than its parent.
+#[cfg(unix)]
+fn is_mount_point(dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(parent) = dir.parent() else {
+        return true;
+    };
+
+    match (dir.metadata(), parent.metadata()) {
+        (Ok(metadata), Ok(parent_metadata)) => metadata.dev() != parent_metadata.dev(),
+        _ => false,
+    }
+}
+
+/// Only drive roots such as `C:\` are recognized.
+#[cfg(not(unix))]
+fn is_mount_point(dir: &Path) -> bool {
+    dir.parent().is_none()
+}
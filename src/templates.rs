@@ -0,0 +1,38 @@
+use crate::error_manager::AppError;
+use crate::lang_string::LangKeys;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Directory whose files are offered by "New from template":
+/// `XDG_TEMPLATES_DIR` when it is set, `~/Templates` otherwise.
+pub fn templates_dir() -> Option<PathBuf> {
+    dirs::template_dir().or_else(|| dirs::home_dir().map(|home| home.join("Templates")))
+}
+
+/// Files (and folders) of the templates directory, sorted by name.
+/// Read every time the menu is opened, so new templates show up without a restart.
+pub fn templates() -> Result<Vec<PathBuf>, AppError> {
+    let Some(templates_dir) = templates_dir() else {
+        return Ok(Vec::new());
+    };
+
+    let mut templates: Vec<PathBuf> = match fs::read_dir(&templates_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|file_name| !file_name.to_string_lossy().starts_with('.'))
+            })
+            .collect(),
+        // Most users never created the directory
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            return Err(AppError::io(LangKeys::TemplatesError, &err).path(&templates_dir));
+        }
+    };
+    templates.sort();
+
+    Ok(templates)
+}
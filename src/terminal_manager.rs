@@ -0,0 +1,434 @@
+use crate::vfs::VfsRouter;
+use egui::{Context, Key, Modifiers};
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Lines kept above the screen, scrolled with the mouse wheel.
+const SCROLLBACK_LINES: usize = 5000;
+
+/// Shell running inside a pseudo terminal, its output feeds a vt100 screen.
+pub struct Terminal {
+    pub id: usize,
+
+    /// Screen content, updated by the thread reading the shell output.
+    pub parser: Arc<Mutex<vt100::Parser>>,
+
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+
+    /// Rows and columns of the screen.
+    size: (u16, u16),
+
+    /// Folder the shell was last sent to with cd.
+    directory: PathBuf,
+
+    /// Something was typed on the command line since the last Enter,
+    /// cd is not sent meanwhile so it is never mixed with the user input.
+    typing: bool,
+
+    pub exited: bool,
+}
+
+impl Terminal {
+    /// Starts the default shell of the user inside `directory`.
+    fn spawn(id: usize, ctx: &Context, directory: &Path) -> io::Result<Self> {
+        let size = (24, 80);
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows: size.0,
+                cols: size.1,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|err| io::Error::other(err.to_string()))?;
+
+        let mut command = CommandBuilder::new_default_prog();
+        command.cwd(directory);
+        command.env("TERM", "xterm-256color");
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|err| io::Error::other(err.to_string()))?;
+
+        // The reader only gets EOF once every handle to the slave side is closed
+        drop(pair.slave);
+
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|err| io::Error::other(err.to_string()))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|err| io::Error::other(err.to_string()))?;
+
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(
+            size.0,
+            size.1,
+            SCROLLBACK_LINES,
+        )));
+        let thread_parser = parser.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 8192];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(count) => {
+                        if let Ok(mut parser) = thread_parser.lock() {
+                            parser.process(&buffer[..count]);
+                        }
+                        ctx.request_repaint();
+                    }
+                }
+            }
+
+            // Shows that the shell exited
+            ctx.request_repaint();
+        });
+
+        Ok(Self {
+            id,
+            parser,
+            master: pair.master,
+            writer,
+            child,
+            size,
+            directory: directory.to_path_buf(),
+            typing: false,
+            exited: false,
+        })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        if self.exited {
+            return;
+        }
+
+        // Enter, Ctrl+C and Ctrl+U leave an empty command line
+        if !bytes.is_empty() {
+            self.typing = !matches!(bytes.last(), Some(b'\r' | b'\x03' | b'\x15'));
+        }
+
+        if let Err(err) = self
+            .writer
+            .write_all(bytes)
+            .and_then(|_| self.writer.flush())
+        {
+            eprintln!("[Terminal->write()->write_all] {}", err);
+        }
+    }
+
+    /// Fits the screen to the panel, the shell is told so it wraps lines at the new width.
+    pub fn resize(&mut self, rows: u16, cols: u16) {
+        if self.size == (rows, cols) {
+            return;
+        }
+        self.size = (rows, cols);
+
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.screen_mut().set_size(rows, cols);
+        }
+
+        let size = PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        if let Err(err) = self.master.resize(size) {
+            eprintln!("[Terminal->resize()->resize] {}", err);
+        }
+    }
+
+    /// Moves the view `lines` up into the scrollback, negative values move it down.
+    pub fn scroll(&mut self, lines: i32) {
+        if let Ok(mut parser) = self.parser.lock() {
+            let offset = parser.screen().scrollback() as i32 + lines;
+            parser.screen_mut().set_scrollback(offset.max(0) as usize);
+        }
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        if let Ok(mut parser) = self.parser.lock() {
+            parser.screen_mut().set_scrollback(0);
+        }
+    }
+
+    fn poll(&mut self) {
+        if !self.exited {
+            self.exited = matches!(self.child.try_wait(), Ok(Some(_)));
+        }
+    }
+
+    /// The shell waits at its prompt, no program runs in the foreground.
+    #[cfg(unix)]
+    fn is_idle(&self) -> bool {
+        match (self.master.process_group_leader(), self.child.process_id()) {
+            (Some(leader), Some(shell)) => leader as u32 == shell,
+            _ => false,
+        }
+    }
+
+    /// There is no foreground process group to look at, the shell is assumed to wait.
+    #[cfg(not(unix))]
+    fn is_idle(&self) -> bool {
+        true
+    }
+
+    /// Sends cd to the shell when it is at its prompt with nothing typed,
+    /// neither a running program nor a half typed command is written into.
+    fn change_directory(&mut self, directory: &Path) {
+        if self.exited || self.typing || self.directory == directory || !self.is_idle() {
+            return;
+        }
+
+        self.directory = directory.to_path_buf();
+        self.write(cd_command(directory).as_bytes());
+    }
+}
+
+/// Terminals of the bottom panel, each one in its own tab.
+pub struct TerminalManager {
+    pub visible: bool,
+    pub terminals: Vec<Terminal>,
+
+    /// Index of the tab shown, it follows the navigation.
+    pub active: usize,
+
+    /// Why the last terminal could not be started.
+    pub error: Option<String>,
+
+    next_id: usize,
+}
+
+impl TerminalManager {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            terminals: Vec::new(),
+            active: 0,
+            error: None,
+            next_id: 0,
+        }
+    }
+
+    /// Shows the panel, with a first terminal if there is none yet, or hides it.
+    pub fn toggle(&mut self, ctx: &Context, directory: &Path, vfs: &VfsRouter) {
+        self.visible = !self.visible;
+        if self.visible && self.terminals.is_empty() {
+            self.open_terminal(ctx, directory, vfs);
+        }
+    }
+
+    /// Starts a shell in a new tab. Archives and servers have no local folder,
+    /// the shell then starts in the home folder.
+    pub fn open_terminal(&mut self, ctx: &Context, directory: &Path, vfs: &VfsRouter) {
+        self.next_id += 1;
+
+        let directory = if vfs.is_local(directory) {
+            directory.to_path_buf()
+        } else {
+            dirs::home_dir().unwrap_or_default()
+        };
+        match Terminal::spawn(self.next_id, ctx, &directory) {
+            Ok(terminal) => {
+                self.terminals.push(terminal);
+                self.active = self.terminals.len() - 1;
+                self.error = None;
+            }
+            Err(err) => {
+                eprintln!(
+                    "[TerminalManager->open_terminal()->Terminal::spawn] {}",
+                    err
+                );
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+
+    /// Kills the shell, and the program running inside it if any.
+    pub fn close_terminal(&mut self, idx: usize) {
+        if idx >= self.terminals.len() {
+            return;
+        }
+
+        let mut terminal = self.terminals.remove(idx);
+        if !terminal.exited {
+            if let Err(err) = terminal.child.kill() {
+                eprintln!("[TerminalManager->close_terminal()->kill] {}", err);
+            }
+
+            // Reaped once it is gone, it would stay a zombie until Rocket quits otherwise
+            let mut child = terminal.child;
+            thread::spawn(move || child.wait());
+        }
+
+        if self.active >= idx && self.active > 0 {
+            self.active -= 1;
+        }
+        if self.terminals.is_empty() {
+            self.visible = false;
+        }
+    }
+
+    /// Types `command` in the terminal shown and runs it. A new terminal is opened
+    /// when there is none or a program already runs in the one shown.
+    pub fn run_command(&mut self, ctx: &Context, command: &str, directory: &Path, vfs: &VfsRouter) {
+        self.visible = true;
+
+        let busy = self
+            .terminals
+            .get(self.active)
+            .is_none_or(|terminal| terminal.exited || !terminal.is_idle());
+        if busy {
+            self.open_terminal(ctx, directory, vfs);
+            // The error is shown in the panel, the command is not typed in a busy terminal
+            if self.error.is_some() {
+                return;
+            }
+        }
+
+        if let Some(terminal) = self.active_terminal() {
+            terminal.write(format!("{}\r", command).as_bytes());
+        }
+    }
+
+    pub fn active_terminal(&mut self) -> Option<&mut Terminal> {
+        self.terminals.get_mut(self.active)
+    }
+
+    /// Sends the terminal shown to `directory`, called every frame while the panel is visible.
+    pub fn follow(&mut self, directory: &Path, vfs: &VfsRouter) {
+        for terminal in &mut self.terminals {
+            terminal.poll();
+        }
+
+        if !vfs.is_local(directory) {
+            return;
+        }
+
+        if let Some(terminal) = self.active_terminal() {
+            terminal.change_directory(directory);
+        }
+    }
+
+    /// Types the quoted `paths` on the command line of the terminal shown.
+    pub fn insert_paths(&mut self, paths: &[PathBuf]) {
+        let mut text: String = paths
+            .iter()
+            .map(|path| shell_quote(path))
+            .collect::<Vec<String>>()
+            .join(" ");
+        if text.is_empty() {
+            return;
+        }
+        text.push(' ');
+
+        if let Some(terminal) = self.active_terminal() {
+            terminal.write(text.as_bytes());
+        }
+    }
+}
+
+/// Quotes `path` so the shell reads it as a single word.
+#[cfg(unix)]
+pub fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+#[cfg(not(unix))]
+pub fn shell_quote(path: &Path) -> String {
+    format!("\"{}\"", path.to_string_lossy())
+}
+
+/// The leading space keeps the command out of the shell history.
+#[cfg(unix)]
+fn cd_command(directory: &Path) -> String {
+    format!(" cd -- {}\r", shell_quote(directory))
+}
+
+#[cfg(not(unix))]
+fn cd_command(directory: &Path) -> String {
+    format!("cd /d {}\r", shell_quote(directory))
+}
+
+/// Bytes a terminal sends for a key which does not type text.
+/// `application_cursor` is the arrow key mode some programs switch to, e.g. vim or less.
+pub fn key_bytes(key: Key, modifiers: Modifiers, application_cursor: bool) -> Option<Vec<u8>> {
+    let arrow = |letter: char| {
+        let prefix = if application_cursor { "\x1bO" } else { "\x1b[" };
+        Some(format!("{}{}", prefix, letter).into_bytes())
+    };
+
+    if modifiers.ctrl && !modifiers.alt {
+        let name = key.name();
+        if let [letter @ b'A'..=b'Z'] = name.as_bytes() {
+            return Some(vec![letter - b'A' + 1]);
+        }
+    }
+
+    let sequence = match key {
+        Key::Enter => "\r",
+        Key::Backspace => "\x7f",
+        Key::Tab if modifiers.shift => "\x1b[Z",
+        Key::Tab => "\t",
+        Key::Escape => "\x1b",
+        Key::ArrowUp => return arrow('A'),
+        Key::ArrowDown => return arrow('B'),
+        Key::ArrowRight => return arrow('C'),
+        Key::ArrowLeft => return arrow('D'),
+        Key::Home => return arrow('H'),
+        Key::End => return arrow('F'),
+        Key::Insert => "\x1b[2~",
+        Key::Delete => "\x1b[3~",
+        Key::PageUp => "\x1b[5~",
+        Key::PageDown => "\x1b[6~",
+        Key::F1 => "\x1bOP",
+        Key::F2 => "\x1bOQ",
+        Key::F3 => "\x1bOR",
+        Key::F4 => "\x1bOS",
+        Key::F5 => "\x1b[15~",
+        Key::F6 => "\x1b[17~",
+        Key::F7 => "\x1b[18~",
+        Key::F8 => "\x1b[19~",
+        Key::F9 => "\x1b[20~",
+        Key::F10 => "\x1b[21~",
+        Key::F11 => "\x1b[23~",
+        Key::F12 => "\x1b[24~",
+        _ => return None,
+    };
+
+    Some(sequence.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn quotes_paths() {
+        assert_eq!(shell_quote(Path::new("/tmp/a b")), "'/tmp/a b'");
+        assert_eq!(shell_quote(Path::new("/tmp/it's")), "'/tmp/it'\\''s'");
+    }
+
+    #[test]
+    fn translates_keys() {
+        assert_eq!(key_bytes(Key::C, Modifiers::CTRL, false), Some(vec![3]));
+        assert_eq!(
+            key_bytes(Key::ArrowUp, Modifiers::NONE, false),
+            Some(b"\x1b[A".to_vec())
+        );
+        assert_eq!(
+            key_bytes(Key::ArrowUp, Modifiers::NONE, true),
+            Some(b"\x1bOA".to_vec())
+        );
+        assert_eq!(key_bytes(Key::C, Modifiers::NONE, false), None);
+    }
+}
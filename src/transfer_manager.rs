@@ -0,0 +1,317 @@
+use crate::progress::ProgressSender;
+use crate::vfs::local_vfs::LocalVfs;
+use crate::vfs::{Vfs, VfsRouter};
+use egui::Modifiers;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Files are copied in chunks of this size, the copy can be paused or cancelled between two.
+const COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TransferMode {
+    Copy,
+    Move,
+
+    /// Creates symbolic links pointing to the files.
+    Link,
+}
+
+impl TransferMode {
+    /// Mode of a drag and drop: Ctrl copies, Shift+Ctrl links, otherwise the files are moved.
+    pub fn from_modifiers(modifiers: Modifiers) -> Self {
+        if modifiers.command && modifiers.shift {
+            TransferMode::Link
+        } else if modifiers.command {
+            TransferMode::Copy
+        } else {
+            TransferMode::Move
+        }
+    }
+}
+
+/// Size of `path`, directories included recursively.
+fn total_size(path: &Path) -> u64 {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return 0;
+    };
+
+    // Links are recreated, nothing is read through them
+    if metadata.is_symlink() {
+        return 0;
+    }
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| total_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// `original` may be relative to the folder of `link`.
+#[cfg(unix)]
+pub fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+pub fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    // Joining an absolute path keeps it unchanged
+    let target = link
+        .parent()
+        .map_or_else(|| original.to_path_buf(), |parent| parent.join(original));
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+/// Copies `reader` into `writer`, reporting every chunk as progress of `file`.
+fn copy_chunks(
+    reader: &mut dyn Read,
+    writer: &mut dyn Write,
+    file: &Path,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    let mut buffer = vec![0; COPY_CHUNK_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        writer.write_all(&buffer[..read])?;
+        progress.advance(read as u64, file)?;
+    }
+
+    writer.flush()
+}
+
+/// Copies the content of `from` into `to`, reporting every chunk.
+fn copy_file(from: &Path, to: &Path, progress: &ProgressSender) -> io::Result<()> {
+    copy_chunks(
+        &mut File::open(from)?,
+        &mut File::create(to)?,
+        from,
+        progress,
+    )
+}
+
+fn copy_recursive(from: &Path, to: &Path, progress: &ProgressSender) -> io::Result<()> {
+    let metadata = from.symlink_metadata()?;
+
+    // The copy points to the same target, a link to a parent folder would loop otherwise
+    if metadata.is_symlink() {
+        return symlink(&fs::read_link(from)?, to);
+    }
+
+    if metadata.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()), progress)?;
+        }
+
+        return Ok(());
+    }
+
+    // A cancelled copy does not leave half of the file behind
+    if let Err(err) = copy_file(from, to, progress) {
+        let _ = fs::remove_file(to);
+        return Err(err);
+    }
+    fs::set_permissions(to, metadata.permissions())?;
+
+    Ok(())
+}
+
+/// A folder can not be copied or moved inside itself.
+fn check_destination(file: &Path, destination: &Path) -> io::Result<()> {
+    if destination.starts_with(file) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot put {:?} inside itself", file),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Copies, moves or links `files` inside the `destination` folder, meant to run on a background thread.
+pub fn transfer(
+    files: &[PathBuf],
+    destination: &Path,
+    mode: TransferMode,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    // Links do not copy any byte
+    let total = match mode {
+        TransferMode::Link => 0,
+        _ => files.iter().map(|file| total_size(file)).sum(),
+    };
+    progress.total(total);
+
+    for file in files {
+        let Some(file_name) = file.file_name() else {
+            continue;
+        };
+
+        check_destination(file, destination)?;
+
+        // Moving a file where it already is does nothing
+        if mode == TransferMode::Move && file.parent() == Some(destination) {
+            continue;
+        }
+
+        let target = LocalVfs.unused_path(destination, &file_name.to_string_lossy());
+
+        if mode == TransferMode::Link {
+            symlink(file, &target)?;
+            progress.advance(0, file)?;
+            continue;
+        }
+
+        // A rename is instant on the same file system, fall back to copying otherwise
+        if mode == TransferMode::Move && fs::rename(file, &target).is_ok() {
+            progress.advance(total_size(&target), file)?;
+            continue;
+        }
+
+        copy_recursive(file, &target, progress)?;
+
+        if mode == TransferMode::Move {
+            if file.symlink_metadata()?.is_dir() {
+                fs::remove_dir_all(file)?;
+            } else {
+                fs::remove_file(file)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Size of `path` on any backend, directories included recursively.
+fn vfs_total_size(vfs: &VfsRouter, path: &Path) -> u64 {
+    // Links are recreated, nothing is read through them
+    if vfs.read_link(path).is_some() {
+        return 0;
+    }
+
+    let Ok(metadata) = vfs.stat(path) else {
+        return 0;
+    };
+
+    if !metadata.is_dir {
+        return metadata.len;
+    }
+
+    vfs.list(path)
+        .map(|children| {
+            children
+                .iter()
+                .map(|child| vfs_total_size(vfs, child))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn vfs_copy_recursive(
+    vfs: &VfsRouter,
+    from: &Path,
+    to: &Path,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    // The copy points to the same target, a link to a parent folder would loop otherwise
+    if let Some(target) = vfs.read_link(from) {
+        return vfs.symlink(&target, to);
+    }
+
+    if vfs.is_dir(from) {
+        vfs.create_dir(to)?;
+        for child in vfs.list(from)? {
+            if let Some(file_name) = child.file_name() {
+                vfs_copy_recursive(vfs, &child, &to.join(file_name), progress)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    let result = vfs
+        .open(from)
+        .and_then(|mut reader| copy_chunks(&mut reader, &mut vfs.create(to)?, from, progress));
+
+    // A cancelled copy does not leave half of the file behind
+    if result.is_err() {
+        let _ = vfs.remove(to);
+    }
+
+    result
+}
+
+/// Like `transfer`, for files or a destination which are not on the local disk (e.g. on a server).
+/// `vfs` is owned by the background thread, with its own connections.
+pub fn transfer_through_vfs(
+    vfs: &VfsRouter,
+    files: &[PathBuf],
+    destination: &Path,
+    mode: TransferMode,
+    progress: &ProgressSender,
+) -> io::Result<()> {
+    if mode == TransferMode::Link {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "links can only be created on the local disk",
+        ));
+    }
+
+    let total = files.iter().map(|file| vfs_total_size(vfs, file)).sum();
+    progress.total(total);
+
+    for file in files {
+        let Some(file_name) = file.file_name() else {
+            continue;
+        };
+
+        check_destination(file, destination)?;
+
+        // Moving a file where it already is does nothing
+        if mode == TransferMode::Move && file.parent() == Some(destination) {
+            continue;
+        }
+
+        let target = vfs.unused_path(destination, &file_name.to_string_lossy());
+
+        // A rename is instant when both sides are on the same backend, fall back to copying otherwise
+        if mode == TransferMode::Move
+            && vfs.same_backend(file, destination)
+            && vfs.rename(file, &target).is_ok()
+        {
+            progress.advance(vfs_total_size(vfs, &target), file)?;
+            continue;
+        }
+
+        vfs_copy_recursive(vfs, file, &target, progress)?;
+
+        if mode == TransferMode::Move {
+            vfs.remove(file)?;
+        }
+    }
+
+    Ok(())
+}
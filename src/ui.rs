@@ -1,6 +1,15 @@
 pub mod additional_info_panel;
 pub mod central_panel;
+pub mod command_palette_window;
 pub mod display_path_panel;
+pub mod drop_target;
+pub mod file_icon;
 pub mod file_widget;
+pub mod host_key_window;
+pub mod jobs_panel;
+pub mod location_finder_window;
 pub mod navigation_bar_panel;
 pub mod quick_access_panel;
+pub mod select_pattern_window;
+pub mod settings_window;
+pub mod terminal_panel;
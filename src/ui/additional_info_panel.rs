@@ -1,16 +1,38 @@
+use crate::actions::git_action::FileDiff;
 use crate::actions::select_action::SelectAction;
+use crate::config_manager::{Config, DEFAULT_DATE_FORMAT, SizeUnits};
 use crate::icons_manager::IconsManager;
 use crate::lang_string::{LangKeys, LangString};
+use crate::mime_manager::MimeManager;
+use crate::ui::file_icon::file_icon;
+use crate::vfs::{Vfs, VfsRouter};
 use chrono::{DateTime, Local};
 use egui::{Ui, Vec2};
+use std::fmt::Write;
 use std::path::Path;
+use std::time::SystemTime;
 
 pub fn show(
     ui: &mut Ui,
     lang_string: &LangString,
+    config: &Config,
     select_action: &SelectAction,
+    file_diff: &mut Option<FileDiff>,
+    vfs: &VfsRouter,
     icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
 ) {
+    // The diff replaces the details of its file until it is closed or another file is selected
+    if let Some(diff) = file_diff {
+        if select_action.files.len() == 1 && select_action.is_file_selected(&diff.file) {
+            if !diff.render(ui, lang_string) {
+                *file_diff = None;
+            }
+            return;
+        }
+        *file_diff = None;
+    }
+
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.vertical_centered(|ui| {
             if select_action.files.is_empty() {
@@ -18,8 +40,23 @@ pub fn show(
                 return;
             }
 
+            if select_action.files.len() > 1 {
+                ui.heading(lang_string.format(
+                    LangKeys::FilesSelected,
+                    &[("count", select_action.files.len().into())],
+                ));
+            }
+
             for file in &select_action.files {
-                generate_file_info_group(ui, lang_string, file, icons_manager);
+                generate_file_info_group(
+                    ui,
+                    lang_string,
+                    config,
+                    file,
+                    vfs,
+                    icons_manager,
+                    mime_manager,
+                );
             }
         })
     });
@@ -28,77 +65,123 @@ pub fn show(
 fn generate_file_info_group(
     ui: &mut Ui,
     lang_string: &LangString,
+    config: &Config,
     file: &Path,
+    vfs: &VfsRouter,
     icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
 ) {
-    if let Ok(metadata) = file.metadata() {
+    if let Ok(metadata) = vfs.stat(file) {
         if let Some(file_name) = file.file_name()
             && let Some(file_name) = file_name.to_str()
         {
+            let mime_type = mime_manager.mime_type(file, vfs);
+
             ui.group(|ui| {
-                ui.add(
-                    icons_manager
-                        .get_icon(&file.into())
-                        .clone()
-                        .fit_to_exact_size(Vec2::new(128.0, 128.0)),
+                file_icon(
+                    ui,
+                    vfs,
+                    icons_manager,
+                    mime_manager,
+                    file,
+                    Vec2::new(128.0, 128.0),
                 );
                 ui.heading(file_name);
 
+                if let Some(target) = vfs.read_link(file) {
+                    ui.label(lang_string.format(
+                        LangKeys::PointsTo,
+                        &[("target", target.to_string_lossy().into_owned().into())],
+                    ));
+                }
+
                 ui.group(|ui| {
-                    if let Some(extension) = file.extension()
-                        && let Some(extension) = extension.to_str()
-                        && metadata.is_file()
-                    {
-                        ui.label(lang_string.get(LangKeys::Extension) + extension);
+                    if !metadata.is_dir {
+                        ui.label(lang_string.format(
+                            LangKeys::FileType,
+                            &[("mime_type", mime_type.as_str().into())],
+                        ));
                     }
 
-                    let bytes = metadata.len();
-                    if metadata.is_file() {
-                        ui.label(convert_bytes_size_to_human(bytes));
+                    let bytes = metadata.len;
+                    if !metadata.is_dir {
+                        ui.label(convert_bytes_size_to_human(bytes, config.size_units));
                     }
 
-                    const DATE_TIME_FORMAT: &str = "%d/%m/%Y %T";
-                    let mut date_time: DateTime<Local>;
+                    let date_format = config.date_format.as_str();
 
-                    if let Ok(time) = metadata.created() {
-                        date_time = time.into();
+                    if let Some(time) = metadata.created {
                         ui.label(
-                            lang_string.get(LangKeys::CreatedAt)
-                                + date_time.format(DATE_TIME_FORMAT).to_string().as_str(),
+                            lang_string.format(
+                                LangKeys::CreatedAt,
+                                &[("date", format_date_time(time, date_format).into())],
+                            ),
                         );
                     }
-                    if let Ok(time) = metadata.accessed() {
-                        date_time = time.into();
+                    if let Some(time) = metadata.accessed {
                         ui.label(
-                            lang_string.get(LangKeys::AccessedAt)
-                                + date_time.format(DATE_TIME_FORMAT).to_string().as_str(),
+                            lang_string.format(
+                                LangKeys::AccessedAt,
+                                &[("date", format_date_time(time, date_format).into())],
+                            ),
                         );
                     }
-                    if let Ok(time) = metadata.modified() {
-                        date_time = time.into();
+                    if let Some(time) = metadata.modified {
                         ui.label(
-                            lang_string.get(LangKeys::ModifiedAt)
-                                + date_time.format(DATE_TIME_FORMAT).to_string().as_str(),
+                            lang_string.format(
+                                LangKeys::ModifiedAt,
+                                &[("date", format_date_time(time, date_format).into())],
+                            ),
                         );
                     }
                 });
             });
         }
+    } else if let Some(target) = vfs.read_link(file) {
+        // A broken link has nothing to stat, only its target is known
+        ui.group(|ui| {
+            ui.heading(file.file_name().unwrap_or_default().to_string_lossy());
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                lang_string.format(
+                    LangKeys::BrokenLink,
+                    &[("target", target.to_string_lossy().into_owned().into())],
+                ),
+            );
+        });
     }
 }
 
-pub fn convert_bytes_size_to_human(bytes: u64) -> String {
-    const B: u64 = 1;
-    const KIB: u64 = 1024 * B;
-    const MIB: u64 = KIB * KIB;
-    const GIB: u64 = MIB * KIB;
-    const TIB: u64 = GIB * KIB;
-
-    match bytes {
-        b if b < KIB => format!("{:.2} B", bytes),
-        b if b < MIB => format!("{:.2} KiB", bytes / KIB),
-        b if b < GIB => format!("{:.2} MiB", bytes / MIB),
-        b if b < TIB => format!("{:.2} GiB", bytes / GIB),
-        _ => format!("{:.2} TiB", bytes / TIB),
+/// Formats `time` with a user provided strftime format, or the default one if it is invalid.
+pub fn format_date_time(time: SystemTime, date_format: &str) -> String {
+    let date_time: DateTime<Local> = time.into();
+
+    // Unlike to_string(), write! reports invalid formats instead of panicking
+    let mut formatted = String::new();
+    if write!(formatted, "{}", date_time.format(date_format)).is_err() {
+        formatted.clear();
+        let _ = write!(formatted, "{}", date_time.format(DEFAULT_DATE_FORMAT));
+    }
+
+    formatted
+}
+
+pub fn convert_bytes_size_to_human(bytes: u64, size_units: SizeUnits) -> String {
+    let (unit, suffixes) = match size_units {
+        SizeUnits::Binary => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB"]),
+        SizeUnits::Decimal => (1000.0, ["B", "KB", "MB", "GB", "TB"]),
+    };
+
+    let mut size = bytes as f64;
+    let mut suffix_idx = 0;
+    while size >= unit && suffix_idx + 1 < suffixes.len() {
+        size /= unit;
+        suffix_idx += 1;
+    }
+
+    if suffix_idx == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", size, suffixes[suffix_idx])
     }
 }
@@ -1,23 +1,50 @@
 use crate::actions::Actions;
-use crate::actions::select_action::{SelectionMode, SelectionResult};
+use crate::actions::archive_action::ArchiveEntriesPayload;
+use crate::actions::file_operation::FileOperation;
+use crate::actions::select_action::SelectionMode;
+use crate::actions::transfer_action::FilesPayload;
+use crate::applications_manager::ApplicationsManager;
+use crate::archive_manager;
+use crate::archive_manager::ArchiveKind;
+use crate::config_manager::Config;
+use crate::git_manager::{GitManager, GitStatus};
 use crate::icons_manager::IconsManager;
 use crate::lang_string::{LangKeys, LangString};
+use crate::mime_manager::MimeManager;
 use crate::path_manager::PathManager;
-use crate::ui::file_widget::file_widget;
+use crate::templates;
+use crate::transfer_manager::TransferMode;
+use crate::ui::drop_target::drop_target_ui;
+use crate::ui::file_icon::{file_icon, git_emblem};
+use crate::ui::file_widget::{file_widget, git_badges_ui};
+use crate::user_actions;
+use crate::vfs::sftp_vfs::UnknownHostKey;
+use crate::vfs::{Vfs, VfsRouter};
+use crate::view_settings::ViewMode;
 use eframe::egui;
-use egui::{Align, CursorIcon, Key, Pos2, Rect, Ui, Vec2};
+use egui::{
+    Align, Align2, Button, CursorIcon, Id, Key, LayerId, Modifiers, Order, Pos2, Rect, Sense,
+    StrokeKind, TextEdit, TextStyle, Ui, Vec2,
+};
+use egui::text::{CCursor, CCursorRange};
+use egui::text_edit::TextEditState;
 use egui::{Response, ScrollArea};
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, Ordering};
-use crate::error_modal::ErrorModal;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::error_manager::{AppError, ErrorManager, Retry};
 
 pub fn show(
     ui: &mut Ui,
     lang_string: &LangString,
-    general_error_modal: &mut ErrorModal,
+    config: &Config,
+    error_manager: &mut ErrorManager,
     path_manager: &mut PathManager,
     actions: &mut Actions,
     icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
 ) {
     if path_manager.update_folder_content {
         // Do we need to 'waste' this frame to update the cursor?
@@ -31,12 +58,30 @@ pub fn show(
 
         // todo
         if let Err(err) = path_manager.fill_directory_content() {
-            general_error_modal.set_title_and_caption(&"Fill Directory Content Error".to_string(), &err.to_string());
-            general_error_modal.set_visible(true);
+            if err.kind() == io::ErrorKind::WouldBlock {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(lang_string.get(LangKeys::Connecting));
+                });
+                // Nothing else wakes up the UI once the connection is open
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+
+            // The user is asked to trust the server instead
+            if UnknownHostKey::from_error(&err).is_none() {
+                error_manager.report(
+                    AppError::io(LangKeys::ReadFolderError, &err)
+                        .path(&path_manager.current_path)
+                        .retry(Retry::ReloadFolder),
+                );
+            }
             return;
         }
     }
 
+    external_drop_ui(ui, lang_string, path_manager, actions);
+
     if path_manager.deleted_folder {
         ui.label(lang_string.get(LangKeys::DeletedFolder));
         return;
@@ -44,99 +89,255 @@ pub fn show(
 
     if path_manager.directory_content.is_empty() {
         ui.label(lang_string.get(LangKeys::EmptyFolder));
+
+        let background = ui.interact(
+            ui.available_rect_before_wrap(),
+            ui.id().with("central_panel_background"),
+            Sense::click(),
+        );
+        background_context_menu(
+            &background,
+            lang_string,
+            error_manager,
+            path_manager,
+            actions,
+        );
         return;
     }
 
     if let Some(new_current_path) = directory_builder(
         ui,
         lang_string,
-        general_error_modal,
-        &path_manager.directory_content,
+        config,
+        error_manager,
+        path_manager,
         actions,
-        &icons_manager,
+        icons_manager,
+        mime_manager,
+        git_manager,
+        applications_manager,
     ) {
         path_manager.update_current_directory(&new_current_path);
         actions.select_action.clear_selection();
     }
 }
 
+/// Files dropped from other applications are copied inside the current directory.
+fn external_drop_ui(
+    ui: &Ui,
+    lang_string: &LangString,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+) {
+    // Archives are read-only
+    if path_manager.deleted_folder || path_manager.vfs.archive(&path_manager.current_path).is_some()
+    {
+        return;
+    }
+
+    let (hovering, dropped_files) = ui.input(|input_state| {
+        (
+            !input_state.raw.hovered_files.is_empty(),
+            input_state
+                .raw
+                .dropped_files
+                .iter()
+                .filter_map(|dropped_file| dropped_file.path.clone())
+                .collect::<Vec<PathBuf>>(),
+        )
+    });
+
+    if hovering {
+        let painter = ui.ctx().layer_painter(LayerId::new(
+            Order::Foreground,
+            Id::new("external_drop"),
+        ));
+        let rect = ui.max_rect();
+        painter.rect(
+            rect,
+            4.0,
+            ui.visuals().selection.bg_fill.gamma_multiply(0.2),
+            ui.visuals().selection.stroke,
+            StrokeKind::Inside,
+        );
+        painter.text(
+            rect.center(),
+            Align2::CENTER_CENTER,
+            lang_string.get(LangKeys::DropFilesHere),
+            TextStyle::Heading.resolve(ui.style()),
+            ui.visuals().strong_text_color(),
+        );
+    }
+
+    if !dropped_files.is_empty() {
+        actions.drop_files(
+            dropped_files,
+            &path_manager.current_path,
+            TransferMode::Copy,
+            false,
+        );
+    }
+}
+
 fn directory_builder(
     ui: &mut Ui,
     lang_string: &LangString,
-    general_error_modal: &mut ErrorModal,
-    directory_content: &[PathBuf],
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
     actions: &mut Actions,
     icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
 ) -> Option<PathBuf> {
-    let total_widgets = directory_content.len();
-    let widget_row_height = ui.spacing().interact_size.y * 1.65;
-
-    // todo: this is kind of ugly
-    static SCROLL_OFFSET_Y: AtomicU32 = AtomicU32::new(0);
-
-    // Update selection mode
-    ui.ctx().input(|input_state| {
-        // On Windows and Linux, set this to the same value as ctrl.
-        // On Mac, this should be set whenever one of the ⌘ Command keys is down (same as mac_cmd)
-        let new_mode = match (input_state.modifiers.shift, input_state.modifiers.command) {
-            (true, _) => SelectionMode::Ranged,
-            (false, true) => SelectionMode::Multiple,
-            (false, false) => SelectionMode::Single,
-        };
+    let spacing = ui.spacing().item_spacing;
+    let grid_view = path_manager.view_settings.view_mode == ViewMode::Grid;
 
-        actions.select_action.mode = new_mode;
+    let (columns, row_height) = if grid_view {
+        let columns = ((ui.available_width() + spacing.x) / (GRID_CELL_WIDTH + spacing.x))
+            .floor()
+            .max(1.0) as usize;
+        (columns, GRID_CELL_HEIGHT)
+    } else {
+        (1, config.row_height)
+    };
+    let visible_rows = ((ui.available_height() + spacing.y) / (row_height + spacing.y))
+        .floor()
+        .max(1.0) as usize;
 
-        // This check prevents skipping file_widgets since multiple frames can be run,
-        // even if the key_press is really short
-        if actions.select_action.key_select.was_pressed {
-            if input_state.key_down(actions.select_action.key_select.key) {
-                return;
-            }
+    // Which directory does the user want to go to?
+    let keyboard_path = keyboard_navigation(
+        ui,
+        lang_string,
+        error_manager,
+        path_manager,
+        actions,
+        columns,
+        visible_rows * columns,
+    );
 
-            actions.select_action.key_select.was_pressed = false;
-        }
+    // Registered before the rows, so it only gets the clicks and drags in the empty space
+    let background = ui.interact(
+        ui.available_rect_before_wrap(),
+        ui.id().with("central_panel_background"),
+        Sense::click_and_drag(),
+    );
+    rubber_band_input(ui, &background, actions);
+    background_context_menu(
+        &background,
+        lang_string,
+        error_manager,
+        path_manager,
+        actions,
+    );
 
-        // Select a file_widget by key press (A-Z)
-        let key_a_u8 = Key::A as u8;
-        let key_z_u8 = Key::Z as u8;
-        for key in input_state.keys_down.iter() {
-            let key_u8 = *key as u8;
+    let mouse_path = if grid_view {
+        grid_builder(
+            ui,
+            lang_string,
+            config,
+            error_manager,
+            path_manager,
+            actions,
+            icons_manager,
+            mime_manager,
+            git_manager,
+            applications_manager,
+            columns,
+        )
+    } else {
+        list_builder(
+            ui,
+            lang_string,
+            config,
+            error_manager,
+            path_manager,
+            actions,
+            icons_manager,
+            mime_manager,
+            git_manager,
+            applications_manager,
+            row_height,
+        )
+    };
 
-            if key_a_u8 <= key_u8 && key_u8 <= key_z_u8 {
-                // IMPORTANT: egui Key::A (46) to ASCII 'A' (65) requires an offset of 19.
-                // This is fragile if the egui Key enum order changes.
-                let key_enum_to_ascii_offset = 19;
-                let key_uppercase_char = (key_enum_to_ascii_offset + key_u8) as char;
+    if let Some(rubber_band) = &actions.select_action.rubber_band {
+        let visuals = &ui.visuals().selection;
+        ui.painter().rect(
+            rubber_band.rect,
+            0.0,
+            visuals.bg_fill.gamma_multiply(0.3),
+            visuals.stroke,
+            StrokeKind::Inside,
+        );
+    }
 
-                let start_idx = directory_content
-                    .iter()
-                    .enumerate()
-                    .rev()
-                    .find(|(_, f)| actions.select_action.files.contains(*f))
-                    .map(|(idx, _)| idx + 1)
-                    .unwrap_or(0);
-
-                for idx in start_idx..directory_content.len() {
-                    let file = &directory_content[idx];
-                    let file_stem = file.file_stem().and_then(|stem| stem.to_str());
-                    if let Some(file_stem) = file_stem {
-                        if file_stem.to_uppercase().starts_with(key_uppercase_char) {
-                            actions.select_action.select_file_by_key(file, *key);
-                            break;
-                        }
-                    }
-                }
+    mouse_path.or(keyboard_path)
+}
 
-                break;
-            }
-        }
-    });
+/// Dragging in the empty space selects every file the rectangle touches, Ctrl adds to the selection.
+/// Clicking the empty space clears the selection.
+fn rubber_band_input(ui: &Ui, background: &Response, actions: &mut Actions) {
+    let modifiers = ui.input(|input_state| input_state.modifiers);
+    let select_action = &mut actions.select_action;
+
+    if background.clicked() && !modifiers.command {
+        select_action.clear_selection();
+    }
+
+    if background.drag_started()
+        && let Some(origin) = ui.input(|input_state| input_state.pointer.press_origin())
+    {
+        select_action.start_rubber_band(origin, modifiers.command);
+    }
+
+    if background.dragged()
+        && let Some(pointer) = ui.input(|input_state| input_state.pointer.interact_pos())
+    {
+        select_action.update_rubber_band(pointer);
+    }
+
+    if background.drag_stopped() {
+        select_action.stop_rubber_band();
+    }
+}
+
+fn list_builder(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+    icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
+    row_height: f32,
+) -> Option<PathBuf> {
+    let directory_content = &path_manager.directory_content;
+    let total_widgets = directory_content.len();
 
     // Which directory does the user want to go to?
     let mut new_current_path = None;
 
-    let scroll_offset_y = ScrollArea::both()
-        .show_rows(ui, widget_row_height, total_widgets, |ui, row_range| {
+    ScrollArea::both()
+        .drag_to_scroll(false)
+        .show_rows(ui, row_height, total_widgets, |ui, row_range| {
+            scroll_to_cursor(ui, path_manager, actions, row_range.start, 1, row_height);
+
+            // show_rows lays out the ui from the first visible row
+            let row_height = row_height + ui.spacing().item_spacing.y;
+            let content_min =
+                ui.max_rect().min - Vec2::new(0.0, row_range.start as f32 * row_height);
+            actions.select_action.select_rubber_band(
+                directory_content,
+                content_min,
+                Vec2::new(f32::INFINITY, row_height),
+                1,
+            );
+
             // For directory_content[row_range] represents the viewable entries
             for entry in directory_content[row_range].iter() {
                 let new_possible_path = entry
@@ -145,12 +346,18 @@ fn directory_builder(
                     .and_then(|file_name| {
                         file_row_ui(
                             ui,
-                            general_error_modal,
-                            directory_content,
+                            lang_string,
+                            config,
+                            error_manager,
+                            path_manager,
                             entry,
                             &file_name.into(),
+                            row_height,
                             actions,
                             icons_manager,
+                            mime_manager,
+                            git_manager,
+                            applications_manager,
                         )
                     });
 
@@ -158,96 +365,569 @@ fn directory_builder(
                     new_current_path = new_possible_path;
                 }
             }
+        });
 
-            if actions.select_action.key_select.scroll_to_widget
-                && let SelectionResult::Single(path) =
-                    actions.select_action.get_selected_files(lang_string)
-            {
-                let entry = directory_content
-                    .iter()
-                    .enumerate()
-                    .find(|(_, entry)| entry.as_path() == path.as_path());
-                if let Some((idx, _)) = entry {
-                    let bits = SCROLL_OFFSET_Y.load(Ordering::Relaxed);
-                    scroll_to_file_widget(ui, idx + 1, widget_row_height, f32::from_bits(bits));
-                    actions.select_action.key_select.scroll_to_widget = false;
-                }
-            }
-        })
-        .state
-        .offset
-        .y;
+    new_current_path
+}
+
+/// Size of a file in the grid view.
+const GRID_CELL_WIDTH: f32 = 110.0;
+const GRID_CELL_HEIGHT: f32 = 110.0;
+
+fn grid_builder(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+    icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
+    columns: usize,
+) -> Option<PathBuf> {
+    let directory_content = &path_manager.directory_content;
+    let total_rows = directory_content.len().div_ceil(columns);
+
+    // Which directory does the user want to go to?
+    let mut new_current_path = None;
+
+    ScrollArea::vertical()
+        .drag_to_scroll(false)
+        .show_rows(ui, GRID_CELL_HEIGHT, total_rows, |ui, row_range| {
+            scroll_to_cursor(
+                ui,
+                path_manager,
+                actions,
+                row_range.start,
+                columns,
+                GRID_CELL_HEIGHT,
+            );
 
-    SCROLL_OFFSET_Y.store(scroll_offset_y.to_bits(), Ordering::Relaxed);
+            // show_rows lays out the ui from the first visible row
+            let cell_size =
+                Vec2::new(GRID_CELL_WIDTH, GRID_CELL_HEIGHT) + ui.spacing().item_spacing;
+            let content_min =
+                ui.max_rect().min - Vec2::new(0.0, row_range.start as f32 * cell_size.y);
+            actions.select_action.select_rubber_band(
+                directory_content,
+                content_min,
+                cell_size,
+                columns,
+            );
+
+            for row in row_range {
+                let start = row * columns;
+                let end = (start + columns).min(directory_content.len());
+
+                ui.horizontal(|ui| {
+                    for entry in &directory_content[start..end] {
+                        let new_possible_path = entry
+                            .file_name()
+                            .and_then(|file_name| file_name.to_str())
+                            .and_then(|file_name| {
+                                file_cell_ui(
+                                    ui,
+                                    lang_string,
+                                    config,
+                                    error_manager,
+                                    path_manager,
+                                    entry,
+                                    &file_name.into(),
+                                    actions,
+                                    icons_manager,
+                                    mime_manager,
+                                    git_manager,
+                                    applications_manager,
+                                )
+                            });
+
+                        if new_possible_path.is_some() {
+                            new_current_path = new_possible_path;
+                        }
+                    }
+                });
+            }
+        });
 
     new_current_path
 }
 
+/// Scrolls the cursor into view after it was moved with the keyboard.
+/// Must be called inside show_rows, before the rows are added.
+fn scroll_to_cursor(
+    ui: &mut Ui,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+    first_visible_row: usize,
+    columns: usize,
+    row_height: f32,
+) {
+    if !actions.select_action.key_select.scroll_to_widget {
+        return;
+    }
+    actions.select_action.key_select.scroll_to_widget = false;
+
+    let cursor_idx = actions.select_action.cursor.as_ref().and_then(|cursor| {
+        path_manager
+            .directory_content
+            .iter()
+            .position(|entry| entry == cursor)
+    });
+
+    if let Some(cursor_idx) = cursor_idx {
+        scroll_to_file_widget(ui, cursor_idx / columns, first_visible_row, row_height);
+    }
+}
+
 fn scroll_to_file_widget(
     ui: &mut Ui,
-    file_widget_idx: usize,
+    file_widget_row: usize,
+    first_visible_row: usize,
     file_widget_height: f32,
-    scroll_offset_y: f32,
 ) {
+    // Inside show_rows the top of the ui is the top of the first visible row
     let spacing = ui.spacing().item_spacing.y;
-    let y = file_widget_idx as f32 * (file_widget_height + spacing);
+    let rows_below = file_widget_row as f32 - first_visible_row as f32;
+    let y = ui.max_rect().top() + rows_below * (file_widget_height + spacing);
     let target_rect = Rect {
-        min: Pos2::new(0.0, y - scroll_offset_y),
-        max: Pos2::new(0.0, y + file_widget_height - scroll_offset_y),
+        min: Pos2::new(ui.max_rect().left(), y),
+        max: Pos2::new(ui.max_rect().left(), y + file_widget_height),
     };
 
-    ui.scroll_to_rect(target_rect, Some(Align::TOP));
+    ui.scroll_to_rect(target_rect, None);
+}
+
+/// Arrows move the cursor, Shift+arrows extend the selection, Ctrl+arrows move the cursor alone,
+/// Home/End/PageUp/PageDown jump, Space toggles, Enter opens, Backspace goes to the parent
+/// and any other character is searched among the file names.
+/// Returns the directory the user wants to go to, if any.
+fn keyboard_navigation(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+    columns: usize,
+    page_size: usize,
+) -> Option<PathBuf> {
+    let directory_content = &path_manager.directory_content;
+    let select_action = &mut actions.select_action;
+
+    let (modifiers, time, events) = ui.input(|input_state| {
+        (
+            input_state.modifiers,
+            input_state.time,
+            input_state.events.clone(),
+        )
+    });
+    let pressed = |key: Key| ui.input(|input_state| input_state.key_pressed(key));
+    select_action.mode = SelectionMode::from_modifiers(modifiers);
+
+    // Text fields and the Alt shortcuts have priority
+    if ui.ctx().wants_keyboard_input() || modifiers.alt || directory_content.is_empty() {
+        return None;
+    }
+
+    let last_idx = directory_content.len() - 1;
+    let cursor_idx = select_action
+        .cursor
+        .as_ref()
+        .and_then(|cursor| directory_content.iter().position(|entry| entry == cursor));
+
+    // Without a cursor, moving forward starts at the first file and moving backward at the last
+    let forward = |offset: usize| cursor_idx.map_or(0, |idx| (idx + offset).min(last_idx));
+    let backward = |offset: usize| cursor_idx.map_or(last_idx, |idx| idx.saturating_sub(offset));
+
+    let target_idx = if pressed(Key::ArrowDown) {
+        Some(forward(columns))
+    } else if pressed(Key::ArrowUp) {
+        Some(backward(columns))
+    } else if columns > 1 && pressed(Key::ArrowRight) {
+        Some(forward(1))
+    } else if columns > 1 && pressed(Key::ArrowLeft) {
+        Some(backward(1))
+    } else if pressed(Key::PageDown) {
+        Some(forward(page_size))
+    } else if pressed(Key::PageUp) {
+        Some(backward(page_size))
+    } else if pressed(Key::Home) {
+        Some(0)
+    } else if pressed(Key::End) {
+        Some(last_idx)
+    } else {
+        None
+    };
+
+    if let Some(target_idx) = target_idx {
+        let target = &directory_content[target_idx];
+        select_action.move_cursor(target, directory_content, modifiers);
+    }
+
+    if modifiers.command {
+        return None;
+    }
+
+    let typing_ahead = select_action.is_typing_ahead(time);
+    if pressed(Key::Space) && !typing_ahead {
+        select_action.toggle_cursor();
+    }
+
+    for event in &events {
+        let egui::Event::Text(text) = event else {
+            continue;
+        };
+
+        // Space belongs to the search only once something was typed
+        if text == " " && !typing_ahead {
+            continue;
+        }
+
+        if let Some(file) = select_action.type_ahead(text, time, directory_content) {
+            select_action.move_cursor(file, directory_content, Modifiers::NONE);
+        }
+    }
+
+    if pressed(Key::Backspace) {
+        return path_manager.parent_directory();
+    }
+
+    if pressed(Key::Enter)
+        && let Some(cursor) = select_action.cursor.clone()
+        && directory_content.contains(&cursor)
+    {
+        return open_entry(lang_string, error_manager, path_manager, &cursor, actions);
+    }
+
+    None
 }
 
 fn file_row_ui(
     ui: &mut Ui,
-    general_error_modal: &mut ErrorModal,
-    directory_content: &[PathBuf],
+    lang_string: &LangString,
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
     entry: &PathBuf,
     file_name: &String,
+    row_height: f32,
     actions: &mut Actions,
     icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
 ) -> Option<PathBuf> {
     // Which directory does the user want to go to?
     let mut new_current_path = None;
 
     ui.horizontal(|ui| {
-        ui.add(
-            icons_manager
-                .get_icon(&entry)
-                .clone()
-                .fit_to_exact_size(Vec2::new(32.0, 32.0)),
+        let (_, link) = file_icon(
+            ui,
+            &path_manager.vfs,
+            icons_manager,
+            mime_manager,
+            entry,
+            Vec2::splat(row_height),
         );
 
         ui.vertical_centered_justified(|ui| {
-            let file_widget_response =
-                file_widget(ui, actions.select_action.is_file_selected(entry), file_name);
+            if actions.is_renaming(entry) {
+                rename_ui(ui, actions, row_height);
+                return;
+            }
 
-            if file_widget_response.clicked() {
-                actions
-                    .select_action
-                    .select_file(entry, Some(directory_content));
+            let mut file_widget_response = file_widget(
+                ui,
+                actions.select_action.is_file_selected(entry),
+                actions.select_action.cursor.as_ref() == Some(entry),
+                link.as_ref().is_some_and(|(_, dangling)| *dangling),
+                file_name,
+                row_height,
+            );
+            git_badges_ui(ui, file_widget_response.rect, git_manager.status(entry));
+            if let Some((target, dangling)) = &link {
+                file_widget_response = file_widget_response.on_hover_text(link_hover_text(
+                    lang_string,
+                    target,
+                    *dangling,
+                ));
             }
 
-            if file_widget_response.double_clicked() {
-                if entry.is_dir() {
-                    new_current_path = Some(entry.clone());
-                } else {
-                    if let Err(err) = opener::open(entry) {
-                        general_error_modal.set_title_and_caption(&"Opener Error".to_string(), &err.to_string());
-                        general_error_modal.set_visible(true);
-                    }
-                }
+            new_current_path = file_widget_interaction(
+                ui,
+                &file_widget_response,
+                lang_string,
+                config,
+                error_manager,
+                path_manager,
+                entry,
+                actions,
+                mime_manager,
+                git_manager,
+                applications_manager,
+            );
+        });
+    });
+
+    new_current_path
+}
+
+fn link_hover_text(lang_string: &LangString, target: &Path, dangling: bool) -> String {
+    let key = if dangling {
+        LangKeys::BrokenLink
+    } else {
+        LangKeys::PointsTo
+    };
+
+    lang_string.format(
+        key,
+        &[("target", target.to_string_lossy().into_owned().into())],
+    )
+}
+
+/// Text field replacing the name of the file being renamed.
+/// Enter or clicking somewhere else applies the new name, Escape cancels.
+fn rename_ui(ui: &mut Ui, actions: &mut Actions, height: f32) {
+    let Some(rename_action) = &mut actions.rename_action else {
+        return;
+    };
+
+    let text_edit_id = ui.id().with("rename");
+    let response = ui.add_sized(
+        [ui.available_width(), height],
+        TextEdit::singleline(&mut rename_action.name)
+            .id(text_edit_id)
+            .vertical_align(Align::Center),
+    );
+
+    if rename_action.request_focus {
+        response.request_focus();
+
+        // Typing replaces the name but keeps the extension
+        let mut state = TextEditState::load(ui.ctx(), text_edit_id).unwrap_or_default();
+        state.cursor.set_char_range(Some(CCursorRange::two(
+            CCursor::new(0),
+            CCursor::new(rename_action.stem_len()),
+        )));
+        state.store(ui.ctx(), text_edit_id);
+
+        rename_action.request_focus = false;
+    }
+
+    if ui.input(|input_state| input_state.key_pressed(Key::Escape)) {
+        actions.rename_action = None;
+        return;
+    }
+
+    if response.lost_focus() {
+        actions.finish_rename();
+    }
+}
+
+/// Same as file_row_ui, laid out as a big icon above the name for the grid view.
+fn file_cell_ui(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    entry: &PathBuf,
+    file_name: &String,
+    actions: &mut Actions,
+    icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
+) -> Option<PathBuf> {
+    let mut new_current_path = None;
+
+    ui.allocate_ui(Vec2::new(GRID_CELL_WIDTH, GRID_CELL_HEIGHT), |ui| {
+        // Every row must be exactly GRID_CELL_HEIGHT high for show_rows
+        ui.set_min_size(Vec2::new(GRID_CELL_WIDTH, GRID_CELL_HEIGHT));
+        ui.vertical_centered_justified(|ui| {
+            let (icon_response, link) = file_icon(
+                ui,
+                &path_manager.vfs,
+                icons_manager,
+                mime_manager,
+                entry,
+                Vec2::new(64.0, 64.0),
+            );
+            git_emblem(ui, icon_response.rect, git_manager.status(entry));
+
+            let height = ui.spacing().interact_size.y * 1.65;
+            if actions.is_renaming(entry) {
+                rename_ui(ui, actions, height);
+                return;
             }
 
-            file_context_menu(&file_widget_response, entry, actions);
+            let mut file_widget_response = file_widget(
+                ui,
+                actions.select_action.is_file_selected(entry),
+                actions.select_action.cursor.as_ref() == Some(entry),
+                link.as_ref().is_some_and(|(_, dangling)| *dangling),
+                file_name,
+                height,
+            );
+            if let Some((target, dangling)) = &link {
+                file_widget_response = file_widget_response.on_hover_text(link_hover_text(
+                    lang_string,
+                    target,
+                    *dangling,
+                ));
+            }
+
+            new_current_path = file_widget_interaction(
+                ui,
+                &file_widget_response,
+                lang_string,
+                config,
+                error_manager,
+                path_manager,
+                entry,
+                actions,
+                mime_manager,
+                git_manager,
+                applications_manager,
+            );
         });
     });
 
     new_current_path
 }
 
-fn file_context_menu(file_widget_response: &Response, entry: &PathBuf, actions: &mut Actions) {
+/// Selection, drag and drop, opening and context menu of a file widget.
+/// Returns the directory the user wants to go to, if any.
+fn file_widget_interaction(
+    ui: &Ui,
+    file_widget_response: &Response,
+    lang_string: &LangString,
+    config: &Config,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    entry: &PathBuf,
+    actions: &mut Actions,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
+) -> Option<PathBuf> {
+    let mut new_current_path = None;
+    let archive = path_manager.vfs.archive(entry);
+
+    if file_widget_response.clicked() {
+        actions
+            .select_action
+            .select_file(entry, Some(&path_manager.directory_content));
+    }
+
+    if file_widget_response.drag_started() {
+        if !actions.select_action.is_file_selected(entry) {
+            actions.select_action.clear_selection();
+            actions.select_action.select_file(entry, None);
+        }
+
+        // Entries of an archive can be dragged onto a real folder to extract them
+        if let Some(archive) = archive {
+            file_widget_response.dnd_set_drag_payload(ArchiveEntriesPayload {
+                archive: archive.archive_path().to_path_buf(),
+                entries: actions
+                    .select_action
+                    .files
+                    .iter()
+                    .filter_map(|file| archive.inner_path(file))
+                    .map(|inner| inner.to_path_buf())
+                    .collect(),
+            });
+        } else {
+            // winit can not start a drag towards other applications, the payload stays inside Rocket
+            let files = actions.select_action.files.iter().cloned().collect();
+            file_widget_response.dnd_set_drag_payload(FilesPayload { files });
+        }
+    }
+
+    if path_manager.vfs.is_dir(entry) {
+        drop_target_ui(
+            ui,
+            file_widget_response,
+            lang_string,
+            config,
+            &path_manager.vfs,
+            actions,
+            entry,
+        );
+    }
+
+    if file_widget_response.double_clicked() {
+        new_current_path = open_entry(lang_string, error_manager, path_manager, entry, actions);
+    }
+
+    let menu_path = file_context_menu(
+        file_widget_response,
+        lang_string,
+        error_manager,
+        config,
+        path_manager,
+        entry,
+        actions,
+        mime_manager,
+        git_manager,
+        applications_manager,
+    );
+
+    new_current_path.or(menu_path)
+}
+
+/// Folders and archives are browsed, files are opened with their default application.
+/// Returns the directory the user wants to go to, if any.
+fn open_entry(
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    entry: &PathBuf,
+    actions: &mut Actions,
+) -> Option<PathBuf> {
+    let vfs = &path_manager.vfs;
+    let archive = vfs.archive(entry);
+    // Only archives on the local disk can be browsed
+    let is_archive = vfs.is_local(entry) && ArchiveKind::from_path(entry).is_some();
+
+    if vfs.is_dir(entry) || is_archive {
+        return Some(entry.clone());
+    }
+
+    if let Some(archive) = archive
+        && let Some(inner) = archive.inner_path(entry)
+    {
+        if let Err(err) = actions.extract_and_open(lang_string, archive.archive_path(), inner) {
+            error_manager.report(AppError::io(LangKeys::OpenFileError, &err).path(entry));
+        }
+    } else if !vfs.is_local(entry) {
+        if let Err(err) = actions.download_and_open(lang_string, vfs, entry) {
+            error_manager.report(AppError::io(LangKeys::OpenFileError, &err).path(entry));
+        }
+    } else if let Err(err) = opener::open(entry) {
+        error_manager.report(AppError::new(LangKeys::OpenFileError, err).path(entry));
+    }
+
+    None
+}
+
+/// Returns the directory the user wants to go to, if any.
+fn file_context_menu(
+    file_widget_response: &Response,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    config: &Config,
+    path_manager: &PathManager,
+    entry: &PathBuf,
+    actions: &mut Actions,
+    mime_manager: &MimeManager,
+    git_manager: &GitManager,
+    applications_manager: &ApplicationsManager,
+) -> Option<PathBuf> {
+    let mut new_current_path = None;
+
     file_widget_response.context_menu(|ui| {
         // If the file is not selected, clear the selection
         // and add the current file to the selection
@@ -256,8 +936,432 @@ fn file_context_menu(file_widget_response: &Response, entry: &PathBuf, actions:
             actions.select_action.select_file(entry, None);
         }
 
-        if ui.button("Unselect (test)").clicked() {
-            actions.select_action.deselect_file(entry);
+        let vfs = &path_manager.vfs;
+        let is_dir = vfs.is_dir(entry);
+
+        // Archives are read-only
+        let writable = vfs.archive(entry).is_none();
+
+        if ui.button(lang_string.get(LangKeys::Open)).clicked() {
+            new_current_path = open_entry(lang_string, error_manager, path_manager, entry, actions);
+            ui.close();
+        }
+
+        if !is_dir && writable && vfs.is_local(entry) {
+            open_with_menu(
+                ui,
+                lang_string,
+                error_manager,
+                vfs,
+                entry,
+                mime_manager,
+                applications_manager,
+            );
+        }
+
+        ui.separator();
+
+        if ui
+            .add_enabled(writable, Button::new(lang_string.get(LangKeys::Cut)))
+            .clicked()
+        {
+            actions.set_clipboard(TransferMode::Move);
+            ui.close();
+        }
+        if ui.button(lang_string.get(LangKeys::Copy)).clicked() {
+            actions.set_clipboard(TransferMode::Copy);
+            ui.close();
+        }
+        if is_dir
+            && writable
+            && ui
+                .add_enabled(
+                    actions.clipboard.is_some(),
+                    Button::new(lang_string.get(LangKeys::Paste)),
+                )
+                .clicked()
+        {
+            actions.paste(entry);
+            ui.close();
+        }
+
+        ui.separator();
+
+        let single = actions.select_action.files.len() == 1;
+        if ui
+            .add_enabled(
+                writable && single,
+                Button::new(lang_string.get(LangKeys::Rename)),
+            )
+            .clicked()
+        {
+            actions.start_rename(entry);
+            ui.close();
+        }
+        // Only the local disk has a trash
+        let trash = if vfs.is_local(entry) {
+            LangKeys::MoveToTrash
+        } else {
+            LangKeys::DeletePermanently
+        };
+        if ui
+            .add_enabled(writable, Button::new(lang_string.get(trash)))
+            .clicked()
+        {
+            actions.trash_selection(vfs, config.confirmations.delete_files);
+            ui.close();
+        }
+
+        ui.separator();
+
+        if ui.button(lang_string.get(LangKeys::CopyPath)).clicked() {
+            let mut paths: Vec<String> = actions
+                .select_action
+                .files
+                .iter()
+                .map(|file| file.to_string_lossy().into_owned())
+                .collect();
+            paths.sort();
+            ui.ctx().copy_text(paths.join("\n"));
+            ui.close();
+        }
+
+        ui.separator();
+
+        let linkable = writable && vfs.is_local(entry);
+        if ui
+            .add_enabled(
+                linkable,
+                Button::new(lang_string.get(LangKeys::CreateSymlink)),
+            )
+            .clicked()
+        {
+            actions.create_links(vfs, false);
+            ui.close();
+        }
+
+        // Folders can not be hard linked
+        let hard_linkable = linkable
+            && !actions
+                .select_action
+                .files
+                .iter()
+                .any(|file| vfs.is_dir(file));
+        if ui
+            .add_enabled(
+                hard_linkable,
+                Button::new(lang_string.get(LangKeys::CreateHardLink)),
+            )
+            .clicked()
+        {
+            actions.create_links(vfs, true);
+            ui.close();
+        }
+
+        if vfs.read_link(entry).is_some()
+            && ui
+                .button(lang_string.get(LangKeys::GoToLinkTarget))
+                .clicked()
+        {
+            new_current_path = actions.go_to_link_target(vfs, entry);
+            ui.close();
+        }
+
+        archive_context_menu(ui, lang_string, path_manager, entry, actions);
+        git_context_menu(ui, lang_string, error_manager, entry, actions, git_manager);
+        user_actions_context_menu(ui, lang_string, config, path_manager, actions, mime_manager);
+
+        ui.separator();
+
+        if ui.button(lang_string.get(LangKeys::Properties)).clicked() {
+            let files = actions.select_action.files.iter().cloned().collect();
+            actions.open_properties(files, vfs, mime_manager);
+            ui.close();
+        }
+    });
+
+    new_current_path
+}
+
+/// User actions of the config applicable to the selected files.
+fn user_actions_context_menu(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+    mime_manager: &MimeManager,
+) {
+    let mut files: Vec<PathBuf> = actions.select_action.files.iter().cloned().collect();
+    files.sort();
+
+    let applicable = user_actions::applicable(
+        &config.user_actions,
+        &files,
+        &path_manager.current_path,
+        &path_manager.vfs,
+        mime_manager,
+    );
+    if applicable.is_empty() {
+        return;
+    }
+
+    ui.separator();
+
+    ui.menu_button(lang_string.get(LangKeys::UserActions), |ui| {
+        for idx in applicable {
+            let user_action = &config.user_actions[idx];
+            if ui.button(&user_action.name).clicked() {
+                actions.run_user_action(user_action, &files, &path_manager.current_path);
+                ui.close();
+            }
+        }
+    });
+}
+
+/// Stage, unstage, discard and diff actions for the selected files of a git work tree.
+fn git_context_menu(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    entry: &PathBuf,
+    actions: &mut Actions,
+    git_manager: &GitManager,
+) {
+    let Some(work_tree) = git_manager.work_tree() else {
+        return;
+    };
+
+    let files: Vec<PathBuf> = actions.select_action.files.iter().cloned().collect();
+    if !files.iter().all(|file| file.starts_with(work_tree)) {
+        return;
+    }
+    let statuses: Vec<GitStatus> = files.iter().map(|file| git_manager.status(file)).collect();
+
+    ui.separator();
+
+    ui.menu_button(lang_string.get(LangKeys::Git), |ui| {
+        if ui
+            .add_enabled(
+                statuses.iter().any(GitStatus::is_stageable),
+                Button::new(lang_string.get(LangKeys::GitStage)),
+            )
+            .clicked()
+        {
+            actions.queue(FileOperation::GitStage(files.clone()));
+            ui.close();
+        }
+        if ui
+            .add_enabled(
+                statuses.iter().any(|status| status.staged),
+                Button::new(lang_string.get(LangKeys::GitUnstage)),
+            )
+            .clicked()
+        {
+            actions.queue(FileOperation::GitUnstage(files.clone()));
+            ui.close();
+        }
+        if ui
+            .add_enabled(
+                statuses.iter().any(|status| status.modified),
+                Button::new(lang_string.get(LangKeys::GitDiscard)),
+            )
+            .clicked()
+        {
+            actions.confirm_discard(files.clone());
+            ui.close();
+        }
+
+        ui.separator();
+
+        let single_changed = matches!(statuses.as_slice(), [status] if status.has_changes());
+        if ui
+            .add_enabled(
+                single_changed,
+                Button::new(lang_string.get(LangKeys::GitShowDiff)),
+            )
+            .clicked()
+        {
+            if let Err(err) = actions.show_diff(entry) {
+                error_manager.report(AppError::new(LangKeys::GitError, err).path(entry));
+            }
+            ui.close();
         }
     });
 }
+
+/// Applications whose desktop file declares they can open the type of `entry`.
+fn open_with_menu(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    vfs: &VfsRouter,
+    entry: &PathBuf,
+    mime_manager: &MimeManager,
+    applications_manager: &ApplicationsManager,
+) {
+    ui.menu_button(lang_string.get(LangKeys::OpenWith), |ui| {
+        let mime_types = mime_manager.with_parents(&mime_manager.mime_type(entry, vfs));
+        let applications = applications_manager.for_mime_types(&mime_types);
+
+        if applications.is_empty() {
+            ui.label(lang_string.get(LangKeys::NoApplications));
+            return;
+        }
+
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            for application in applications {
+                if !ui.button(&application.name).clicked() {
+                    continue;
+                }
+
+                if let Err(err) = application.launch(std::slice::from_ref(entry)) {
+                    error_manager.report(AppError::io(LangKeys::LaunchError, &err).path(entry));
+                }
+                ui.close();
+            }
+        });
+    });
+}
+
+/// Files of the templates directory, copied inside the current directory.
+fn templates_menu(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+) {
+    // The folder is read every frame while the menu is open, its error is reported once
+    let reported_id = Id::new("templates_error_reported");
+
+    let menu = ui.menu_button(lang_string.get(LangKeys::NewFromTemplate), |ui| {
+        let templates = match templates::templates() {
+            Ok(templates) => templates,
+            Err(error) => {
+                let reported = ui.memory(|memory| memory.data.get_temp::<bool>(reported_id));
+                if reported != Some(true) {
+                    error_manager.report(error);
+                    ui.memory_mut(|memory| memory.data.insert_temp(reported_id, true));
+                }
+                Vec::new()
+            }
+        };
+        if templates.is_empty() {
+            let directory = templates::templates_dir()
+                .map(|directory| directory.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            ui.label(lang_string.format(
+                LangKeys::NoTemplates,
+                &[("directory", directory.into())],
+            ));
+            return;
+        }
+
+        for template in templates {
+            let Some(name) = template.file_stem() else {
+                continue;
+            };
+
+            if ui.button(name.to_string_lossy()).clicked() {
+                actions.new_from_template(
+                    &path_manager.vfs,
+                    &template,
+                    &path_manager.current_path,
+                );
+                ui.close();
+            }
+        }
+    });
+
+    if menu.inner.is_none() {
+        ui.memory_mut(|memory| memory.data.remove::<bool>(reported_id));
+    }
+}
+
+/// Context menu of the empty space: create files or paste inside the current directory.
+fn background_context_menu(
+    response: &Response,
+    lang_string: &LangString,
+    error_manager: &mut ErrorManager,
+    path_manager: &PathManager,
+    actions: &mut Actions,
+) {
+    // Archives are read-only
+    let current_path = &path_manager.current_path;
+    if path_manager.vfs.archive(current_path).is_some() {
+        return;
+    }
+
+    response.context_menu(|ui| {
+        if ui.button(lang_string.get(LangKeys::NewFolder)).clicked() {
+            actions.new_folder(lang_string, &path_manager.vfs, current_path);
+            ui.close();
+        }
+        if ui.button(lang_string.get(LangKeys::NewFile)).clicked() {
+            actions.new_file(lang_string, &path_manager.vfs, current_path);
+            ui.close();
+        }
+        templates_menu(ui, lang_string, error_manager, path_manager, actions);
+
+        ui.separator();
+
+        if ui
+            .add_enabled(
+                actions.clipboard.is_some(),
+                Button::new(lang_string.get(LangKeys::Paste)),
+            )
+            .clicked()
+        {
+            actions.paste(current_path);
+            ui.close();
+        }
+    });
+}
+
+fn archive_context_menu(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    path_manager: &PathManager,
+    entry: &PathBuf,
+    actions: &mut Actions,
+) {
+    // Inside an archive: extract the selection next to the archive
+    if let Some(archive) = path_manager.vfs.archive(entry) {
+        if ui.button(lang_string.get(LangKeys::Extract)).clicked() {
+            let selection = actions
+                .select_action
+                .files
+                .iter()
+                .filter_map(|file| archive.inner_path(file))
+                .map(|inner| inner.to_path_buf())
+                .collect();
+            let archive_path = archive.archive_path();
+            let destination = archive_path.parent().unwrap_or(Path::new("/"));
+
+            actions.extract(lang_string, archive_path, selection, destination);
+            ui.close();
+        }
+
+        return;
+    }
+
+    // Archives are read, and compressed, on the local disk only
+    if !path_manager.vfs.is_local(entry) {
+        return;
+    }
+
+    if ArchiveKind::from_path(entry).is_some()
+        && ui.button(lang_string.get(LangKeys::ExtractHere)).clicked()
+    {
+        let destination = archive_manager::extraction_directory(entry);
+        actions.extract(lang_string, entry, Vec::new(), &destination);
+        ui.close();
+    }
+
+    if ui.button(lang_string.get(LangKeys::Compress)).clicked() {
+        let files = actions.select_action.files.iter().cloned().collect();
+        actions.open_compress_dialog(files, &path_manager.current_path);
+        ui.close();
+    }
+}
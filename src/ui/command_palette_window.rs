@@ -0,0 +1,112 @@
+use crate::command_palette::{CommandPalette, PaletteEntry};
+use crate::command_registry::CommandRegistry;
+use crate::lang_string::{LangKeys, LangString};
+use eframe::egui;
+use egui::{Align, Align2, Context, Id, Key, Layout, Modifiers, ScrollArea, TextEdit, Window};
+
+/// Returns the entry chosen by the user, if any.
+pub fn show(
+    ctx: &Context,
+    lang_string: &LangString,
+    command_palette: &mut CommandPalette,
+    command_registry: &CommandRegistry,
+) -> Option<PaletteEntry> {
+    if !command_palette.visible {
+        return None;
+    }
+
+    // Arrows and Enter are read before the text field sees them
+    let (up, down, enter, escape) = ctx.input_mut(|input_state| {
+        (
+            input_state.consume_key(Modifiers::NONE, Key::ArrowUp),
+            input_state.consume_key(Modifiers::NONE, Key::ArrowDown),
+            input_state.consume_key(Modifiers::NONE, Key::Enter),
+            input_state.consume_key(Modifiers::NONE, Key::Escape),
+        )
+    });
+
+    if up {
+        command_palette.select_previous();
+    }
+    if down {
+        command_palette.select_next();
+    }
+    if escape {
+        command_palette.close();
+        return None;
+    }
+
+    let mut chosen = None;
+    if enter {
+        chosen = command_palette.selected();
+    }
+
+    Window::new(lang_string.get(LangKeys::CommandPalette))
+        .id(Id::new("command_palette"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut command_palette.query).desired_width(f32::INFINITY),
+            );
+            if command_palette.request_focus {
+                response.request_focus();
+                command_palette.request_focus = false;
+            }
+            if response.changed() {
+                command_palette.update_matches(lang_string);
+            }
+
+            ui.separator();
+
+            if command_palette.matches.is_empty() {
+                ui.label(lang_string.get(LangKeys::NoMatchingCommands));
+                return;
+            }
+
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (idx, entry) in command_palette.matches.iter().enumerate() {
+                    let is_selected = idx == command_palette.selected_idx;
+
+                    let response = ui
+                        .horizontal(|ui| {
+                            let response = ui.selectable_label(
+                                is_selected,
+                                command_palette.entry_name(lang_string, *entry),
+                            );
+
+                            let binding = match entry {
+                                PaletteEntry::Command(command) => {
+                                    command_registry.binding(*command)
+                                }
+                                PaletteEntry::UserAction(_) => None,
+                            };
+                            if let Some(binding) = binding {
+                                let shortcut_text = ctx.format_shortcut(&binding);
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    ui.weak(shortcut_text);
+                                });
+                            }
+
+                            response
+                        })
+                        .inner;
+
+                    if is_selected && (up || down) {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        chosen = Some(*entry);
+                    }
+                }
+            });
+        });
+
+    if chosen.is_some() {
+        command_palette.close();
+    }
+
+    chosen
+}
@@ -1,10 +1,32 @@
+use crate::actions::Actions;
+use crate::address_bar::AddressBar;
+use crate::config_manager::Config;
+use crate::git_manager::{BranchInfo, GitManager};
+use crate::job_manager::JobManager;
+use crate::lang_string::{LangKeys, LangString};
 use crate::path_manager::PathManager;
+use crate::ui::drop_target::drop_target_ui;
+use crate::vfs::Vfs;
 use eframe::egui;
-use egui::{Button, Image, Label, TextStyle, Ui, Vec2, WidgetText};
+use egui::text::{CCursor, CCursorRange};
+use egui::text_edit::TextEditState;
+use egui::{
+    Button, Color32, Image, Key, Label, Modifiers, ScrollArea, Sense, TextEdit, TextStyle, Ui,
+    Vec2, WidgetText,
+};
 use std::path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub fn show(ui: &mut Ui, path_manager: &mut PathManager, folder_img: &Image) {
+pub fn show(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    path_manager: &mut PathManager,
+    address_bar: &mut AddressBar,
+    actions: &mut Actions,
+    git_manager: &GitManager,
+    folder_img: &Image,
+) {
     let available_space = ui.available_size();
 
     ui.allocate_ui_with_layout(
@@ -14,36 +36,303 @@ pub fn show(ui: &mut Ui, path_manager: &mut PathManager, folder_img: &Image) {
             ui.horizontal(|ui| {
                 ui.add(folder_img.clone().fit_to_exact_size(Vec2::new(64.0, 64.0)));
 
-                let mut current_path = PathBuf::new();
-                let path = path_manager.current_path.clone();
-                let path_components = path.components();
-                let parts: Vec<_> = path_components
-                    .map(|component| component.as_os_str())
-                    .collect();
-
-                for (path_idx, path_part) in parts.iter().enumerate() {
-                    current_path.push(path_part);
-
-                    if path_idx + 1 == parts.len() {
-                        ui.add(Label::new(
-                            WidgetText::from(path_part.to_string_lossy())
-                                .text_style(TextStyle::Heading),
-                        ));
-                    } else if ui
-                        .add(Button::new(
-                            WidgetText::from(path_part.to_string_lossy())
-                                .text_style(TextStyle::Heading),
-                        ))
-                        .clicked()
-                    {
-                        path_manager.update_current_directory(&current_path);
-                    }
-
-                    if current_path.parent().is_some() {
-                        ui.label(String::from(path::MAIN_SEPARATOR));
-                    }
+                if address_bar.editing {
+                    address_bar_ui(ui, lang_string, path_manager, address_bar);
+                    return;
+                }
+
+                breadcrumb_ui(
+                    ui,
+                    lang_string,
+                    config,
+                    path_manager,
+                    actions,
+                );
+
+                if let Some(branch) = &git_manager.branch {
+                    branch_ui(ui, lang_string, branch);
+                }
+
+                jobs_button_ui(ui, lang_string, &mut actions.job_manager);
+
+                // Clicking the empty space after the breadcrumb turns it into a text field
+                let empty_space = ui.allocate_response(ui.available_size(), Sense::click());
+                if empty_space.clicked() {
+                    address_bar.start_editing(&path_manager.current_path);
                 }
             });
         },
     );
 }
+
+fn address_bar_ui(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    path_manager: &mut PathManager,
+    address_bar: &mut AddressBar,
+) {
+    let text_edit_id = ui.id().with("address_bar");
+
+    // Tab must be read before the text field sees it, otherwise the focus moves away
+    let tab_pressed = ui.memory(|memory| memory.has_focus(text_edit_id))
+        && ui.input_mut(|input_state| input_state.consume_key(Modifiers::NONE, Key::Tab));
+    if tab_pressed {
+        address_bar.complete(&mut path_manager.vfs);
+        move_cursor_to_end(ui, text_edit_id, address_bar.text.chars().count());
+    }
+
+    let error_width = if address_bar.error.is_some() { 250.0 } else { 0.0 };
+    let response = ui.add(
+        TextEdit::singleline(&mut address_bar.text)
+            .id(text_edit_id)
+            .lock_focus(true)
+            .font(TextStyle::Heading)
+            .desired_width(ui.available_width() - error_width),
+    );
+
+    if address_bar.request_focus {
+        response.request_focus();
+        address_bar.request_focus = false;
+    }
+
+    if response.changed() {
+        address_bar.text_changed();
+    }
+
+    if let Some(error) = &address_bar.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    if ui.input(|input_state| input_state.key_pressed(Key::Escape)) {
+        address_bar.stop_editing();
+        return;
+    }
+
+    if response.lost_focus() {
+        if ui.input(|input_state| input_state.key_pressed(Key::Enter)) {
+            if let Some(new_path) = address_bar.submit(lang_string, &mut path_manager.vfs) {
+                path_manager.update_current_directory(&new_path);
+                path_manager.update_cursor_icon = true;
+            } else {
+                address_bar.request_focus = true;
+            }
+        } else if !tab_pressed {
+            // Clicked somewhere else
+            address_bar.stop_editing();
+        }
+    }
+}
+
+fn move_cursor_to_end(ui: &Ui, text_edit_id: egui::Id, char_count: usize) {
+    let mut state = TextEditState::load(ui.ctx(), text_edit_id).unwrap_or_default();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(char_count))));
+    state.store(ui.ctx(), text_edit_id);
+}
+
+/// Width kept for the "…" button shown when leading segments are collapsed.
+const ELLIPSIS_WIDTH: f32 = 40.0;
+
+/// Width of the clickable empty space kept after the breadcrumb.
+const MIN_EMPTY_SPACE: f32 = 40.0;
+
+fn breadcrumb_ui(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    path_manager: &mut PathManager,
+    actions: &mut Actions,
+) {
+    // Every segment is paired with the path it leads to
+    let mut segment_path = PathBuf::new();
+    let segments: Vec<(PathBuf, String)> = path_manager
+        .current_path
+        .components()
+        .map(|component| {
+            segment_path.push(component);
+            (
+                segment_path.clone(),
+                component.as_os_str().to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+
+    let first_visible = first_visible_segment(ui, &segments);
+    let mut jump_to = None;
+
+    // Leading segments which do not fit are collapsed into a menu
+    if first_visible > 0 {
+        ui.menu_button(WidgetText::from("…").text_style(TextStyle::Heading), |ui| {
+            for (path, label) in &segments[..first_visible] {
+                if ui.button(label).clicked() {
+                    jump_to = Some(path.clone());
+                    ui.close();
+                }
+            }
+        });
+        ui.label(String::from(path::MAIN_SEPARATOR));
+    }
+
+    for (segment_idx, (path, label)) in segments.iter().enumerate().skip(first_visible) {
+        if segment_idx + 1 == segments.len() {
+            ui.add(Label::new(
+                WidgetText::from(label).text_style(TextStyle::Heading),
+            ));
+        } else {
+            let response = ui.add(Button::new(
+                WidgetText::from(label).text_style(TextStyle::Heading),
+            ));
+
+            if response.clicked() {
+                jump_to = Some(path.clone());
+            }
+
+            drop_target_ui(
+                ui,
+                &response,
+                lang_string,
+                config,
+                &path_manager.vfs,
+                actions,
+                path,
+            );
+        }
+
+        if let Some(sibling) = sibling_menu_ui(ui, path_manager, path) {
+            jump_to = Some(sibling);
+        }
+
+        if path.parent().is_some() && segment_idx + 1 != segments.len() {
+            ui.label(String::from(path::MAIN_SEPARATOR));
+        }
+    }
+
+    if let Some(path) = jump_to {
+        path_manager.update_current_directory(&path);
+    }
+}
+
+/// Index of the first segment displayed, so that the breadcrumb fits the panel.
+/// The last segment is always displayed.
+fn first_visible_segment(ui: &Ui, segments: &[(PathBuf, String)]) -> usize {
+    let font_id = TextStyle::Heading.resolve(ui.style());
+    let button_padding = ui.spacing().button_padding.x * 2.0;
+    let item_spacing = ui.spacing().item_spacing.x;
+
+    // Button, dropdown arrow and separator of every segment
+    let extra_width = button_padding + ui.spacing().interact_size.x + 4.0 * item_spacing;
+
+    let available_width = ui.available_width() - ELLIPSIS_WIDTH - MIN_EMPTY_SPACE;
+    let mut used_width = 0.0;
+    let mut first_visible = segments.len();
+
+    for (idx, (_, label)) in segments.iter().enumerate().rev() {
+        let text_width = ui.fonts(|fonts| {
+            fonts
+                .layout_no_wrap(label.clone(), font_id.clone(), Color32::WHITE)
+                .size()
+                .x
+        });
+
+        used_width += text_width + extra_width;
+        if used_width > available_width && idx + 1 < segments.len() {
+            break;
+        }
+
+        first_visible = idx;
+    }
+
+    first_visible
+}
+
+/// Small dropdown listing the subdirectories of `path`, for lateral jumps.
+fn sibling_menu_ui(ui: &mut Ui, path_manager: &PathManager, path: &Path) -> Option<PathBuf> {
+    let mut jump_to = None;
+
+    ui.menu_button("⏷", |ui| {
+        let mut subdirectories: Vec<PathBuf> = path_manager
+            .vfs
+            .list(path)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| path_manager.vfs.is_dir(entry))
+            .collect();
+        subdirectories.sort();
+
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            for subdirectory in subdirectories {
+                let Some(file_name) = subdirectory.file_name() else {
+                    continue;
+                };
+
+                let is_current = path_manager.current_path.starts_with(&subdirectory);
+                if ui
+                    .selectable_label(is_current, file_name.to_string_lossy())
+                    .clicked()
+                {
+                    jump_to = Some(subdirectory.clone());
+                    ui.close();
+                }
+            }
+        });
+    });
+
+    jump_to
+}
+
+/// Branch of the repository of the current folder, with the commits to push and to pull.
+fn branch_ui(ui: &mut Ui, lang_string: &LangString, branch: &BranchInfo) {
+    let mut text = format!("⎇ {}", branch.name);
+    if branch.ahead > 0 {
+        text.push_str(&format!(" ↑{}", branch.ahead));
+    }
+    if branch.behind > 0 {
+        text.push_str(&format!(" ↓{}", branch.behind));
+    }
+
+    let hover_text = match &branch.upstream {
+        Some(upstream) => lang_string.format(
+            LangKeys::GitAheadBehind,
+            &[
+                ("ahead", branch.ahead.into()),
+                ("behind", branch.behind.into()),
+                ("upstream", upstream.clone().into()),
+            ],
+        ),
+        None => lang_string.get(LangKeys::GitNoUpstream),
+    };
+
+    ui.separator();
+    ui.add(Label::new(
+        WidgetText::from(text).text_style(TextStyle::Heading),
+    ))
+    .on_hover_text(hover_text);
+}
+
+/// Number of running jobs, clicking it shows or hides the jobs panel.
+/// Hidden while there is neither a job nor a history.
+fn jobs_button_ui(ui: &mut Ui, lang_string: &LangString, job_manager: &mut JobManager) {
+    if job_manager.jobs.is_empty() && job_manager.history.is_empty() {
+        return;
+    }
+
+    let running = job_manager.running_count();
+    let text = if running > 0 {
+        format!("⏳ {}", running)
+    } else {
+        String::from("⏳")
+    };
+
+    ui.separator();
+    if ui
+        .selectable_label(
+            job_manager.visible,
+            WidgetText::from(text).text_style(TextStyle::Heading),
+        )
+        .on_hover_text(lang_string.format(LangKeys::JobsRunning, &[("count", running.into())]))
+        .clicked()
+    {
+        job_manager.visible = !job_manager.visible;
+    }
+}
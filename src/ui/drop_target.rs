@@ -0,0 +1,64 @@
+use crate::actions::Actions;
+use crate::actions::archive_action::ArchiveEntriesPayload;
+use crate::actions::transfer_action::FilesPayload;
+use crate::config_manager::Config;
+use crate::lang_string::LangString;
+use crate::transfer_manager::TransferMode;
+use crate::vfs::VfsRouter;
+use eframe::egui;
+use egui::{Response, StrokeKind, Ui};
+use std::path::Path;
+
+/// Makes `response` accept the files dragged out of the central panel.
+/// Dropped files are moved inside `path` (Ctrl copies, Shift+Ctrl links),
+/// archive entries are extracted there.
+pub fn drop_target_ui(
+    ui: &Ui,
+    response: &Response,
+    lang_string: &LangString,
+    config: &Config,
+    vfs: &VfsRouter,
+    actions: &mut Actions,
+    path: &Path,
+) {
+    // Archives are read-only
+    if vfs.archive(path).is_some() {
+        return;
+    }
+
+    // A folder can not be dropped inside itself
+    let files_payload = response
+        .dnd_hover_payload::<FilesPayload>()
+        .filter(|payload| !payload.files.iter().any(|file| path.starts_with(file)));
+
+    if files_payload.is_some()
+        || response
+            .dnd_hover_payload::<ArchiveEntriesPayload>()
+            .is_some()
+    {
+        ui.painter().rect_stroke(
+            response.rect,
+            2.0,
+            ui.visuals().selection.stroke,
+            StrokeKind::Outside,
+        );
+    }
+
+    if let Some(payload) = response.dnd_release_payload::<ArchiveEntriesPayload>() {
+        actions.extract(lang_string, &payload.archive, payload.entries.clone(), path);
+    }
+
+    if let Some(payload) = response.dnd_release_payload::<FilesPayload>() {
+        if payload.files.iter().any(|file| path.starts_with(file)) {
+            return;
+        }
+
+        let mode = TransferMode::from_modifiers(ui.input(|input_state| input_state.modifiers));
+        actions.drop_files(
+            payload.files.clone(),
+            path,
+            mode,
+            config.confirmations.move_files,
+        );
+    }
+}
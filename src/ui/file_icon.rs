@@ -0,0 +1,117 @@
+use crate::git_manager::GitStatus;
+use crate::icons_manager::IconsManager;
+use crate::mime_manager::{DIRECTORY_MIME_TYPE, MimeManager};
+use crate::path_manager;
+use crate::ui::file_widget::git_badges;
+use crate::vfs::{Vfs, VfsRouter};
+use egui::{Align2, Color32, FontId, Rect, Response, Ui, Vec2};
+use std::path::{Path, PathBuf};
+
+/// Hidden files are drawn faded.
+const HIDDEN_ICON_ALPHA: u8 = 110;
+
+/// Icon of `file` with the emblems of its state: symbolic link, read-only and remote.
+/// Special folders get their own icon, or the plain folder icon with a glyph over it.
+/// Returns the target of a symbolic link and whether it is missing, None for other files.
+pub fn file_icon(
+    ui: &mut Ui,
+    vfs: &VfsRouter,
+    icons_manager: &IconsManager,
+    mime_manager: &MimeManager,
+    file: &Path,
+    size: Vec2,
+) -> (Response, Option<(PathBuf, bool)>) {
+    let mime_type = mime_manager.mime_type(file, vfs);
+
+    let mut glyph = None;
+    let mut icon = if mime_type == DIRECTORY_MIME_TYPE {
+        let kind = icons_manager.folder_kind(file, vfs);
+        icons_manager.get_folder_icon(kind).unwrap_or_else(|| {
+            glyph = kind.glyph();
+            icons_manager.get_icon(file, &mime_type)
+        })
+    } else {
+        icons_manager.get_icon(file, &mime_type)
+    };
+    if path_manager::is_hidden(file) {
+        icon = icon.tint(Color32::from_white_alpha(HIDDEN_ICON_ALPHA));
+    }
+
+    let response = ui.add(icon.fit_to_exact_size(size));
+    let link = vfs
+        .link_target(file)
+        .map(|target| (target, vfs.is_dangling(file)));
+
+    let rect = response.rect;
+    if ui.is_rect_visible(rect) {
+        if let Some(glyph) = glyph {
+            ui.painter().text(
+                rect.center() + Vec2::new(0.0, rect.height() * 0.08),
+                Align2::CENTER_CENTER,
+                glyph,
+                FontId::proportional(rect.height() * 0.4),
+                ui.visuals().strong_text_color(),
+            );
+        }
+
+        if let Some((_, dangling)) = &link {
+            let color = if *dangling {
+                ui.visuals().error_fg_color
+            } else {
+                ui.visuals().strong_text_color()
+            };
+            emblem(ui, rect, Align2::LEFT_BOTTOM, "↗", color);
+        }
+
+        // Everything inside an archive is read-only, the emblem would be on every file
+        let readonly =
+            vfs.archive(file).is_none() && vfs.stat(file).is_ok_and(|metadata| metadata.readonly);
+        if readonly {
+            emblem(
+                ui,
+                rect,
+                Align2::RIGHT_BOTTOM,
+                "🔒",
+                ui.visuals().warn_fg_color,
+            );
+        }
+
+        if !vfs.is_local(file) {
+            emblem(
+                ui,
+                rect,
+                Align2::LEFT_TOP,
+                "🌐",
+                ui.visuals().strong_text_color(),
+            );
+        }
+    }
+
+    (response, link)
+}
+
+/// Most important git state of the file, for the grid view where there is no room for badges.
+pub fn git_emblem(ui: &Ui, icon_rect: Rect, status: GitStatus) {
+    if let Some((letter, color)) = git_badges(ui, status).first()
+        && ui.is_rect_visible(icon_rect)
+    {
+        emblem(ui, icon_rect, Align2::RIGHT_TOP, letter, *color);
+    }
+}
+
+/// Glyph in a small disc painted over a corner of an icon.
+pub fn emblem(ui: &Ui, icon_rect: Rect, corner: Align2, glyph: &str, color: Color32) {
+    let radius = (icon_rect.height() * 0.22).max(5.0);
+    let inner_rect = icon_rect.shrink(radius);
+    let center = corner.pos_in_rect(&inner_rect);
+
+    let painter = ui.painter();
+    painter.circle_filled(center, radius, ui.visuals().extreme_bg_color);
+    painter.text(
+        center,
+        Align2::CENTER_CENTER,
+        glyph,
+        FontId::proportional(radius * 1.6),
+        color,
+    );
+}
@@ -1,13 +1,23 @@
-use egui::{Align2, Color32, FontFamily, FontId, Response, Sense, StrokeKind, Ui, Vec2};
+use crate::git_manager::GitStatus;
+use egui::{Align2, Color32, FontId, Rect, Response, Sense, StrokeKind, TextStyle, Ui, Vec2};
 
-pub fn file_widget(ui: &mut Ui, selected: bool, text: &String) -> Response {
-    let desired_space = ui.spacing().interact_size.y * egui::vec2(1.0, 1.65);
+/// `has_cursor` outlines the file the keyboard acts on.
+/// `dangling` paints the name of a symbolic link whose target is missing in the error color.
+pub fn file_widget(
+    ui: &mut Ui,
+    selected: bool,
+    has_cursor: bool,
+    dangling: bool,
+    text: &String,
+    height: f32,
+) -> Response {
+    let desired_space = egui::vec2(ui.spacing().interact_size.y, height);
     let (id, rect) =
         ui.allocate_space(desired_space);
     let response = ui.interact(rect, id, Sense::click_and_drag());
 
     response.widget_info(|| {
-        egui::WidgetInfo::selected(egui::WidgetType::Button, true, selected, "File Widget")
+        egui::WidgetInfo::selected(egui::WidgetType::Button, true, selected, text)
     });
 
     if ui.is_rect_visible(rect) {
@@ -26,7 +36,18 @@ pub fn file_widget(ui: &mut Ui, selected: bool, text: &String) -> Response {
             StrokeKind::Inside,
         );
 
-        let color = if selected {
+        if has_cursor {
+            ui.painter().rect_stroke(
+                rect,
+                rounding,
+                ui.visuals().selection.stroke,
+                StrokeKind::Inside,
+            );
+        }
+
+        let color = if dangling {
+            ui.visuals().error_fg_color
+        } else if selected {
             visuals.text_color().gamma_multiply(1.5)
         } else {
             visuals.text_color()
@@ -36,13 +57,60 @@ pub fn file_widget(ui: &mut Ui, selected: bool, text: &String) -> Response {
             rect.left_center() + Vec2::new(10.0, 0.0),
             Align2::LEFT_CENTER,
             text,
-            FontId {
-                size: 15.0,
-                family: FontFamily::Monospace,
-            },
+            TextStyle::Monospace.resolve(ui.style()),
             color,
         );
     }
 
     response
 }
+
+/// Letter and color of every state of `status`, the most important first.
+pub fn git_badges(ui: &Ui, status: GitStatus) -> Vec<(&'static str, Color32)> {
+    let mut badges = Vec::new();
+    if status.conflicted {
+        badges.push(("C", ui.visuals().error_fg_color));
+    }
+    if status.staged {
+        badges.push(("S", Color32::from_rgb(80, 180, 80)));
+    }
+    if status.modified {
+        badges.push(("M", ui.visuals().warn_fg_color));
+    }
+    if status.untracked {
+        badges.push(("U", Color32::from_rgb(90, 160, 220)));
+    }
+    if status.ignored {
+        badges.push(("I", ui.visuals().weak_text_color()));
+    }
+
+    badges
+}
+
+/// Git state badges painted inside the right end of a file widget.
+pub fn git_badges_ui(ui: &Ui, widget_rect: Rect, status: GitStatus) {
+    if status.is_clean() || !ui.is_rect_visible(widget_rect) {
+        return;
+    }
+
+    let size = (widget_rect.height() * 0.6).min(18.0);
+    let font_id = FontId::monospace(size * 0.75);
+    let mut right = widget_rect.right() - 6.0;
+
+    for (letter, color) in git_badges(ui, status) {
+        let badge_rect = Rect::from_center_size(
+            egui::pos2(right - size / 2.0, widget_rect.center().y),
+            Vec2::splat(size),
+        );
+        ui.painter().rect_filled(badge_rect, 3.0, color);
+        ui.painter().text(
+            badge_rect.center(),
+            Align2::CENTER_CENTER,
+            letter,
+            font_id.clone(),
+            Color32::BLACK,
+        );
+
+        right -= size + 3.0;
+    }
+}
@@ -0,0 +1,45 @@
+use crate::lang_string::{LangKeys, LangString};
+use crate::path_manager::PathManager;
+use eframe::egui;
+use egui::{Context, Id, RichText, Window};
+
+/// Asks the user to trust a server missing from `~/.ssh/known_hosts`,
+/// shown while `vfs.unknown_host_key` is set.
+pub fn show(ctx: &Context, lang_string: &LangString, path_manager: &mut PathManager) {
+    let Some(unknown_host_key) = &path_manager.vfs.unknown_host_key else {
+        return;
+    };
+
+    let mut trusted = false;
+    let mut cancelled = false;
+
+    Window::new(lang_string.get(LangKeys::UnknownServer))
+        .id(Id::new("host_key_confirmation"))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(lang_string.format(
+                LangKeys::UnknownServerQuestion,
+                &[("host", unknown_host_key.host.clone().into())],
+            ));
+            ui.label(RichText::new(&unknown_host_key.fingerprint).monospace());
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(lang_string.get(LangKeys::TrustServer)).clicked() {
+                    trusted = true;
+                }
+                if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if trusted {
+        path_manager.vfs.trust_host_key();
+        // Connects again, the key is saved once it is accepted
+        path_manager.update_folder_content = true;
+    } else if cancelled {
+        path_manager.vfs.unknown_host_key = None;
+    }
+}
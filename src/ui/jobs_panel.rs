@@ -0,0 +1,90 @@
+use crate::config_manager::Config;
+use crate::job_manager::{JobManager, JobOutcome, JobRecord, format_duration};
+use crate::lang_string::{LangKeys, LangString};
+use crate::ui::additional_info_panel::format_date_time;
+use eframe::egui;
+use egui::{Align, Button, CollapsingHeader, Layout, ScrollArea, Ui};
+
+/// Running jobs with their controls, then the history of the finished ones.
+pub fn show(ui: &mut Ui, lang_string: &LangString, config: &Config, job_manager: &mut JobManager) {
+    ui.horizontal(|ui| {
+        ui.strong(lang_string.get(LangKeys::Jobs));
+
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if ui.small_button("✖").clicked() {
+                job_manager.visible = false;
+            }
+            if ui
+                .add_enabled(
+                    !job_manager.history.is_empty(),
+                    Button::new(lang_string.get(LangKeys::ClearHistory)),
+                )
+                .clicked()
+            {
+                job_manager.clear_history();
+            }
+        });
+    });
+
+    ui.separator();
+
+    ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+        if job_manager.jobs.is_empty() {
+            ui.weak(lang_string.get(LangKeys::NoRunningJobs));
+        }
+
+        for job in &mut job_manager.jobs {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(&job.title);
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        // A hidden modal can be brought back
+                        if !job.visible && ui.button(lang_string.get(LangKeys::ShowJob)).clicked() {
+                            job.visible = true;
+                        }
+                        job.controls_ui(ui, lang_string);
+                    });
+                });
+
+                job.progress_ui(ui, lang_string, config);
+            });
+        }
+
+        ui.add_space(4.0);
+
+        CollapsingHeader::new(lang_string.get(LangKeys::JobHistory))
+            .default_open(true)
+            .show(ui, |ui| {
+                for record in &job_manager.history {
+                    record_ui(ui, lang_string, config, record);
+                }
+            });
+    });
+}
+
+/// One line per finished job, the error below it if it failed.
+fn record_ui(ui: &mut Ui, lang_string: &LangString, config: &Config, record: &JobRecord) {
+    ui.horizontal(|ui| {
+        let (glyph, color) = match record.outcome {
+            JobOutcome::Done => ("✔", ui.visuals().text_color()),
+            JobOutcome::Failed(_) => ("✖", ui.visuals().error_fg_color),
+            JobOutcome::Cancelled => ("⏹", ui.visuals().warn_fg_color),
+        };
+        ui.colored_label(color, glyph);
+        ui.label(&record.title);
+
+        ui.weak(format_date_time(record.finished_at, &config.date_format));
+        ui.weak(&record.amount);
+        ui.weak(lang_string.format(
+            LangKeys::JobFinishedIn,
+            &[("duration", format_duration(record.elapsed).into())],
+        ));
+    });
+
+    if let JobOutcome::Failed(err) = &record.outcome {
+        ui.indent(("job_error", record.finished_at), |ui| {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        });
+    }
+}
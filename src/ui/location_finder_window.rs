@@ -0,0 +1,88 @@
+use crate::lang_string::{LangKeys, LangString};
+use crate::location_finder::LocationFinder;
+use crate::path_manager::PathManager;
+use eframe::egui;
+use egui::{Align2, Context, Id, Key, Modifiers, ScrollArea, TextEdit, Window};
+
+pub fn show(
+    ctx: &Context,
+    lang_string: &LangString,
+    location_finder: &mut LocationFinder,
+    path_manager: &mut PathManager,
+) {
+    if !location_finder.visible {
+        return;
+    }
+
+    // Arrows and Enter are read before the text field sees them
+    let (up, down, enter, escape) = ctx.input_mut(|input_state| {
+        (
+            input_state.consume_key(Modifiers::NONE, Key::ArrowUp),
+            input_state.consume_key(Modifiers::NONE, Key::ArrowDown),
+            input_state.consume_key(Modifiers::NONE, Key::Enter),
+            input_state.consume_key(Modifiers::NONE, Key::Escape),
+        )
+    });
+
+    if up {
+        location_finder.select_previous();
+    }
+    if down {
+        location_finder.select_next();
+    }
+    if escape {
+        location_finder.close();
+        return;
+    }
+
+    let mut jump_to = None;
+    if enter {
+        jump_to = location_finder.selected().cloned();
+    }
+
+    Window::new(lang_string.get(LangKeys::GoToLocation))
+        .id(Id::new("location_finder"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut location_finder.query).desired_width(f32::INFINITY),
+            );
+            if location_finder.request_focus {
+                response.request_focus();
+                location_finder.request_focus = false;
+            }
+            if response.changed() {
+                location_finder.update_matches(&path_manager.recent_locations);
+            }
+
+            ui.separator();
+
+            if location_finder.matches.is_empty() {
+                ui.label(lang_string.get(LangKeys::NoRecentLocations));
+                return;
+            }
+
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (idx, path) in location_finder.matches.iter().enumerate() {
+                    let is_selected = idx == location_finder.selected_idx;
+                    let response = ui.selectable_label(is_selected, path.to_string_lossy());
+
+                    if is_selected && (up || down) {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        jump_to = Some(path.clone());
+                    }
+                }
+            });
+        });
+
+    if let Some(path) = jump_to {
+        location_finder.close();
+        path_manager.update_current_directory(&path);
+        path_manager.update_cursor_icon = true;
+    }
+}
@@ -1,38 +1,63 @@
+use crate::config_manager::ConfigManager;
 use crate::lang_string::{LangKeys, LangString};
 use crate::path_manager::PathManager;
 use crate::search_manager::SearchManager;
+use crate::view_settings::{SortKey, ViewMode};
 use eframe::egui;
-use egui::{Key, TextEdit, Ui};
+use egui::{Area, ComboBox, Frame, Id, Key, Order, PointerButton, Response, TextEdit, Ui};
+use std::path::{Path, PathBuf};
+
+/// How long Back/Forward must be held down to open the history dropdown.
+/// egui stops counting a press as a click past 0.8s, so releasing the button does not navigate.
+const LONG_PRESS_SECONDS: f64 = 0.8;
+
+#[derive(Copy, Clone, PartialEq)]
+enum HistoryDirection {
+    Back,
+    Forward,
+}
 
 pub fn show(
     ui: &mut Ui,
     lang_string: &LangString,
     path_manager: &mut PathManager,
     search_manager: &mut SearchManager,
+    config_manager: &mut ConfigManager,
 ) {
+    handle_navigation_input(ui, path_manager);
+
     ui.horizontal(|ui| {
-        if ui.button(lang_string.get(LangKeys::GoBack)).clicked()
-            && let Some(new_path) = path_manager.previous_paths.pop()
-        {
-            path_manager
-                .next_paths
-                .push(path_manager.current_path.clone());
-            path_manager.current_path = new_path;
-            path_manager.update_folder_content = true;
+        let back_response = ui.add_enabled(
+            path_manager.history.can_go_back(),
+            egui::Button::new(lang_string.get(LangKeys::GoBack)),
+        );
+        if back_response.clicked() {
+            path_manager.go_back();
         }
+        history_dropdown(ui, &back_response, HistoryDirection::Back, path_manager);
 
-        if ui.button(lang_string.get(LangKeys::GoForward)).clicked()
-            && let Some(new_path) = path_manager.next_paths.pop()
+        let forward_response = ui.add_enabled(
+            path_manager.history.can_go_forward(),
+            egui::Button::new(lang_string.get(LangKeys::GoForward)),
+        );
+        if forward_response.clicked() {
+            path_manager.go_forward();
+        }
+        history_dropdown(ui, &forward_response, HistoryDirection::Forward, path_manager);
+
+        if ui
+            .add_enabled(
+                path_manager.parent_directory().is_some(),
+                egui::Button::new(lang_string.get(LangKeys::GoUp)),
+            )
+            .clicked()
         {
-            path_manager
-                .previous_paths
-                .push(path_manager.current_path.clone());
-            path_manager.current_path = new_path;
-            path_manager.update_folder_content = true;
+            path_manager.go_up();
         }
 
         let response = ui.add(
-            TextEdit::singleline(&mut search_manager.search_query).hint_text("Search for ..."),
+            TextEdit::singleline(&mut search_manager.search_query)
+                .hint_text(lang_string.get(LangKeys::SearchFor)),
         );
         if response.lost_focus() {
             response.ctx.input(|input_state| {
@@ -41,5 +66,164 @@ pub fn show(
                 }
             });
         }
+
+        view_settings_ui(ui, lang_string, path_manager);
+
+        if ui
+            .button("⚙")
+            .on_hover_text(lang_string.get(LangKeys::Settings))
+            .clicked()
+        {
+            config_manager.settings_visible = !config_manager.settings_visible;
+        }
+    });
+}
+
+/// View mode and sort order of the central panel.
+fn view_settings_ui(ui: &mut Ui, lang_string: &LangString, path_manager: &mut PathManager) {
+    let view_settings = &mut path_manager.view_settings;
+    let previous_settings = *view_settings;
+
+    ui.separator();
+
+    ComboBox::from_id_salt("view_mode")
+        .selected_text(view_mode_name(lang_string, view_settings.view_mode))
+        .show_ui(ui, |ui| {
+            for view_mode in [ViewMode::List, ViewMode::Grid] {
+                ui.selectable_value(
+                    &mut view_settings.view_mode,
+                    view_mode,
+                    view_mode_name(lang_string, view_mode),
+                );
+            }
+        });
+
+    ComboBox::from_id_salt("sort_key")
+        .selected_text(sort_key_name(lang_string, view_settings.sort_key))
+        .show_ui(ui, |ui| {
+            for sort_key in SortKey::ALL {
+                ui.selectable_value(
+                    &mut view_settings.sort_key,
+                    sort_key,
+                    sort_key_name(lang_string, sort_key),
+                );
+            }
+        });
+
+    let direction = if view_settings.ascending {
+        "⏶"
+    } else {
+        "⏷"
+    };
+    let direction_tooltip = lang_string.get(if view_settings.ascending {
+        LangKeys::Ascending
+    } else {
+        LangKeys::Descending
+    });
+    if ui.button(direction).on_hover_text(direction_tooltip).clicked() {
+        view_settings.ascending = !view_settings.ascending;
+    }
+
+    if view_settings.sort_key != previous_settings.sort_key
+        || view_settings.ascending != previous_settings.ascending
+    {
+        path_manager.update_folder_content = true;
+    }
+}
+
+pub fn view_mode_name(lang_string: &LangString, view_mode: ViewMode) -> String {
+    lang_string.get(match view_mode {
+        ViewMode::List => LangKeys::ListView,
+        ViewMode::Grid => LangKeys::GridView,
+    })
+}
+
+fn sort_key_name(lang_string: &LangString, sort_key: SortKey) -> String {
+    lang_string.get(match sort_key {
+        SortKey::Name => LangKeys::SortByName,
+        SortKey::Size => LangKeys::SortBySize,
+        SortKey::Modified => LangKeys::SortByModified,
+        SortKey::Extension => LangKeys::SortByExtension,
+    })
+}
+
+/// Mouse back/forward buttons, the keyboard shortcuts go through the CommandRegistry.
+fn handle_navigation_input(ui: &mut Ui, path_manager: &mut PathManager) {
+    let (back, forward) = ui.input(|input_state| {
+        (
+            input_state.pointer.button_pressed(PointerButton::Extra1),
+            input_state.pointer.button_pressed(PointerButton::Extra2),
+        )
     });
+
+    if back {
+        path_manager.go_back();
+    }
+    if forward {
+        path_manager.go_forward();
+    }
+}
+
+/// Right-clicking (or holding down) Back/Forward lists the locations in that direction.
+fn history_dropdown(
+    ui: &mut Ui,
+    button_response: &Response,
+    direction: HistoryDirection,
+    path_manager: &mut PathManager,
+) {
+    let open_id = Id::new(("history_dropdown", direction == HistoryDirection::Back));
+    let mut open = ui.memory(|memory| memory.data.get_temp::<bool>(open_id).unwrap_or(false));
+
+    let long_pressed = button_response.is_pointer_button_down_on()
+        && ui.input(|input_state| {
+            input_state
+                .pointer
+                .press_start_time()
+                .is_some_and(|start| input_state.time - start > LONG_PRESS_SECONDS)
+        });
+    if button_response.secondary_clicked() || long_pressed {
+        open = true;
+    }
+
+    if open {
+        let entries: Vec<(usize, &Path)> = match direction {
+            HistoryDirection::Back => path_manager.history.back_entries().collect(),
+            HistoryDirection::Forward => path_manager.history.forward_entries().collect(),
+        };
+        let entries: Vec<(usize, PathBuf)> = entries
+            .into_iter()
+            .map(|(idx, path)| (idx, path.to_path_buf()))
+            .collect();
+
+        let mut selected = None;
+        let area_response = Area::new(open_id.with("area"))
+            .order(Order::Foreground)
+            .fixed_pos(button_response.rect.left_bottom())
+            .show(ui.ctx(), |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    for (idx, path) in &entries {
+                        if ui.button(path.to_string_lossy()).clicked() {
+                            selected = Some(*idx);
+                        }
+                    }
+                });
+            })
+            .response;
+
+        if let Some(idx) = selected {
+            path_manager.go_to_history_entry(idx);
+            open = false;
+        }
+
+        // Any click outside of the dropdown (or releasing the long press elsewhere) closes it
+        let clicked_outside = ui.input(|input_state| input_state.pointer.any_click())
+            && !area_response.contains_pointer()
+            && !button_response.contains_pointer();
+        let escape_pressed = ui.input(|input_state| input_state.key_pressed(Key::Escape));
+        if entries.is_empty() || clicked_outside || escape_pressed {
+            open = false;
+        }
+    }
+
+    ui.memory_mut(|memory| memory.data.insert_temp(open_id, open));
 }
@@ -1,6 +1,70 @@
+use crate::actions::Actions;
+use crate::config_manager::Config;
+use crate::lang_string::{LangKeys, LangString};
+use crate::path_manager::PathManager;
+use crate::ui::drop_target::drop_target_ui;
 use eframe::egui;
-use egui::Ui;
+use egui::{Key, TextEdit, Ui};
+use std::path::PathBuf;
 
-pub fn show(ui: &mut Ui) {
-    ui.label("Quick access");
+pub fn show(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    config: &Config,
+    path_manager: &mut PathManager,
+    actions: &mut Actions,
+) {
+    ui.label(lang_string.get(LangKeys::QuickAccess));
+
+    ui.separator();
+    ui.label(lang_string.get(LangKeys::ConnectToServer));
+
+    let response = ui.add(
+        TextEdit::singleline(&mut path_manager.remote_location_query)
+            .hint_text("sftp://user@host/path"),
+    );
+    if response.lost_focus()
+        && ui.input(|input_state| input_state.key_pressed(Key::Enter))
+        && !path_manager.remote_location_query.trim().is_empty()
+    {
+        let location = PathBuf::from(path_manager.remote_location_query.trim());
+        path_manager.update_current_directory(&location);
+        path_manager.update_cursor_icon = true;
+    }
+
+    // Servers stay connected until the user disconnects from them
+    let mut disconnect = None;
+    let mut open = None;
+    for root in path_manager.vfs.remote_roots() {
+        ui.horizontal(|ui| {
+            let response = ui.link(root.to_string_lossy());
+            if response.clicked() {
+                open = Some(root.to_path_buf());
+            }
+            drop_target_ui(
+                ui,
+                &response,
+                lang_string,
+                config,
+                &path_manager.vfs,
+                actions,
+                root,
+            );
+
+            if ui
+                .small_button("x")
+                .on_hover_text(lang_string.get(LangKeys::Disconnect))
+                .clicked()
+            {
+                disconnect = Some(root.to_path_buf());
+            }
+        });
+    }
+
+    if let Some(root) = open {
+        path_manager.update_current_directory(&root);
+    }
+    if let Some(root) = disconnect {
+        path_manager.vfs.disconnect(&root);
+    }
 }
@@ -0,0 +1,80 @@
+use crate::actions::select_action::SelectAction;
+use crate::lang_string::{LangKeys, LangString};
+use crate::path_manager::PathManager;
+use eframe::egui;
+use egui::{Align2, Context, Id, Key, Modifiers, TextEdit, Window};
+
+/// "Select by pattern" window, opened by setting `select_action.select_pattern`.
+pub fn show(
+    ctx: &Context,
+    lang_string: &LangString,
+    select_action: &mut SelectAction,
+    path_manager: &PathManager,
+) {
+    let Some(mut pattern) = select_action.select_pattern.take() else {
+        return;
+    };
+
+    let (enter, escape) = ctx.input_mut(|input_state| {
+        (
+            input_state.consume_key(Modifiers::NONE, Key::Enter),
+            input_state.consume_key(Modifiers::NONE, Key::Escape),
+        )
+    });
+    if escape {
+        return;
+    }
+
+    let no_match_id = Id::new("select_pattern_no_match");
+    let mut confirmed = enter;
+    let mut cancelled = false;
+
+    Window::new(lang_string.get(LangKeys::SelectByPattern))
+        .id(Id::new("select_pattern"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .show(ctx, |ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut pattern)
+                    .hint_text(lang_string.get(LangKeys::SelectPatternHint))
+                    .lock_focus(true),
+            );
+            response.request_focus();
+            if response.changed() {
+                ui.memory_mut(|memory| memory.data.remove::<bool>(no_match_id));
+            }
+
+            if ui.memory(|memory| memory.data.get_temp::<bool>(no_match_id).unwrap_or(false)) {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    lang_string.get(LangKeys::NoFilesMatch),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(lang_string.get(LangKeys::Select)).clicked() {
+                    confirmed = true;
+                }
+                if ui.button(lang_string.get(LangKeys::Cancel)).clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if cancelled {
+        ctx.memory_mut(|memory| memory.data.remove::<bool>(no_match_id));
+        return;
+    }
+
+    if confirmed && !pattern.trim().is_empty() {
+        if select_action.select_by_pattern(&pattern, &path_manager.directory_content) > 0 {
+            ctx.memory_mut(|memory| memory.data.remove::<bool>(no_match_id));
+            return;
+        }
+
+        ctx.memory_mut(|memory| memory.data.insert_temp(no_match_id, true));
+    }
+
+    select_action.select_pattern = Some(pattern);
+}
@@ -0,0 +1,168 @@
+use crate::config_manager::{Config, ConfigManager, SizeUnits, Theme};
+use crate::lang_string::{LangKeys, LangString, Languages};
+use crate::ui::additional_info_panel::format_date_time;
+use crate::ui::navigation_bar_panel::view_mode_name;
+use crate::view_settings::ViewMode;
+use eframe::egui;
+use egui::{ComboBox, Context, Id, Slider, TextEdit, Window};
+use std::time::SystemTime;
+
+/// Returns true if the config was changed this frame and must be applied and saved.
+pub fn show(
+    ctx: &Context,
+    lang_string: &LangString,
+    config_manager: &mut ConfigManager,
+    icon_themes: &[String],
+) -> bool {
+    if !config_manager.settings_visible {
+        return false;
+    }
+
+    let previous_config = config_manager.config.clone();
+    let config = &mut config_manager.config;
+    let mut visible = true;
+
+    Window::new(lang_string.get(LangKeys::Settings))
+        .id(Id::new("settings_window"))
+        .open(&mut visible)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("settings_grid")
+                .num_columns(2)
+                .spacing([20.0, 8.0])
+                .show(ui, |ui| {
+                    settings_grid_ui(ui, lang_string, config, icon_themes)
+                });
+        });
+
+    config_manager.settings_visible = visible;
+
+    config_manager.config != previous_config
+}
+
+fn settings_grid_ui(
+    ui: &mut egui::Ui,
+    lang_string: &LangString,
+    config: &mut Config,
+    icon_themes: &[String],
+) {
+    ui.label(lang_string.get(LangKeys::Theme));
+    ComboBox::from_id_salt("settings_theme")
+        .selected_text(theme_name(lang_string, config.theme))
+        .show_ui(ui, |ui| {
+            for theme in [Theme::Dark, Theme::Light, Theme::System] {
+                ui.selectable_value(&mut config.theme, theme, theme_name(lang_string, theme));
+            }
+        });
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::Language));
+    ComboBox::from_id_salt("settings_language")
+        .selected_text(config.language.native_name())
+        .show_ui(ui, |ui| {
+            for language in Languages::ALL {
+                ui.selectable_value(&mut config.language, language, language.native_name());
+            }
+        });
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::IconTheme));
+    ComboBox::from_id_salt("settings_icon_theme")
+        .selected_text(icon_theme_name(lang_string, &config.icon_theme))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(
+                &mut config.icon_theme,
+                String::new(),
+                lang_string.get(LangKeys::BundledIcons),
+            );
+            for icon_theme in icon_themes {
+                ui.selectable_value(&mut config.icon_theme, icon_theme.clone(), icon_theme);
+            }
+        });
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::FontSize));
+    ui.add(Slider::new(&mut config.font_size, 10.0..=24.0).step_by(1.0));
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::RowHeight));
+    ui.add(Slider::new(&mut config.row_height, 20.0..=60.0).step_by(1.0));
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::DefaultView));
+    ComboBox::from_id_salt("settings_default_view")
+        .selected_text(view_mode_name(lang_string, config.default_view))
+        .show_ui(ui, |ui| {
+            for view_mode in [ViewMode::List, ViewMode::Grid] {
+                ui.selectable_value(
+                    &mut config.default_view,
+                    view_mode,
+                    view_mode_name(lang_string, view_mode),
+                );
+            }
+        });
+    ui.end_row();
+
+    ui.label("");
+    ui.checkbox(&mut config.show_hidden, lang_string.get(LangKeys::ShowHiddenFiles));
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::Confirmations));
+    ui.vertical(|ui| {
+        ui.checkbox(
+            &mut config.confirmations.move_files,
+            lang_string.get(LangKeys::AskBeforeMoving),
+        );
+        ui.checkbox(
+            &mut config.confirmations.delete_files,
+            lang_string.get(LangKeys::AskBeforeDeleting),
+        );
+    });
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::DateFormat));
+    ui.vertical(|ui| {
+        ui.add(TextEdit::singleline(&mut config.date_format).desired_width(160.0));
+        ui.small(format_date_time(SystemTime::now(), &config.date_format));
+    });
+    ui.end_row();
+
+    ui.label(lang_string.get(LangKeys::SizeUnits));
+    ComboBox::from_id_salt("settings_size_units")
+        .selected_text(size_units_name(lang_string, config.size_units))
+        .show_ui(ui, |ui| {
+            for size_units in [SizeUnits::Binary, SizeUnits::Decimal] {
+                ui.selectable_value(
+                    &mut config.size_units,
+                    size_units,
+                    size_units_name(lang_string, size_units),
+                );
+            }
+        });
+    ui.end_row();
+
+}
+
+fn theme_name(lang_string: &LangString, theme: Theme) -> String {
+    lang_string.get(match theme {
+        Theme::Dark => LangKeys::ThemeDark,
+        Theme::Light => LangKeys::ThemeLight,
+        Theme::System => LangKeys::ThemeSystem,
+    })
+}
+
+fn icon_theme_name(lang_string: &LangString, icon_theme: &str) -> String {
+    if icon_theme.is_empty() {
+        lang_string.get(LangKeys::BundledIcons)
+    } else {
+        icon_theme.to_string()
+    }
+}
+
+fn size_units_name(lang_string: &LangString, size_units: SizeUnits) -> String {
+    lang_string.get(match size_units {
+        SizeUnits::Binary => LangKeys::SizeUnitsBinary,
+        SizeUnits::Decimal => LangKeys::SizeUnitsDecimal,
+    })
+}
@@ -0,0 +1,361 @@
+use crate::actions::select_action::SelectAction;
+use crate::actions::transfer_action::FilesPayload;
+use crate::lang_string::{LangKeys, LangString};
+use crate::path_manager::PathManager;
+use crate::terminal_manager;
+use crate::terminal_manager::{Terminal, TerminalManager};
+use eframe::egui;
+use egui::text::LayoutJob;
+use egui::{
+    Color32, Event, EventFilter, FontId, Rect, Sense, Stroke, StrokeKind, TextFormat, TextStyle,
+    Ui, Vec2,
+};
+use std::path::PathBuf;
+
+/// Tabs of the terminals above the screen of the one shown.
+pub fn show(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    terminal_manager: &mut TerminalManager,
+    path_manager: &PathManager,
+    select_action: &SelectAction,
+) {
+    ui.horizontal(|ui| {
+        let mut close = None;
+        for (idx, terminal) in terminal_manager.terminals.iter().enumerate() {
+            let mut label = format!("{} {}", lang_string.get(LangKeys::Terminal), terminal.id);
+            if terminal.exited {
+                label.push_str(" ⏹");
+            }
+
+            if ui
+                .selectable_label(idx == terminal_manager.active, label)
+                .clicked()
+            {
+                terminal_manager.active = idx;
+            }
+            if ui
+                .small_button("✖")
+                .on_hover_text(lang_string.get(LangKeys::CloseTerminal))
+                .clicked()
+            {
+                close = Some(idx);
+            }
+        }
+        if let Some(idx) = close {
+            terminal_manager.close_terminal(idx);
+        }
+
+        if ui
+            .button("+")
+            .on_hover_text(lang_string.get(LangKeys::NewTerminal))
+            .clicked()
+        {
+            terminal_manager.open_terminal(ui.ctx(), &path_manager.current_path, &path_manager.vfs);
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    !select_action.files.is_empty(),
+                    egui::Button::new(lang_string.get(LangKeys::InsertSelection)),
+                )
+                .clicked()
+            {
+                let mut paths: Vec<PathBuf> = select_action.files.iter().cloned().collect();
+                paths.sort();
+                terminal_manager.insert_paths(&paths);
+            }
+        });
+    });
+
+    if let Some(error) = &terminal_manager.error {
+        ui.colored_label(
+            ui.visuals().error_fg_color,
+            format!(
+                "{}: {}",
+                lang_string.get(LangKeys::TerminalStartError),
+                error
+            ),
+        );
+    }
+
+    ui.separator();
+
+    let mut dropped = None;
+    if let Some(terminal) = terminal_manager.active_terminal() {
+        dropped = terminal_ui(ui, lang_string, terminal);
+    }
+    if let Some(paths) = dropped {
+        terminal_manager.insert_paths(&paths);
+    }
+}
+
+/// Screen of the terminal, typing goes to the shell once it was clicked.
+/// Returns the files dropped on it, their paths are typed on the command line.
+fn terminal_ui(
+    ui: &mut Ui,
+    lang_string: &LangString,
+    terminal: &mut Terminal,
+) -> Option<Vec<PathBuf>> {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let (char_width, row_height) =
+        ui.fonts(|fonts| (fonts.glyph_width(&font_id, 'M'), fonts.row_height(&font_id)));
+
+    let (rect, response) = ui.allocate_exact_size(ui.available_size(), Sense::click());
+    let rows = (rect.height() / row_height).floor().max(1.0) as u16;
+    let cols = (rect.width() / char_width).floor().max(1.0) as u16;
+    terminal.resize(rows, cols);
+
+    let id = response.id;
+    if response.clicked() {
+        response.request_focus();
+    }
+
+    let focused = response.has_focus();
+    if focused {
+        // Tab, arrows and Escape belong to the shell, not to the focus navigation of egui
+        ui.memory_mut(|memory| {
+            memory.set_focus_lock_filter(
+                id,
+                EventFilter {
+                    tab: true,
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    escape: true,
+                },
+            )
+        });
+        input_ui(ui, terminal);
+    }
+
+    if response.hovered() {
+        let scroll = ui.input(|input_state| input_state.raw_scroll_delta.y);
+        let lines = (scroll / row_height).round() as i32;
+        if lines != 0 {
+            terminal.scroll(lines);
+        }
+    }
+
+    if ui.is_rect_visible(rect) {
+        ui.painter()
+            .rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        paint_screen(
+            ui,
+            terminal,
+            rect,
+            &font_id,
+            Vec2::new(char_width, row_height),
+            focused,
+        );
+
+        if terminal.exited {
+            ui.painter().text(
+                rect.right_top() + Vec2::new(-8.0, 4.0),
+                egui::Align2::RIGHT_TOP,
+                lang_string.get(LangKeys::TerminalExited),
+                font_id.clone(),
+                ui.visuals().warn_fg_color,
+            );
+        }
+    }
+
+    if response.dnd_hover_payload::<FilesPayload>().is_some() {
+        ui.painter()
+            .rect_stroke(rect, 2.0, ui.visuals().selection.stroke, StrokeKind::Inside);
+    }
+
+    response
+        .dnd_release_payload::<FilesPayload>()
+        .map(|payload| payload.files.clone())
+}
+
+/// Sends the keys typed this frame to the shell.
+fn input_ui(ui: &Ui, terminal: &mut Terminal) {
+    let (application_cursor, bracketed_paste) = match terminal.parser.lock() {
+        Ok(parser) => (
+            parser.screen().application_cursor(),
+            parser.screen().bracketed_paste(),
+        ),
+        Err(_) => return,
+    };
+
+    let events = ui.input(|input_state| input_state.events.clone());
+    let mut bytes = Vec::new();
+    for event in events {
+        match event {
+            Event::Text(text) => bytes.extend(text.as_bytes()),
+            Event::Paste(text) => {
+                if bracketed_paste {
+                    bytes.extend(b"\x1b[200~");
+                    bytes.extend(text.as_bytes());
+                    bytes.extend(b"\x1b[201~");
+                } else {
+                    bytes.extend(text.as_bytes());
+                }
+            }
+            // Ctrl+V also comes as a Paste event
+            Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } if !(modifiers.ctrl && key == egui::Key::V) => {
+                if let Some(key_bytes) =
+                    terminal_manager::key_bytes(key, modifiers, application_cursor)
+                {
+                    bytes.extend(key_bytes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !bytes.is_empty() {
+        terminal.write(&bytes);
+        terminal.scroll_to_bottom();
+    }
+}
+
+/// Draws the cells row by row, consecutive cells with the same colors share a text section.
+fn paint_screen(
+    ui: &Ui,
+    terminal: &Terminal,
+    rect: Rect,
+    font_id: &FontId,
+    cell_size: Vec2,
+    focused: bool,
+) {
+    let Ok(parser) = terminal.parser.lock() else {
+        return;
+    };
+    let screen = parser.screen();
+    let (rows, cols) = screen.size();
+    let default_color = ui.visuals().text_color();
+
+    for row in 0..rows {
+        let mut job = LayoutJob::default();
+        let mut text = String::new();
+        let mut format = TextFormat::default();
+
+        for col in 0..cols {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+
+            let mut foreground = cell_color(cell.fgcolor()).unwrap_or(default_color);
+            let mut background = cell_color(cell.bgcolor()).unwrap_or(Color32::TRANSPARENT);
+            if cell.inverse() {
+                (foreground, background) = (
+                    if background == Color32::TRANSPARENT {
+                        ui.visuals().extreme_bg_color
+                    } else {
+                        background
+                    },
+                    foreground,
+                );
+            }
+            let cell_format = TextFormat {
+                font_id: font_id.clone(),
+                color: foreground,
+                background,
+                italics: cell.italic(),
+                underline: if cell.underline() {
+                    Stroke::new(1.0, foreground)
+                } else {
+                    Stroke::NONE
+                },
+                ..Default::default()
+            };
+
+            if cell_format != format && !text.is_empty() {
+                job.append(&text, 0.0, format.clone());
+                text.clear();
+            }
+            format = cell_format;
+
+            let contents = cell.contents();
+            text.push_str(if contents.is_empty() {
+                " "
+            } else {
+                contents.as_str()
+            });
+        }
+        if !text.is_empty() {
+            job.append(&text, 0.0, format);
+        }
+
+        let galley = ui.fonts(|fonts| fonts.layout_job(job));
+        let position = rect.left_top() + Vec2::new(0.0, row as f32 * cell_size.y);
+        ui.painter().galley(position, galley, default_color);
+    }
+
+    if !screen.hide_cursor() && screen.scrollback() == 0 {
+        let (row, col) = screen.cursor_position();
+        let cursor_rect = Rect::from_min_size(
+            rect.left_top() + Vec2::new(col as f32 * cell_size.x, row as f32 * cell_size.y),
+            cell_size,
+        );
+        if focused {
+            ui.painter()
+                .rect_filled(cursor_rect, 0.0, default_color.gamma_multiply(0.6));
+        } else {
+            ui.painter().rect_stroke(
+                cursor_rect,
+                0.0,
+                Stroke::new(1.0, default_color),
+                StrokeKind::Inside,
+            );
+        }
+    }
+}
+
+/// None for the default color of the terminal.
+fn cell_color(color: vt100::Color) -> Option<Color32> {
+    match color {
+        vt100::Color::Default => None,
+        vt100::Color::Idx(idx) => Some(indexed_color(idx)),
+        vt100::Color::Rgb(red, green, blue) => Some(Color32::from_rgb(red, green, blue)),
+    }
+}
+
+/// xterm 256 colors: 16 ANSI colors, a 6x6x6 color cube, then 24 grays.
+fn indexed_color(idx: u8) -> Color32 {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 49, 49),
+        (13, 188, 121),
+        (229, 229, 16),
+        (36, 114, 200),
+        (188, 63, 188),
+        (17, 168, 205),
+        (229, 229, 229),
+        (102, 102, 102),
+        (241, 76, 76),
+        (35, 209, 139),
+        (245, 245, 67),
+        (59, 142, 234),
+        (214, 112, 214),
+        (41, 184, 219),
+        (255, 255, 255),
+    ];
+
+    match idx {
+        0..=15 => {
+            let (red, green, blue) = ANSI[idx as usize];
+            Color32::from_rgb(red, green, blue)
+        }
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let idx = idx - 16;
+            Color32::from_rgb(level(idx / 36), level(idx / 6 % 6), level(idx % 6))
+        }
+        _ => {
+            let gray = 8 + (idx - 232) * 10;
+            Color32::from_rgb(gray, gray, gray)
+        }
+    }
+}
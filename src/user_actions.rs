@@ -0,0 +1,168 @@
+use crate::mime_manager::MimeManager;
+use crate::terminal_manager::shell_quote;
+use crate::vfs::VfsRouter;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Shell command defined by the user in the config, run on the selected files.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserAction {
+    pub name: String,
+
+    /// `%f` becomes the selected files, `%d` the current folder, both quoted, and `%%` a `%`.
+    pub command: String,
+
+    /// MIME types of the files the action applies to, `image/*` matches every image.
+    /// Empty for every file.
+    pub mime_types: Vec<String>,
+
+    /// Typed in the terminal panel instead of running in the background.
+    pub in_terminal: bool,
+}
+
+impl UserAction {
+    /// Whether the action can run on `files`, each one given with its MIME types
+    /// (the type and the ones it inherits from).
+    fn applies_to(&self, files: &[Vec<String>]) -> bool {
+        if files.is_empty() {
+            return !self.command.contains("%f") && self.mime_types.is_empty();
+        }
+
+        self.mime_types.is_empty()
+            || files.iter().all(|file_mime_types| {
+                file_mime_types.iter().any(|mime_type| {
+                    self.mime_types
+                        .iter()
+                        .any(|filter| mime_type_matches(filter, mime_type))
+                })
+            })
+    }
+}
+
+/// `image/*` matches every image, other filters must be equal.
+fn mime_type_matches(filter: &str, mime_type: &str) -> bool {
+    match filter.strip_suffix("/*") {
+        Some(media) => mime_type.split('/').next() == Some(media),
+        None => filter == mime_type,
+    }
+}
+
+/// Indices of the actions which can run on `files` from `directory`, in the order of the config.
+pub fn applicable(
+    user_actions: &[UserAction],
+    files: &[PathBuf],
+    directory: &Path,
+    vfs: &VfsRouter,
+    mime_manager: &MimeManager,
+) -> Vec<usize> {
+    // Commands run on local paths, archive entries and remote files have none
+    if files
+        .iter()
+        .map(PathBuf::as_path)
+        .chain([directory])
+        .any(|path| !vfs.is_local(path))
+    {
+        return Vec::new();
+    }
+
+    let mime_types: Vec<Vec<String>> = files
+        .iter()
+        .map(|file| mime_manager.with_parents(&mime_manager.mime_type(file, vfs)))
+        .collect();
+
+    user_actions
+        .iter()
+        .enumerate()
+        .filter(|(_, user_action)| user_action.applies_to(&mime_types))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Replaces the placeholders of `command`.
+pub fn expand(command: &str, files: &[PathBuf], directory: &Path) -> String {
+    let mut expanded = String::new();
+    let mut chars = command.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            expanded.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('f') => {
+                let quoted: Vec<String> = files.iter().map(|file| shell_quote(file)).collect();
+                expanded.push_str(&quoted.join(" "));
+            }
+            Some('d') => expanded.push_str(&shell_quote(directory)),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    expanded
+}
+
+/// `command` run by the shell of the platform.
+pub fn shell_command(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn expands_placeholders() {
+        let files = vec![PathBuf::from("/home/a b.png"), PathBuf::from("/home/c.png")];
+        assert_eq!(
+            expand(
+                "convert %f -quality 90%% %d/out.jpg",
+                &files,
+                Path::new("/home")
+            ),
+            "convert '/home/a b.png' '/home/c.png' -quality 90% '/home'/out.jpg"
+        );
+        assert_eq!(expand("echo %x %", &[], Path::new("/")), "echo %x %");
+    }
+
+    #[test]
+    fn filters_by_mime_type() {
+        let user_action = UserAction {
+            name: String::from("Resize"),
+            command: String::from("mogrify -resize 50% %f"),
+            mime_types: vec![String::from("image/*")],
+            in_terminal: false,
+        };
+        let png = vec![String::from("image/png")];
+        let text = vec![String::from("text/x-python"), String::from("text/plain")];
+
+        assert!(user_action.applies_to(&[png.clone()]));
+        assert!(!user_action.applies_to(&[png, text.clone()]));
+        assert!(!user_action.applies_to(&[]));
+
+        let any_text = UserAction {
+            mime_types: vec![String::from("text/plain")],
+            ..user_action
+        };
+        assert!(any_text.applies_to(&[text]));
+    }
+}
@@ -0,0 +1,407 @@
+use crate::archive_manager;
+use crate::vfs::archive_vfs::ArchiveVfs;
+use crate::vfs::local_vfs::LocalVfs;
+use crate::vfs::sftp_vfs::{SftpLocation, SftpVfs, UnknownHostKey};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::thread;
+use std::time::SystemTime;
+
+pub mod archive_vfs;
+pub mod local_vfs;
+pub mod sftp_vfs;
+
+/// Backend independent subset of file metadata.
+#[derive(Clone, Debug)]
+pub struct VfsMetadata {
+    pub is_dir: bool,
+    pub len: u64,
+    pub readonly: bool,
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<fs::Metadata> for VfsMetadata {
+    fn from(metadata: fs::Metadata) -> Self {
+        Self {
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            readonly: metadata.permissions().readonly(),
+            created: metadata.created().ok(),
+            accessed: metadata.accessed().ok(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+/// A place files can be browsed in: the local disk, an archive, a remote server...
+///
+/// Every path handed to a backend is the full path displayed by the application,
+/// backends mounted on top of another location strip their mount point themselves.
+pub trait Vfs {
+    /// Paths of the entries placed directly inside `dir`.
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>>;
+
+    fn stat(&self, path: &Path) -> io::Result<VfsMetadata>;
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>>;
+
+    /// Creates (or truncates) a file and opens it for writing.
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write>>;
+
+    fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Removes a file, or a directory with everything inside it.
+    fn remove(&self, path: &Path) -> io::Result<()>;
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.stat(path).map(|metadata| metadata.is_dir).unwrap_or(false)
+    }
+
+    /// Target stored inside a symbolic link, as written (it may be relative).
+    /// None when `path` is not a link or the backend has no links.
+    fn read_link(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    /// Creates a symbolic link at `link` storing `target` as written.
+    fn symlink(&self, _target: &Path, link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot create the link {:?} here", link),
+        ))
+    }
+
+    /// `dir/name.ext`, or `dir/name (2).ext` etc. if the name is already taken.
+    fn unused_path(&self, dir: &Path, name: &str) -> PathBuf {
+        // Dangling links can not be stat'ed but still take the name
+        let is_free =
+            |candidate: &Path| self.stat(candidate).is_err() && self.read_link(candidate).is_none();
+
+        let candidate = dir.join(name);
+        if is_free(&candidate) {
+            return candidate;
+        }
+
+        // Hidden files such as ".bashrc" have no extension
+        let (stem, extension) = match name.rfind('.') {
+            Some(dot_idx) if dot_idx > 0 => name.split_at(dot_idx),
+            _ => (name, ""),
+        };
+
+        (2..)
+            .map(|idx| dir.join(format!("{} ({}){}", stem, idx, extension)))
+            .find(|candidate| is_free(candidate))
+            .unwrap_or(candidate)
+    }
+}
+
+/// Which backend of a `VfsRouter` serves a path.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum BackendId {
+    Local,
+    Archive,
+    /// Index inside `VfsRouter::remotes`.
+    Remote(usize),
+}
+
+/// Dispatches every call to the backend responsible for the path.
+pub struct VfsRouter {
+    local: LocalVfs,
+
+    /// Archive being browsed, mounted on top of its file.
+    archive: Option<ArchiveVfs>,
+
+    /// Servers connected to during this session, kept open so
+    /// going back to them does not require a new handshake.
+    remotes: Vec<SftpVfs>,
+
+    /// Server being connected to on a background thread, with its root.
+    connecting: Option<(PathBuf, Receiver<io::Result<SftpVfs>>)>,
+
+    /// Fingerprints of unknown servers the user chose to trust, by server root.
+    trusted_keys: HashMap<PathBuf, String>,
+
+    /// Server whose key is waiting for the user to trust it.
+    pub unknown_host_key: Option<UnknownHostKey>,
+}
+
+impl VfsRouter {
+    pub fn new() -> Self {
+        Self {
+            local: LocalVfs,
+            archive: None,
+            remotes: Vec::new(),
+            connecting: None,
+            trusted_keys: HashMap::new(),
+            unknown_host_key: None,
+        }
+    }
+
+    pub fn backend(&self, path: &Path) -> &dyn Vfs {
+        match (self.backend_id(path), &self.archive) {
+            (BackendId::Remote(idx), _) => &self.remotes[idx],
+            (BackendId::Archive, Some(archive)) => archive,
+            _ => &self.local,
+        }
+    }
+
+    fn backend_id(&self, path: &Path) -> BackendId {
+        if let Some(idx) = self.remotes.iter().position(|remote| remote.serves(path)) {
+            return BackendId::Remote(idx);
+        }
+
+        match &self.archive {
+            Some(archive) if archive.serves(path) => BackendId::Archive,
+            _ => BackendId::Local,
+        }
+    }
+
+    /// Router for work running on a background thread, which can not share the connections
+    /// of the UI. Mounts the backends of `paths`, connecting to servers blocks.
+    pub fn mounted_for(
+        paths: &[PathBuf],
+        trusted_keys: HashMap<PathBuf, String>,
+    ) -> io::Result<Self> {
+        let mut router = Self {
+            trusted_keys,
+            ..Self::new()
+        };
+
+        for path in paths {
+            if !sftp_vfs::is_sftp_path(path) {
+                router.mount_for(path)?;
+                continue;
+            }
+
+            if router.remotes.iter().any(|remote| remote.serves(path)) {
+                continue;
+            }
+            let location = Self::parse_location(path)?;
+            let trusted_fingerprint = router.trusted_keys.get(&location.root());
+            let remote = SftpVfs::connect(&location, trusted_fingerprint.map(String::as_str))?;
+            router.remotes.push(remote);
+        }
+
+        Ok(router)
+    }
+
+    /// Fingerprints trusted during this session, for `mounted_for`.
+    pub fn trusted_keys(&self) -> HashMap<PathBuf, String> {
+        self.trusted_keys.clone()
+    }
+
+    /// Makes sure the backend needed to browse `path` is mounted.
+    ///
+    /// Only one archive is kept mounted at a time, it is replaced once another one is opened.
+    /// Servers are connected to in the background, this fails with `WouldBlock` meanwhile.
+    pub fn mount_for(&mut self, path: &Path) -> io::Result<()> {
+        if sftp_vfs::is_sftp_path(path) {
+            return self.connect(path);
+        }
+
+        let Some((archive, _)) = archive_manager::split_archive_path(path) else {
+            return Ok(());
+        };
+
+        if self
+            .archive
+            .as_ref()
+            .is_some_and(|mounted| mounted.archive_path() == archive)
+        {
+            return Ok(());
+        }
+
+        self.archive = None;
+        self.archive = Some(ArchiveVfs::mount(&archive)?);
+        Ok(())
+    }
+
+    fn parse_location(path: &Path) -> io::Result<SftpLocation> {
+        SftpLocation::parse(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not a valid sftp://user@host/path location", path),
+            )
+        })
+    }
+
+    /// Starts connecting to the server of `path`, or picks up the connection once it is open.
+    fn connect(&mut self, path: &Path) -> io::Result<()> {
+        if self.remotes.iter().any(|remote| remote.serves(path)) {
+            return Ok(());
+        }
+
+        let location = Self::parse_location(path)?;
+        let root = location.root();
+        let host = location.host.clone();
+        let connecting =
+            || io::Error::new(io::ErrorKind::WouldBlock, format!("connecting to {}", host));
+
+        if let Some((connecting_root, receiver)) = &self.connecting
+            && *connecting_root == root
+        {
+            let result = match receiver.try_recv() {
+                Err(TryRecvError::Empty) => return Err(connecting()),
+                Err(TryRecvError::Disconnected) => Err(io::Error::other("the connection was lost")),
+                Ok(result) => result,
+            };
+            self.connecting = None;
+
+            return match result {
+                Ok(remote) => {
+                    self.remotes.push(remote);
+                    Ok(())
+                }
+                Err(err) => {
+                    if let Some(unknown_host_key) = UnknownHostKey::from_error(&err) {
+                        self.unknown_host_key = Some(unknown_host_key.clone());
+                    }
+                    Err(err)
+                }
+            };
+        }
+
+        // A host which does not answer would freeze the UI
+        let trusted_fingerprint = self.trusted_keys.get(&root).cloned();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(SftpVfs::connect(&location, trusted_fingerprint.as_deref()));
+        });
+
+        self.connecting = Some((root, receiver));
+        Err(connecting())
+    }
+
+    /// Trusts the fingerprint of `unknown_host_key`, the next connection to it succeeds.
+    pub fn trust_host_key(&mut self) {
+        if let Some(unknown_host_key) = self.unknown_host_key.take() {
+            self.trusted_keys
+                .insert(unknown_host_key.root, unknown_host_key.fingerprint);
+        }
+    }
+
+    /// Is `path` on the disk of this machine (not inside an archive, nor on a server)?
+    pub fn is_local(&self, path: &Path) -> bool {
+        !sftp_vfs::is_sftp_path(path) && self.archive(path).is_none()
+    }
+
+    /// Roots of the servers Rocket is connected to.
+    pub fn remote_roots(&self) -> impl Iterator<Item = &Path> {
+        self.remotes.iter().map(|remote| remote.root())
+    }
+
+    pub fn disconnect(&mut self, root: &Path) {
+        self.remotes.retain(|remote| remote.root() != root);
+    }
+
+    /// Archive mounted on top of `path`, if `path` points inside one.
+    pub fn archive(&self, path: &Path) -> Option<&ArchiveVfs> {
+        self.archive
+            .as_ref()
+            .filter(|archive| archive.serves(path))
+    }
+
+    /// Forgets cached listings, so changes made on disk are picked up.
+    pub fn refresh(&mut self) {
+        self.archive = None;
+        for remote in &self.remotes {
+            remote.refresh();
+        }
+    }
+
+    /// Copies `from` (recursively) to `to`, the two may live on different backends.
+    pub fn copy(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let source = self.backend(from);
+        let destination = self.backend(to);
+
+        if source.stat(from)?.is_dir {
+            destination.create_dir(to)?;
+            for child in source.list(from)? {
+                if let Some(file_name) = child.file_name() {
+                    self.copy(&child, &to.join(file_name))?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        let mut reader = source.open(from)?;
+        let mut writer = destination.create(to)?;
+        io::copy(&mut reader, &mut writer)?;
+        writer.flush()
+    }
+
+    /// Are `a` and `b` served by the same backend, so one can be renamed to the other?
+    pub fn same_backend(&self, a: &Path, b: &Path) -> bool {
+        self.backend_id(a) == self.backend_id(b)
+    }
+
+    /// Target of the symbolic link `path`, relative targets are resolved against its folder.
+    pub fn link_target(&self, path: &Path) -> Option<PathBuf> {
+        let target = self.read_link(path)?;
+        match path.parent() {
+            Some(parent) if target.is_relative() => Some(parent.join(target)),
+            _ => Some(target),
+        }
+    }
+
+    /// A symbolic link whose target does not exist.
+    pub fn is_dangling(&self, path: &Path) -> bool {
+        self.read_link(path).is_some() && self.stat(path).is_err()
+    }
+}
+
+impl Vfs for VfsRouter {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        // The archive file itself is listed as the root of the archive
+        match &self.archive {
+            Some(archive) if archive.archive_path() == dir => archive.list(dir),
+            _ => self.backend(dir).list(dir),
+        }
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<VfsMetadata> {
+        self.backend(path).stat(path)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        self.backend(path).open(path)
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write>> {
+        self.backend(path).create(path)
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.backend(path).create_dir(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.backend(from).rename(from, to)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.backend(path).remove(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.backend(path).is_dir(path)
+    }
+
+    fn read_link(&self, path: &Path) -> Option<PathBuf> {
+        self.backend(path).read_link(path)
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.backend(link).symlink(target, link)
+    }
+}
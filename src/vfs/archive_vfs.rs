@@ -0,0 +1,106 @@
+use crate::archive_manager;
+use crate::archive_manager::ArchiveIndex;
+use crate::vfs::{Vfs, VfsMetadata};
+use std::io;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Read-only view of an archive, browsed as if it was a folder.
+///
+/// Paths inside an archive are virtual: `/home/user/file.zip/dir` is the `dir`
+/// folder stored in `file.zip`.
+pub struct ArchiveVfs {
+    index: ArchiveIndex,
+}
+
+impl ArchiveVfs {
+    pub fn mount(archive: &Path) -> io::Result<Self> {
+        Ok(Self {
+            index: ArchiveIndex::read(archive)?,
+        })
+    }
+
+    /// The archive file on disk.
+    pub fn archive_path(&self) -> &Path {
+        &self.index.archive
+    }
+
+    /// Does `path` point inside the archive (the archive file itself excluded)?
+    pub fn serves(&self, path: &Path) -> bool {
+        self.index.contains(path)
+    }
+
+    /// Path of `path` relative to the archive root.
+    pub fn inner_path<'p>(&self, path: &'p Path) -> Option<&'p Path> {
+        self.index.inner_path(path)
+    }
+
+    fn read_only_error() -> io::Error {
+        io::Error::new(io::ErrorKind::ReadOnlyFilesystem, "archives are read-only")
+    }
+
+    fn not_found_error(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{:?} does not exist inside the archive", path),
+        )
+    }
+}
+
+impl Vfs for ArchiveVfs {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let inner = self.inner_path(dir).unwrap_or(Path::new(""));
+        if !inner.as_os_str().is_empty() && self.index.entry(inner).is_none() {
+            return Err(Self::not_found_error(inner));
+        }
+
+        Ok(self
+            .index
+            .children(inner)
+            .iter()
+            .map(|entry| self.index.archive.join(&entry.path))
+            .collect())
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<VfsMetadata> {
+        let inner = self.inner_path(path).unwrap_or(Path::new(""));
+        let entry = self
+            .index
+            .entry(inner)
+            .ok_or_else(|| Self::not_found_error(inner))?;
+
+        Ok(VfsMetadata {
+            is_dir: entry.is_dir,
+            len: entry.size,
+            readonly: true,
+            created: None,
+            accessed: None,
+            modified: entry.modified,
+        })
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        let inner = self
+            .inner_path(path)
+            .ok_or_else(|| Self::not_found_error(path))?;
+        let bytes = archive_manager::read_entry(&self.index.archive, inner)?;
+
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+
+    fn create(&self, _path: &Path) -> io::Result<Box<dyn Write>> {
+        Err(Self::read_only_error())
+    }
+
+    fn create_dir(&self, _path: &Path) -> io::Result<()> {
+        Err(Self::read_only_error())
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Err(Self::read_only_error())
+    }
+
+    fn remove(&self, _path: &Path) -> io::Result<()> {
+        Err(Self::read_only_error())
+    }
+}
@@ -0,0 +1,551 @@
+use crate::vfs::{Vfs, VfsMetadata};
+use ssh2::{CheckResult, HashType, KnownHostFileKind, Session, Sftp};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::env::home_dir;
+use std::fmt;
+use std::io;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+pub const SFTP_SCHEME: &str = "sftp:";
+const DEFAULT_SFTP_PORT: u16 = 22;
+
+/// A server which does not answer is given up on after this long.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Any network operation of an established session.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `sftp://user@host:port/path`, as typed by the user.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SftpLocation {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+
+    /// Absolute path on the remote machine.
+    pub path: PathBuf,
+}
+
+impl SftpLocation {
+    /// Parses a sftp url.
+    ///
+    /// PathBuf collapses `sftp://host` into `sftp:/host` when it is rebuilt from its components,
+    /// so both forms are accepted.
+    pub fn parse(path: &Path) -> Option<Self> {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let rest = path.strip_prefix(SFTP_SCHEME)?.trim_start_matches('/');
+
+        let (authority, remote_path) = match rest.find('/') {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+
+        let (user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host_port)) => (user.to_string(), host_port),
+            None => {
+                let user = env::var("USER").or_else(|_| env::var("USERNAME")).ok()?;
+                (user, authority)
+            }
+        };
+
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (host_port, DEFAULT_SFTP_PORT),
+        };
+
+        if host.is_empty() || user.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            user,
+            host: host.to_string(),
+            port,
+            path: PathBuf::from(remote_path),
+        })
+    }
+
+    /// The `sftp://user@host:port` prefix shared by every path of this server.
+    pub fn root(&self) -> PathBuf {
+        if self.port == DEFAULT_SFTP_PORT {
+            PathBuf::from(format!("{}//{}@{}", SFTP_SCHEME, self.user, self.host))
+        } else {
+            PathBuf::from(format!(
+                "{}//{}@{}:{}",
+                SFTP_SCHEME, self.user, self.host, self.port
+            ))
+        }
+    }
+
+    fn same_server(&self, other: &SftpLocation) -> bool {
+        self.user == other.user && self.host == other.host && self.port == other.port
+    }
+}
+
+pub fn is_sftp_path(path: &Path) -> bool {
+    path.to_string_lossy().starts_with(SFTP_SCHEME)
+}
+
+/// Error of a connection to a server missing from `~/.ssh/known_hosts`.
+/// The user checks its fingerprint before it is trusted.
+#[derive(Clone, Debug)]
+pub struct UnknownHostKey {
+    /// Root of the server, as returned by `SftpLocation::root`.
+    pub root: PathBuf,
+    pub host: String,
+
+    /// `SHA256:...`, as printed by `ssh-keygen -l`.
+    pub fingerprint: String,
+}
+
+impl UnknownHostKey {
+    /// The unknown key `err` was caused by, if any.
+    pub fn from_error(err: &io::Error) -> Option<&Self> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for UnknownHostKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the host key of {} is unknown ({})",
+            self.host, self.fingerprint
+        )
+    }
+}
+
+impl std::error::Error for UnknownHostKey {}
+
+/// Base64 without padding, the encoding OpenSSH displays fingerprints in.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (idx, byte)| {
+            word | ((*byte as u32) << (16 - idx * 8))
+        });
+
+        // 3 bytes make 4 characters, a partial chunk one more character than its length
+        for idx in 0..=chunk.len() {
+            encoded.push(ALPHABET[(word >> (18 - idx * 6)) as usize & 0x3f] as char);
+        }
+    }
+
+    encoded
+}
+
+/// A directory tree on a remote machine, reached over SSH.
+///
+/// Authentication uses the ssh-agent first, then the default keys inside `~/.ssh`.
+pub struct SftpVfs {
+    location: SftpLocation,
+    root: PathBuf,
+
+    /// Kept alive for as long as the sftp channel is used.
+    _session: Session,
+    sftp: Sftp,
+
+    /// Every round trip goes over the network, while the UI asks for
+    /// metadata of each visible file every frame.
+    stat_cache: RefCell<HashMap<PathBuf, VfsMetadata>>,
+
+    /// Listings are asked for every frame too, e.g. by the breadcrumb menus.
+    list_cache: RefCell<HashMap<PathBuf, Vec<PathBuf>>>,
+
+    /// Targets of symbolic links, None for files known not to be one.
+    link_cache: RefCell<HashMap<PathBuf, Option<PathBuf>>>,
+}
+
+impl SftpVfs {
+    /// Opens a session, blocking until the server answered or `CONNECT_TIMEOUT` elapsed.
+    ///
+    /// A server missing from `~/.ssh/known_hosts` fails with an `UnknownHostKey`,
+    /// unless its fingerprint is `trusted_fingerprint` (it is then remembered).
+    pub fn connect(location: &SftpLocation, trusted_fingerprint: Option<&str>) -> io::Result<Self> {
+        let tcp = Self::connect_tcp(location)?;
+        tcp.set_read_timeout(Some(SESSION_TIMEOUT))?;
+        tcp.set_write_timeout(Some(SESSION_TIMEOUT))?;
+
+        let mut session = Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(SESSION_TIMEOUT.as_millis() as u32);
+        session.handshake()?;
+
+        Self::verify_host_key(&session, location, trusted_fingerprint)?;
+        Self::authenticate(&session, &location.user)?;
+
+        let sftp = session.sftp()?;
+
+        Ok(Self {
+            location: location.clone(),
+            root: location.root(),
+            _session: session,
+            sftp,
+            stat_cache: RefCell::new(HashMap::new()),
+            list_cache: RefCell::new(HashMap::new()),
+            link_cache: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Tries every address of the host, each one for `CONNECT_TIMEOUT` at most.
+    fn connect_tcp(location: &SftpLocation) -> io::Result<TcpStream> {
+        let mut last_error = None;
+        for address in (location.host.as_str(), location.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(tcp) => return Ok(tcp),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} has no address", location.host),
+            )
+        }))
+    }
+
+    /// Refuses to talk to a server whose key changed since it was added to `~/.ssh/known_hosts`.
+    /// Unknown servers are remembered once the user trusted their fingerprint.
+    fn verify_host_key(
+        session: &Session,
+        location: &SftpLocation,
+        trusted_fingerprint: Option<&str>,
+    ) -> io::Result<()> {
+        let Some((key, key_type)) = session.host_key() else {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "the server did not send a host key",
+            ));
+        };
+
+        // Without a home folder no key is known, every server is confirmed by the user
+        let known_hosts_file = home_dir().map(|home| home.join(".ssh").join("known_hosts"));
+
+        let mut known_hosts = session.known_hosts()?;
+        if let Some(known_hosts_file) = &known_hosts_file {
+            let _ = known_hosts.read_file(known_hosts_file, KnownHostFileKind::OpenSSH);
+        }
+
+        match known_hosts.check_port(&location.host, location.port, key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::Mismatch => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "the host key of {} does not match the one in {:?}",
+                    location.host,
+                    known_hosts_file.unwrap_or_default()
+                ),
+            )),
+            CheckResult::NotFound | CheckResult::Failure => {
+                let fingerprint = session
+                    .host_key_hash(HashType::Sha256)
+                    .map(|hash| format!("SHA256:{}", base64(hash)))
+                    .unwrap_or_default();
+                if fingerprint.is_empty() || trusted_fingerprint != Some(fingerprint.as_str()) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        UnknownHostKey {
+                            root: location.root(),
+                            host: location.host.clone(),
+                            fingerprint,
+                        },
+                    ));
+                }
+
+                // Trusted for this session only
+                let Some(known_hosts_file) = known_hosts_file else {
+                    return Ok(());
+                };
+
+                let host = if location.port == DEFAULT_SFTP_PORT {
+                    location.host.clone()
+                } else {
+                    format!("[{}]:{}", location.host, location.port)
+                };
+
+                known_hosts.add(&host, key, "added by rocket", key_type.into())?;
+                if let Err(err) =
+                    known_hosts.write_file(&known_hosts_file, KnownHostFileKind::OpenSSH)
+                {
+                    eprintln!(
+                        "[SftpVfs->verify_host_key()->write_file()] Cannot update {:?}: {}",
+                        known_hosts_file, err
+                    );
+                }
+
+                Ok(())
+            }
+        }
+    }
+
+    fn authenticate(session: &Session, user: &str) -> io::Result<()> {
+        if session.userauth_agent(user).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+
+        if let Some(ssh_dir) = home_dir().map(|home| home.join(".ssh")) {
+            for key_name in ["id_ed25519", "id_ecdsa", "id_rsa"] {
+                let private_key = ssh_dir.join(key_name);
+                if !private_key.is_file() {
+                    continue;
+                }
+
+                let public_key = private_key.with_extension("pub");
+                let public_key = public_key.is_file().then_some(public_key.as_path());
+
+                if session
+                    .userauth_pubkey_file(user, public_key, &private_key, None)
+                    .is_ok()
+                    && session.authenticated()
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("no ssh key in the agent or in ~/.ssh was accepted for {}", user),
+        ))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Does `path` belong to this server?
+    pub fn serves(&self, path: &Path) -> bool {
+        SftpLocation::parse(path).is_some_and(|location| location.same_server(&self.location))
+    }
+
+    pub fn refresh(&self) {
+        self.stat_cache.borrow_mut().clear();
+        self.list_cache.borrow_mut().clear();
+        self.link_cache.borrow_mut().clear();
+    }
+
+    /// Forgets the listing of the folder `path` is created in.
+    fn forget_parent(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            self.list_cache.borrow_mut().remove(parent);
+        }
+    }
+
+    fn remote_path(&self, path: &Path) -> io::Result<PathBuf> {
+        SftpLocation::parse(path)
+            .filter(|location| location.same_server(&self.location))
+            .map(|location| location.path)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:?} is not served by {:?}", path, self.root),
+                )
+            })
+    }
+
+    fn metadata_from_stat(stat: &ssh2::FileStat) -> VfsMetadata {
+        let time = |secs: Option<u64>| {
+            secs.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+        };
+
+        VfsMetadata {
+            is_dir: stat.is_dir(),
+            len: stat.size.unwrap_or(0),
+            readonly: stat.perm.is_some_and(|perm| perm & 0o222 == 0),
+            created: None,
+            accessed: time(stat.atime),
+            modified: time(stat.mtime),
+        }
+    }
+
+    fn remove_remote(&self, remote: &Path) -> io::Result<()> {
+        let stat = self.sftp.lstat(remote)?;
+        if !stat.is_dir() {
+            return Ok(self.sftp.unlink(remote)?);
+        }
+
+        for (child, _) in self.sftp.readdir(remote)? {
+            self.remove_remote(&child)?;
+        }
+
+        Ok(self.sftp.rmdir(remote)?)
+    }
+}
+
+impl Vfs for SftpVfs {
+    fn list(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        if let Some(children) = self.list_cache.borrow().get(dir) {
+            return Ok(children.clone());
+        }
+
+        let remote = self.remote_path(dir)?;
+        let mut stat_cache = self.stat_cache.borrow_mut();
+        let mut link_cache = self.link_cache.borrow_mut();
+
+        let children: Vec<PathBuf> = self
+            .sftp
+            .readdir(&remote)?
+            .iter()
+            .filter_map(|(remote_child, stat)| {
+                let path = dir.join(remote_child.file_name()?);
+                stat_cache.insert(path.clone(), Self::metadata_from_stat(stat));
+                // Listings do not follow links, so only links need to be read later
+                if !stat.file_type().is_symlink() {
+                    link_cache.insert(path.clone(), None);
+                }
+                Some(path)
+            })
+            .collect();
+        self.list_cache
+            .borrow_mut()
+            .insert(dir.to_path_buf(), children.clone());
+
+        Ok(children)
+    }
+
+    fn stat(&self, path: &Path) -> io::Result<VfsMetadata> {
+        if let Some(metadata) = self.stat_cache.borrow().get(path) {
+            return Ok(metadata.clone());
+        }
+
+        let metadata = Self::metadata_from_stat(&self.sftp.stat(&self.remote_path(path)?)?);
+        self.stat_cache
+            .borrow_mut()
+            .insert(path.to_path_buf(), metadata.clone());
+
+        Ok(metadata)
+    }
+
+    fn open(&self, path: &Path) -> io::Result<Box<dyn Read>> {
+        Ok(Box::new(self.sftp.open(&self.remote_path(path)?)?))
+    }
+
+    fn create(&self, path: &Path) -> io::Result<Box<dyn Write>> {
+        self.stat_cache.borrow_mut().remove(path);
+        self.forget_parent(path);
+        Ok(Box::new(self.sftp.create(&self.remote_path(path)?)?))
+    }
+
+    fn create_dir(&self, path: &Path) -> io::Result<()> {
+        self.stat_cache.borrow_mut().remove(path);
+        self.forget_parent(path);
+        Ok(self.sftp.mkdir(&self.remote_path(path)?, 0o755)?)
+    }
+
+    fn read_link(&self, path: &Path) -> Option<PathBuf> {
+        if let Some(target) = self.link_cache.borrow().get(path) {
+            return target.clone();
+        }
+
+        let target = self
+            .remote_path(path)
+            .ok()
+            .and_then(|remote| self.sftp.readlink(&remote).ok());
+        self.link_cache
+            .borrow_mut()
+            .insert(path.to_path_buf(), target.clone());
+
+        target
+    }
+
+    fn symlink(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.refresh();
+        // ssh2 names the target `path` and the link `target`
+        Ok(self.sftp.symlink(target, &self.remote_path(link)?)?)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.refresh();
+        Ok(self
+            .sftp
+            .rename(&self.remote_path(from)?, &self.remote_path(to)?, None)?)
+    }
+
+    fn remove(&self, path: &Path) -> io::Result<()> {
+        self.refresh();
+        self.remove_remote(&self.remote_path(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_url() {
+        let location =
+            SftpLocation::parse(Path::new("sftp://build@ci.local:2222/srv/out")).unwrap();
+
+        assert_eq!(location.user, "build");
+        assert_eq!(location.host, "ci.local");
+        assert_eq!(location.port, 2222);
+        assert_eq!(location.path, PathBuf::from("/srv/out"));
+    }
+
+    #[test]
+    fn parses_url_rebuilt_from_path_components() {
+        let mut rebuilt = PathBuf::new();
+        for component in Path::new("sftp://build@ci.local/srv").components() {
+            rebuilt.push(component);
+        }
+
+        let location = SftpLocation::parse(&rebuilt).unwrap();
+        assert_eq!(location.host, "ci.local");
+        assert_eq!(location.port, DEFAULT_SFTP_PORT);
+        assert_eq!(location.path, PathBuf::from("/srv"));
+    }
+
+    #[test]
+    fn encodes_fingerprints_like_openssh() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg");
+        assert_eq!(base64(b"fo"), "Zm8");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff, 0xfe]), "//4");
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        assert!(SftpLocation::parse(Path::new("/home/user")).is_none());
+        assert!(SftpLocation::parse(Path::new("ftp://user@host/")).is_none());
+    }
+
+    /// Runs against a throwaway sshd, for example:
+    /// `docker run -p 2222:2222 -e PUBLIC_KEY="$(cat ~/.ssh/id_ed25519.pub)" -e USER_NAME=rocket linuxserver/openssh-server`
+    /// then `ROCKET_SFTP_TEST_URL=sftp://rocket@localhost:2222/tmp cargo test -- --ignored`
+    /// (`ssh -p 2222 rocket@localhost` once first, so the server is inside `~/.ssh/known_hosts`)
+    #[test]
+    #[ignore]
+    fn round_trip_against_local_sshd() {
+        let url = env::var("ROCKET_SFTP_TEST_URL").expect("ROCKET_SFTP_TEST_URL is not set");
+        let location = SftpLocation::parse(Path::new(&url)).unwrap();
+        let vfs = SftpVfs::connect(&location, None).unwrap();
+
+        let dir = PathBuf::from(&url).join("rocket_sftp_test");
+        let _ = vfs.remove(&dir);
+        vfs.create_dir(&dir).unwrap();
+
+        let file = dir.join("hello.txt");
+        vfs.create(&file).unwrap().write_all(b"hello").unwrap();
+        assert_eq!(vfs.list(&dir).unwrap(), vec![file.clone()]);
+        assert_eq!(vfs.stat(&file).unwrap().len, 5);
+
+        let renamed = dir.join("renamed.txt");
+        vfs.rename(&file, &renamed).unwrap();
+        let mut content = String::new();
+        vfs.open(&renamed).unwrap().read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+
+        vfs.remove(&dir).unwrap();
+        assert!(vfs.stat(&dir).is_err());
+    }
+}
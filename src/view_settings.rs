@@ -0,0 +1,97 @@
+use crate::vfs::{Vfs, VfsMetadata};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ViewMode {
+    List,
+    Grid,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+    Extension,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 4] = [
+        SortKey::Name,
+        SortKey::Size,
+        SortKey::Modified,
+        SortKey::Extension,
+    ];
+}
+
+/// How the content of a directory is displayed.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ViewSettings {
+    pub view_mode: ViewMode,
+    pub sort_key: SortKey,
+    pub ascending: bool,
+}
+
+impl Default for ViewSettings {
+    fn default() -> Self {
+        Self {
+            view_mode: ViewMode::List,
+            sort_key: SortKey::Name,
+            ascending: true,
+        }
+    }
+}
+
+impl ViewSettings {
+    /// Sorts `entries` by sort_key, folders are always listed before files.
+    pub fn sort(&self, entries: &mut Vec<PathBuf>, vfs: &dyn Vfs) {
+        let mut keyed: Vec<(PathBuf, Option<VfsMetadata>, String)> = entries
+            .drain(..)
+            .map(|entry| {
+                let metadata = vfs.stat(&entry).ok();
+                let name = entry
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                (entry, metadata, name)
+            })
+            .collect();
+
+        keyed.sort_by(|(a_path, a_metadata, a_name), (b_path, b_metadata, b_name)| {
+            let a_is_dir = a_metadata.as_ref().is_some_and(|metadata| metadata.is_dir);
+            let b_is_dir = b_metadata.as_ref().is_some_and(|metadata| metadata.is_dir);
+
+            let ordering = match self.sort_key {
+                SortKey::Name => a_name.cmp(b_name),
+                SortKey::Size => {
+                    let a_len = a_metadata.as_ref().map(|metadata| metadata.len);
+                    let b_len = b_metadata.as_ref().map(|metadata| metadata.len);
+                    a_len.cmp(&b_len)
+                }
+                SortKey::Modified => {
+                    let a_modified = a_metadata.as_ref().and_then(|metadata| metadata.modified);
+                    let b_modified = b_metadata.as_ref().and_then(|metadata| metadata.modified);
+                    a_modified.cmp(&b_modified)
+                }
+                SortKey::Extension => a_path.extension().cmp(&b_path.extension()),
+            }
+            .then_with(|| a_name.cmp(b_name));
+
+            let ordering = if self.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            };
+
+            match (a_is_dir, b_is_dir) {
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                _ => ordering,
+            }
+        });
+
+        entries.extend(keyed.into_iter().map(|(entry, _, _)| entry));
+    }
+}
@@ -0,0 +1,16 @@
+use std::env;
+use std::path::PathBuf;
+
+/// `$XDG_DATA_HOME` then every `$XDG_DATA_DIRS` entry, most important first.
+/// Applications, the MIME database and icon themes are looked up in their subdirectories.
+pub fn data_dirs() -> Vec<PathBuf> {
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|data_dirs| !data_dirs.is_empty())
+        .unwrap_or_else(|| String::from("/usr/local/share:/usr/share"));
+
+    dirs::data_dir()
+        .into_iter()
+        .chain(env::split_paths(&data_dirs))
+        .collect()
+}